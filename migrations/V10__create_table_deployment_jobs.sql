@@ -0,0 +1,19 @@
+ALTER TABLE accounts ADD COLUMN deployment_status TEXT NOT NULL DEFAULT 'PENDING';
+
+-- wallets of existing accounts were deployed while the account was created
+UPDATE accounts SET deployment_status = 'DEPLOYED';
+
+CREATE TABLE IF NOT EXISTS deployment_jobs (
+    id               TEXT    PRIMARY KEY,
+    account_id       TEXT    NOT NULL,
+    paymaster_tokens TEXT        NULL,
+    status           TEXT    NOT NULL,
+    attempts         INTEGER NOT NULL,
+    next_attempt_at  INTEGER NOT NULL,
+    last_error       TEXT        NULL,
+    created_at       INTEGER NOT NULL,
+    updated_at       INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS deployment_jobs_status ON deployment_jobs (status, next_attempt_at);
+CREATE INDEX IF NOT EXISTS deployment_jobs_account_id ON deployment_jobs (account_id);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS treasury_fundings (
+    id               TEXT    PRIMARY KEY,
+    account_id       TEXT    NOT NULL,
+    recipient        TEXT    NOT NULL,
+    amount           TEXT    NOT NULL,
+    transaction_hash TEXT        NULL,
+    status           TEXT    NOT NULL,
+    error            TEXT        NULL,
+    created_at       INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS treasury_fundings_account_id ON treasury_fundings (account_id);
+CREATE INDEX IF NOT EXISTS treasury_fundings_created_at ON treasury_fundings (created_at);
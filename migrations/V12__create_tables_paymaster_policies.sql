@@ -0,0 +1,24 @@
+CREATE TABLE IF NOT EXISTS paymaster_policies (
+    id              TEXT    PRIMARY KEY,
+    account_id      TEXT        NULL,
+    gas_budget      TEXT        NULL,
+    allowed_targets TEXT        NULL,
+    valid_from      INTEGER     NULL,
+    valid_until     INTEGER     NULL,
+    active          INTEGER NOT NULL,
+    created_at      INTEGER NOT NULL,
+    updated_at      INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS paymaster_policies_account_id ON paymaster_policies (account_id);
+
+CREATE TABLE IF NOT EXISTS paymaster_sponsorships (
+    id           TEXT    PRIMARY KEY,
+    policy_id    TEXT    NOT NULL,
+    account_id   TEXT    NOT NULL,
+    user_op_hash TEXT    NOT NULL,
+    max_gas_cost TEXT    NOT NULL,
+    created_at   INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS paymaster_sponsorships_policy_id_account_id ON paymaster_sponsorships (policy_id, account_id);
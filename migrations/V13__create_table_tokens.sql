@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS tokens (
+    id                  TEXT    PRIMARY KEY,
+    address             TEXT    NOT NULL,
+    chain_id            INTEGER NOT NULL,
+    symbol              TEXT        NULL,
+    decimals            INTEGER NOT NULL,
+    paymaster_supported INTEGER NOT NULL,
+    created_at          INTEGER NOT NULL,
+    updated_at          INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS tokens_address_chain_id ON tokens (address, chain_id);
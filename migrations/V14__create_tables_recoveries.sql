@@ -0,0 +1,25 @@
+CREATE TABLE IF NOT EXISTS recoveries (
+    id            TEXT    PRIMARY KEY,
+    account_id    TEXT    NOT NULL,
+    new_owner     TEXT    NOT NULL,
+    status        TEXT    NOT NULL,
+    executable_at INTEGER NOT NULL,
+    user_op_hash  TEXT        NULL,
+    last_error    TEXT        NULL,
+    created_at    INTEGER NOT NULL,
+    updated_at    INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS recoveries_account_id ON recoveries (account_id);
+CREATE INDEX IF NOT EXISTS recoveries_status ON recoveries (status, executable_at);
+
+CREATE TABLE IF NOT EXISTS recovery_approvals (
+    id          TEXT    PRIMARY KEY,
+    recovery_id TEXT    NOT NULL,
+    guardian_id TEXT    NOT NULL,
+    signer      TEXT    NOT NULL,
+    signature   TEXT    NOT NULL,
+    created_at  INTEGER NOT NULL
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS recovery_approvals_recovery_id_guardian_id ON recovery_approvals (recovery_id, guardian_id);
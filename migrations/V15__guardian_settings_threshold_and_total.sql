@@ -0,0 +1,33 @@
+-- signing strategies were stored by name, like 'TwoOfThree', and become a threshold and a total
+CREATE TABLE IF NOT EXISTS guardian_settings_m_of_n (
+    id         TEXT    PRIMARY KEY,
+    account_id TEXT    NOT NULL,
+    threshold  INTEGER NOT NULL,
+    total      INTEGER NOT NULL
+);
+
+INSERT INTO guardian_settings_m_of_n (id, account_id, threshold, total)
+SELECT
+    id,
+    account_id,
+    CASE signers
+        WHEN 'TwoOfTwo'     THEN 2
+        WHEN 'TwoOfThree'   THEN 2
+        WHEN 'ThreeOfThree' THEN 3
+        ELSE 1
+    END,
+    CASE signers
+        WHEN 'OneOfTwo'     THEN 2
+        WHEN 'TwoOfTwo'     THEN 2
+        WHEN 'OneOfThree'   THEN 3
+        WHEN 'TwoOfThree'   THEN 3
+        WHEN 'ThreeOfThree' THEN 3
+        ELSE 1
+    END
+FROM guardian_settings;
+
+DROP TABLE guardian_settings;
+
+ALTER TABLE guardian_settings_m_of_n RENAME TO guardian_settings;
+
+CREATE INDEX IF NOT EXISTS guardian_settings_account_id ON guardian_settings (account_id);
@@ -0,0 +1,11 @@
+ALTER TABLE guardians ADD COLUMN verified_address TEXT NULL;
+ALTER TABLE guardians ADD COLUMN verified_at INTEGER NULL;
+
+CREATE TABLE IF NOT EXISTS guardian_challenges (
+    nonce         TEXT    PRIMARY KEY,
+    guardian_id   TEXT    NOT NULL,
+    nomination_id TEXT    NOT NULL,
+    created_at    INTEGER NOT NULL,
+    expires_at    INTEGER NOT NULL,
+    consumed_at   INTEGER     NULL
+);
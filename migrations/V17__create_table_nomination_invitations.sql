@@ -0,0 +1,12 @@
+CREATE TABLE IF NOT EXISTS nomination_invitations (
+    id            TEXT    PRIMARY KEY,
+    nomination_id TEXT    NOT NULL,
+    email         TEXT    NOT NULL,
+    kind          TEXT    NOT NULL,
+    created_at    INTEGER NOT NULL,
+    expires_at    INTEGER NOT NULL,
+    consumed_at   INTEGER     NULL
+);
+
+CREATE INDEX IF NOT EXISTS nomination_invitations_nomination_id ON nomination_invitations (nomination_id);
+CREATE INDEX IF NOT EXISTS nomination_invitations_email_created_at ON nomination_invitations (email, created_at);
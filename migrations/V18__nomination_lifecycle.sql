@@ -0,0 +1,32 @@
+ALTER TABLE nominations ADD COLUMN expires_at INTEGER NULL;
+
+-- an account nominates a guardian once at a time, keep the first accepted nomination and
+-- cancel the others, then the first pending one when none was accepted
+UPDATE nominations SET status = 'CANCELLED'
+WHERE status = 'ACCEPTED' AND EXISTS (
+    SELECT 1 FROM nominations other
+    WHERE other.account_id = nominations.account_id
+      AND other.guardian_id = nominations.guardian_id
+      AND other.status = 'ACCEPTED'
+      AND other.id < nominations.id
+);
+UPDATE nominations SET status = 'CANCELLED'
+WHERE status = 'PENDING' AND EXISTS (
+    SELECT 1 FROM nominations other
+    WHERE other.account_id = nominations.account_id
+      AND other.guardian_id = nominations.guardian_id
+      AND (other.status = 'ACCEPTED' OR (other.status = 'PENDING' AND other.id < nominations.id))
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS nominations_account_id_guardian_id_live ON nominations (account_id, guardian_id) WHERE status IN ('PENDING', 'ACCEPTED');
+CREATE INDEX IF NOT EXISTS nominations_status_expires_at ON nominations (status, expires_at);
+
+CREATE TABLE IF NOT EXISTS nomination_history (
+    id            TEXT    PRIMARY KEY,
+    nomination_id TEXT    NOT NULL,
+    from_status   TEXT        NULL,
+    to_status     TEXT    NOT NULL,
+    created_at    INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS nomination_history_nomination_id ON nomination_history (nomination_id);
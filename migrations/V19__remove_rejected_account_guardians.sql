@@ -0,0 +1,15 @@
+-- rejecting a nomination used to make the guardian available to the account as well
+DELETE FROM account_guardians
+WHERE status = 'AVAILABLE'
+  AND NOT EXISTS (
+    SELECT 1 FROM nominations
+    WHERE nominations.account_id = account_guardians.account_id
+      AND nominations.guardian_id = account_guardians.guardian_id
+      AND nominations.status = 'ACCEPTED'
+  )
+  AND EXISTS (
+    SELECT 1 FROM nominations
+    WHERE nominations.account_id = account_guardians.account_id
+      AND nominations.guardian_id = account_guardians.guardian_id
+      AND nominations.status = 'REJECTED'
+  );
@@ -0,0 +1,6 @@
+ALTER TABLE verifications ADD COLUMN created_at INTEGER NOT NULL DEFAULT 0;
+
+-- codes were valid for a minute from when they were sent
+UPDATE verifications SET created_at = expires_at - 60000;
+
+CREATE INDEX IF NOT EXISTS verifications_email_created_at ON verifications (email, created_at);
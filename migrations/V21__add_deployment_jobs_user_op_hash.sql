@@ -0,0 +1,3 @@
+ALTER TABLE deployment_jobs ADD COLUMN user_op_hash TEXT NULL;
+
+CREATE INDEX IF NOT EXISTS deployment_jobs_user_op_hash ON deployment_jobs (user_op_hash);
@@ -0,0 +1 @@
+CREATE INDEX IF NOT EXISTS recoveries_user_op_hash ON recoveries (user_op_hash);
@@ -0,0 +1 @@
+ALTER TABLE accounts ADD COLUMN eoa_private_data_key TEXT NOT NULL DEFAULT '';
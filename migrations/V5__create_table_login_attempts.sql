@@ -0,0 +1,11 @@
+ALTER TABLE verifications ADD COLUMN consumed_at INTEGER NULL;
+
+CREATE TABLE IF NOT EXISTS login_attempts (
+    id         TEXT    PRIMARY KEY,
+    email      TEXT    NOT NULL,
+    account_id TEXT        NULL,
+    method     TEXT    NOT NULL,
+    success    INTEGER NOT NULL,
+    reason     TEXT        NULL,
+    created_at INTEGER NOT NULL
+);
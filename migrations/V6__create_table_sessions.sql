@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS sessions (
+    id                 TEXT    PRIMARY KEY,
+    account_id         TEXT    NOT NULL,
+    refresh_token_hash TEXT    NOT NULL,
+    device             TEXT        NULL,
+    user_agent         TEXT        NULL,
+    created_at         INTEGER NOT NULL,
+    last_seen_at       INTEGER NOT NULL,
+    expires_at         INTEGER NOT NULL,
+    revoked_at         INTEGER     NULL
+);
+
+CREATE INDEX IF NOT EXISTS sessions_refresh_token_hash ON sessions (refresh_token_hash);
@@ -0,0 +1,6 @@
+CREATE TABLE IF NOT EXISTS siwe_nonces (
+    nonce       TEXT    PRIMARY KEY,
+    created_at  INTEGER NOT NULL,
+    expires_at  INTEGER NOT NULL,
+    consumed_at INTEGER     NULL
+);
@@ -0,0 +1,21 @@
+CREATE TABLE IF NOT EXISTS user_operations (
+    id                       TEXT    PRIMARY KEY,
+    account_id               TEXT    NOT NULL,
+    user_op_hash             TEXT    NOT NULL,
+    sender                   TEXT    NOT NULL,
+    nonce                    TEXT    NOT NULL,
+    call_data_summary        TEXT    NOT NULL,
+    call_gas_limit           TEXT    NOT NULL,
+    verification_gas_limit   TEXT    NOT NULL,
+    pre_verification_gas     TEXT    NOT NULL,
+    max_fee_per_gas          TEXT    NOT NULL,
+    max_priority_fee_per_gas TEXT    NOT NULL,
+    paymaster                TEXT        NULL,
+    bundler_response         TEXT        NULL,
+    status                   TEXT    NOT NULL,
+    created_at               INTEGER NOT NULL,
+    updated_at               INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS user_operations_account_id ON user_operations (account_id, created_at);
+CREATE INDEX IF NOT EXISTS user_operations_user_op_hash ON user_operations (user_op_hash);
@@ -0,0 +1,5 @@
+ALTER TABLE user_operations ADD COLUMN transaction_hash TEXT NULL;
+ALTER TABLE user_operations ADD COLUMN block_number INTEGER NULL;
+ALTER TABLE user_operations ADD COLUMN actual_gas_cost TEXT NULL;
+
+CREATE INDEX IF NOT EXISTS user_operations_status ON user_operations (status);
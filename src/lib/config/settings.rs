@@ -27,8 +27,73 @@ pub struct Wallet {
     private: String,
     rpc: String,
     bundler_url: String,
-    default_max_fee: String,
-    default_max_priority_fee: String,
+    master_key: String,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Gas {
+    pub blocks: u64,
+    pub percentile: f64,
+    pub multiplier: f64,
+    pub floor: String,
+    pub ceiling: String,
+    pub cache_ttl_ms: i64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Treasury {
+    pub reserve: String,
+    pub per_account_cap: String,
+    pub daily_cap: String,
+    pub low_balance_threshold: String,
+    pub alert_webhook_url: Option<String>,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Paymaster {
+    pub verifying_paymaster: Option<String>,
+    signer: Option<String>,
+    pub validity_secs: u64,
+}
+
+impl Paymaster {
+    pub fn signer(&self) -> anyhow::Result<Option<String>> {
+        self.signer
+            .clone()
+            .map(|key| match key.as_str() {
+                "secret" => SECRETS.get("paymaster:signer").map_err(|e| {
+                    anyhow::anyhow!("Missing paymaster:signer in the SecureStore vault: {}", e)
+                }),
+                key => Ok(key.to_string()),
+            })
+            .transpose()
+    }
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Recovery {
+    pub timelock_secs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Invitation {
+    pub url: String,
+    pub template_id: i64,
+    pub ttl_secs: u64,
+    pub reminder_secs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Nomination {
+    pub ttl_secs: u64,
+}
+
+#[derive(Clone, Debug, Deserialize)]
+pub struct Token {
+    pub address: String,
+    pub symbol: String,
+    pub decimals: u8,
+    pub paymaster: bool,
 }
 
 #[derive(Clone, Debug, Deserialize)]
@@ -76,6 +141,12 @@ impl Jwt {
     }
 }
 
+#[derive(Clone, Debug, Deserialize)]
+#[allow(unused)]
+pub struct Siwe {
+    pub domain: String,
+}
+
 #[derive(Clone, Debug, Deserialize)]
 #[allow(unused)]
 pub struct Contracts {
@@ -127,6 +198,15 @@ pub struct Settings {
     pub jwt: Jwt,
     pub secrets: Secrets,
     pub wallet: Wallet,
+    pub gas: Gas,
+    pub treasury: Treasury,
+    pub paymaster: Paymaster,
+    pub recovery: Recovery,
+    pub invitation: Invitation,
+    pub nomination: Nomination,
+    #[serde(default)]
+    pub tokens: Vec<Token>,
+    pub siwe: Siwe,
     pub contracts: Contracts
 }
 
@@ -155,11 +235,13 @@ impl Settings {
         self.wallet.bundler_url.clone()
     }
 
-    pub fn default_max_fee(&'_ self) -> String {
-        self.wallet.default_max_fee.clone()
-    }
-    pub fn default_max_priority_fee(&'_ self) -> String {
-        self.wallet.default_max_priority_fee.clone()
+    pub fn wallet_master_key(&'_ self) -> anyhow::Result<String> {
+        match self.wallet.master_key.clone().as_str() {
+            "secret" => SECRETS.get("wallet:master_key").map_err(|e| {
+                anyhow::anyhow!("Missing wallet:master_key in the SecureStore vault: {}", e)
+            }),
+            key => Ok(key.to_string()),
+        }
     }
 }
 
@@ -167,6 +249,7 @@ pub enum Env {
     Mumbai,
     Local,
     Prod,
+    Test,
 }
 
 impl Settings {
@@ -175,6 +258,7 @@ impl Settings {
             Env::Mumbai => "mumbai".into(),
             Env::Local => "local".into(),
             Env::Prod => "prod".into(),
+            Env::Test => "test".into(),
         });
         
         let s = Config::builder()
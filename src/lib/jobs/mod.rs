@@ -0,0 +1,5 @@
+pub mod nomination_expiry;
+pub mod nomination_reminders;
+pub mod receipt_tracker;
+pub mod recovery;
+pub mod wallet_deployment;
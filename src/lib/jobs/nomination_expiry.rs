@@ -0,0 +1,28 @@
+use crate::{
+    operations::nomination::{transition, EXPIRED},
+    repos::{db::AppState, nomination_repo},
+};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Expires pending nominations past their ttl in the background until the server stops.
+pub async fn run(app_state: AppState) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = app_state.clock.now_ms();
+        if let Err(e) = expire_nominations(&app_state, now).await {
+            log::warn!("Error expiring nominations: {}", e);
+        }
+    }
+}
+
+// a nomination answered or cancelled since it was read is left as it is
+pub async fn expire_nominations(app_state: &AppState, now: i64) -> anyhow::Result<()> {
+    let db = &app_state.database;
+    for nomination in nomination_repo::find_all_expired(db, now).await? {
+        transition(db, &nomination, EXPIRED, now).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,65 @@
+use crate::{
+    operations::{
+        invitation::{send_invitation, REMINDER},
+        nomination::{current_status, PENDING},
+    },
+    repos::{account_repo, db::AppState, nomination_invitation_repo, nomination_repo},
+    routes::verification_api::{check_send_limit, SEND_LIMIT_WINDOW_MS},
+};
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Reminds nominated guardians of pending nominations in the background until the server stops.
+pub async fn run(app_state: AppState) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = app_state.clock.now_ms();
+        if let Err(e) = send_reminders(&app_state, now).await {
+            log::warn!("Error sending nomination reminders: {}", e);
+        }
+    }
+}
+
+// a pending nomination gets a reminder once its last invitation is older than reminder_secs,
+// within the same send limit as resends, unless it expired
+pub async fn send_reminders(app_state: &AppState, now: i64) -> anyhow::Result<()> {
+    let db = &app_state.database;
+    let remind_before = now - app_state.settings.invitation.reminder_secs as i64 * 1000;
+
+    for nomination in nomination_repo::find_all_by_status(db, PENDING.to_string()).await? {
+        if current_status(&nomination, now) != PENDING {
+            continue;
+        }
+        let last_sent =
+            nomination_invitation_repo::find_latest_by_nomination_id(db, nomination.id.clone())
+                .await?;
+        if !last_sent.map_or(false, |invitation| invitation.created_at <= remind_before) {
+            continue;
+        }
+
+        let email_count = nomination_invitation_repo::count_by_email_created_after(
+            db,
+            &nomination.email,
+            now - SEND_LIMIT_WINDOW_MS,
+        )
+        .await?;
+        if let Err(e) = check_send_limit(
+            &app_state.settings,
+            "Nomination invitation",
+            &nomination.email,
+            email_count,
+        ) {
+            log::info!("Skipping nomination reminder: {}", e);
+            continue;
+        }
+
+        let nominator = match account_repo::find_by_id(db, nomination.account_id.clone()).await? {
+            Some(account) => account.email,
+            None => continue,
+        };
+        send_invitation(app_state, &nomination, &nominator, REMINDER, now).await?;
+    }
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use crate::{
+    jobs::{recovery::settle_recovery, wallet_deployment::settle_deployment},
+    operations::bundler::{get_block_number, get_user_operation_receipt},
+    repos::{db::AppState, user_operation_repo},
+};
+use sea_orm::DatabaseConnection;
+use std::time::Duration;
+
+pub const PENDING: &str = "PENDING";
+pub const INCLUDED: &str = "INCLUDED";
+pub const SUCCESS: &str = "SUCCESS";
+pub const REVERTED: &str = "REVERTED";
+pub const DROPPED: &str = "DROPPED";
+pub const FAILED: &str = "FAILED";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(15);
+const CONFIRMATIONS: u64 = 3;
+const DROP_AFTER_MS: i64 = 1000 * 60 * 30; // 30 minutes
+
+/// Polls the bundler for receipts of pending user operations until the server stops.
+pub async fn run(app_state: AppState) {
+    let settings = &app_state.settings;
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = app_state.clock.now_ms();
+        if let Err(e) = track_receipts(
+            &app_state.database,
+            &settings.bundler(),
+            &settings.rpc(),
+            now,
+        )
+        .await
+        {
+            log::warn!("Error tracking user operation receipts: {}", e);
+        }
+    }
+}
+
+// pending -> included -> success/reverted once the block has enough confirmations,
+// pending -> dropped when the bundler never includes it
+pub async fn track_receipts(
+    db: &DatabaseConnection,
+    bundler: &str,
+    rpc: &str,
+    now: i64,
+) -> anyhow::Result<()> {
+    let user_operations =
+        user_operation_repo::find_all_by_statuses(db, vec![PENDING, INCLUDED]).await?;
+    if user_operations.is_empty() {
+        return Ok(());
+    }
+    let block_number = get_block_number(rpc).await?;
+
+    for user_operation in user_operations {
+        let receipt = match get_user_operation_receipt(bundler, &user_operation.user_op_hash).await
+        {
+            Ok(receipt) => receipt,
+            Err(e) => {
+                log::warn!(
+                    "Error fetching receipt for user operation {}: {}",
+                    user_operation.user_op_hash,
+                    e
+                );
+                continue;
+            }
+        };
+
+        match receipt {
+            Some(receipt) => {
+                let included_in = receipt.receipt.block_number.as_u64();
+                let status = if block_number + 1 < included_in + CONFIRMATIONS {
+                    INCLUDED
+                } else if receipt.success {
+                    SUCCESS
+                } else {
+                    REVERTED
+                };
+                user_operation_repo::update_receipt(
+                    db,
+                    user_operation.id,
+                    status,
+                    format!("{:?}", receipt.receipt.transaction_hash),
+                    included_in as i64,
+                    receipt.actual_gas_cost.to_string(),
+                    now,
+                )
+                .await?;
+                if status != INCLUDED {
+                    settle(db, &user_operation.user_op_hash, status, now).await?;
+                }
+            }
+            // the block it was included in was reorged out
+            None if user_operation.status == INCLUDED => {
+                user_operation_repo::update_status(db, user_operation.id, PENDING, now).await?
+            }
+            None if now - user_operation.created_at > DROP_AFTER_MS => {
+                user_operation_repo::update_status(db, user_operation.id, DROPPED, now).await?;
+                settle(db, &user_operation.user_op_hash, DROPPED, now).await?;
+            }
+            None => {}
+        }
+    }
+    Ok(())
+}
+
+// the jobs that sent a user op move on once it reached its final status
+async fn settle(
+    db: &DatabaseConnection,
+    user_op_hash: &str,
+    status: &str,
+    now: i64,
+) -> anyhow::Result<()> {
+    settle_deployment(db, user_op_hash, status, now).await?;
+    settle_recovery(db, user_op_hash, status, now).await
+}
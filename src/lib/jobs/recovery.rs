@@ -0,0 +1,183 @@
+use crate::{
+    jobs::receipt_tracker,
+    operations::{
+        recovery::{active_approvals, approval_status, guardian_signatures, reset_owner_call_data},
+        user_operation::{store_user_operation, user_op_hash},
+    },
+    repos::{account_repo, db::AppState, recovery_repo},
+};
+use clutch_wallet_lib::utils::wallet_lib::Transaction;
+use ethers::{
+    prelude::*,
+    types::{Address, U256},
+};
+use sea_orm::DatabaseConnection;
+use std::{str::FromStr, time::Duration};
+
+pub const PENDING: &str = "PENDING";
+pub const SUBMITTED: &str = "SUBMITTED";
+pub const EXECUTED: &str = "EXECUTED";
+pub const FAILED: &str = "FAILED";
+pub const CANCELLED: &str = "CANCELLED";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(30);
+const VALIDITY_SECS: u64 = 60 * 60;
+
+/// Rotates the owner key of recovered wallets in the background until the server stops.
+pub async fn run(app_state: AppState) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = app_state.clock.now_ms();
+        if let Err(e) = process_recoveries(&app_state, now).await {
+            log::warn!("Error processing recoveries: {}", e);
+        }
+    }
+}
+
+// a recovery past its timelock waits for enough approvals, the key rotation is sent once and
+// a failed one is kept on the recovery so the owner can start another
+pub async fn process_recoveries(app_state: &AppState, now: i64) -> anyhow::Result<()> {
+    let db = &app_state.database;
+    let recoveries = recovery_repo::find_all_due(db, PENDING, now).await?;
+
+    for recovery in recoveries {
+        let (approvals, required) = approval_status(db, &recovery).await?;
+        if approvals < required {
+            continue;
+        }
+        match rotate_owner(app_state, &recovery, now).await {
+            Ok(user_op_hash) => {
+                recovery_repo::update_status(
+                    db,
+                    recovery.id,
+                    SUBMITTED,
+                    Some(user_op_hash),
+                    None,
+                    now,
+                )
+                .await?;
+            }
+            Err(e) => {
+                log::warn!(
+                    "Error recovering wallet for account {}: {}",
+                    recovery.account_id,
+                    e
+                );
+                recovery_repo::update_status(
+                    db,
+                    recovery.id,
+                    FAILED,
+                    None,
+                    Some(e.to_string()),
+                    now,
+                )
+                .await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Moves a submitted recovery to EXECUTED, handing the account to the new owner, once its user
+/// op succeeded, or to FAILED when it reverted or was dropped.
+pub async fn settle_recovery(
+    db: &DatabaseConnection,
+    user_op_hash: &str,
+    status: &str,
+    now: i64,
+) -> anyhow::Result<()> {
+    let recovery = match recovery_repo::find_by_user_op_hash_and_status(
+        db,
+        user_op_hash.to_string(),
+        SUBMITTED,
+    )
+    .await?
+    {
+        Some(recovery) => recovery,
+        None => return Ok(()),
+    };
+    if status == receipt_tracker::SUCCESS {
+        recovery_repo::update_status(db, recovery.id, EXECUTED, recovery.user_op_hash, None, now)
+            .await?;
+        account_repo::update_recovered_owner(db, recovery.account_id, recovery.new_owner, now).await
+    } else {
+        let error = format!("Recovery user operation {} {}", user_op_hash, status);
+        log::warn!("{} for account {}", error, recovery.account_id);
+        recovery_repo::update_status(
+            db,
+            recovery.id,
+            FAILED,
+            recovery.user_op_hash,
+            Some(error),
+            now,
+        )
+        .await
+    }
+}
+
+// the guardians' signatures authorize the call that hands the wallet to the new owner, the
+// current owner key isn't needed, it may well be the one that was lost
+async fn rotate_owner(
+    app_state: &AppState,
+    recovery: &recovery_repo::Model,
+    now: i64,
+) -> anyhow::Result<String> {
+    let settings = &app_state.settings;
+    let wallet = app_state.wallet.as_ref();
+    let db = &app_state.database;
+    let account = account_repo::find_by_id(db, recovery.account_id.clone())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Account not found {}", recovery.account_id))?;
+    let guardians = guardian_signatures(&active_approvals(db, recovery).await?)?;
+    let wallet_address = Address::from_str(&account.wallet_address)?;
+    let new_owner = Address::from_str(&recovery.new_owner)?;
+    let security_control_module = Address::from_str(&settings.contracts.security_control_module())?;
+
+    let fees = app_state.gas_oracle.fees().await?;
+    let mut user_op = wallet
+        .from_transaction(
+            fees.max_fee_per_gas,
+            fees.max_priority_fee_per_gas,
+            wallet_address,
+            vec![Transaction {
+                to: security_control_module,
+                data: Some(reset_owner_call_data(wallet_address, new_owner, guardians)),
+                value: Some(U256::zero()),
+                gas_limit: None,
+            }],
+        )
+        .await?;
+    wallet.estimate_user_operation_gas(&mut user_op).await?;
+
+    // the wallet lets a call to its security control module through without an owner
+    // signature, the module checks the guardians' instead
+    let valid_after = (now / 1000) as u64;
+    let valid_until = valid_after + VALIDITY_SECS;
+    let (_, validation_data) = wallet
+        .pack_user_op_hash(user_op.clone(), Some(valid_after), Some(valid_until))
+        .await?;
+    user_op.signature = Bytes::from(
+        wallet
+            .pack_user_op_signature(vec![], validation_data)
+            .await?,
+    );
+
+    let entry_point = Address::from_str(&settings.contracts.entry_point())?;
+    let user_op_hash = format!(
+        "{:?}",
+        user_op_hash(&user_op, entry_point, settings.chain_id())
+    );
+    let response = wallet.send_user_operation(user_op.clone()).await?;
+    store_user_operation(
+        db,
+        account.id.clone(),
+        &user_op,
+        user_op_hash.clone(),
+        receipt_tracker::PENDING,
+        format!("{:?}", response),
+        now,
+    )
+    .await?;
+    Ok(user_op_hash)
+}
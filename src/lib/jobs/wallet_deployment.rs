@@ -0,0 +1,293 @@
+use crate::{
+    jobs::receipt_tracker,
+    operations::{
+        encryption::decrypt_private_key,
+        token::parse_amount,
+        user_operation::{store_user_operation, user_op_hash},
+        wallet_backend::WalletBackend,
+    },
+    repos::{account_repo, db::AppState, deployment_job_repo},
+    routes::sign_message,
+};
+use clutch_wallet_lib::utils::wallet_lib::WalletInstance;
+use ethers::{
+    prelude::*,
+    types::{Address, H256, U256},
+};
+use sea_orm::DatabaseConnection;
+use std::{str::FromStr, time::Duration};
+
+pub const PENDING: &str = "PENDING";
+pub const SUBMITTED: &str = "SUBMITTED";
+pub const DEPLOYED: &str = "DEPLOYED";
+pub const FAILED: &str = "FAILED";
+
+const POLL_INTERVAL: Duration = Duration::from_secs(10);
+const MAX_ATTEMPTS: i32 = 8;
+const BASE_BACKOFF_MS: i64 = 1000 * 30; // 30 seconds
+const MAX_BACKOFF_MS: i64 = 1000 * 60 * 60; // 1 hour
+const VALIDITY_SECS: u64 = 60 * 60;
+const PAYMASTER_ALLOWANCE: &str = "100000"; // whole tokens, scaled by the token decimals
+
+/// Deploys the wallets of new accounts in the background until the server stops.
+pub async fn run(app_state: AppState) {
+    let mut interval = tokio::time::interval(POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = app_state.clock.now_ms();
+        if let Err(e) = process_deployments(&app_state, now).await {
+            log::warn!("Error processing wallet deployments: {}", e);
+        }
+    }
+}
+
+/// The address the wallet will be deployed at, known before the deployment happens.
+pub async fn counterfactual_address(
+    wallet: &dyn WalletBackend,
+    owner: Address,
+) -> anyhow::Result<Address> {
+    let zero_hash: H256 = [0u8; 32].into();
+    wallet
+        .create_unsigned_deploy_wallet_user_op(0, owner, zero_hash, "0x")
+        .await
+        .map(|user_op| user_op.sender)
+}
+
+// a sent deployment waits in SUBMITTED for the receipt tracker to settle it, a failed attempt
+// is retried with exponential backoff, after MAX_ATTEMPTS both the job and the account are
+// marked failed
+pub async fn process_deployments(app_state: &AppState, now: i64) -> anyhow::Result<()> {
+    let db = &app_state.database;
+    let jobs = deployment_job_repo::find_all_due(db, PENDING, now).await?;
+
+    for job in jobs {
+        let attempts = job.attempts + 1;
+        match deploy_account_wallet(app_state, &job, now).await {
+            Ok(user_op_hash) => {
+                deployment_job_repo::update_submitted(
+                    db,
+                    job.id,
+                    SUBMITTED,
+                    attempts,
+                    user_op_hash,
+                    now,
+                )
+                .await?
+            }
+            Err(e) => {
+                log::warn!(
+                    "Error deploying wallet for account {} (attempt {}): {}",
+                    job.account_id,
+                    attempts,
+                    e
+                );
+                retry_or_fail(db, &job, attempts, e.to_string(), now).await?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Settles the deployment sent as `user_op_hash` once its user op reached a final status, the
+/// wallet is deployed when it succeeded, a reverted or dropped one is retried.
+pub async fn settle_deployment(
+    db: &DatabaseConnection,
+    user_op_hash: &str,
+    status: &str,
+    now: i64,
+) -> anyhow::Result<()> {
+    let job = match deployment_job_repo::find_by_user_op_hash_and_status(
+        db,
+        user_op_hash.to_string(),
+        SUBMITTED,
+    )
+    .await?
+    {
+        Some(job) => job,
+        None => return Ok(()),
+    };
+    if status == receipt_tracker::SUCCESS {
+        deployment_job_repo::update_attempt(
+            db,
+            job.id,
+            DEPLOYED,
+            job.attempts,
+            job.next_attempt_at,
+            None,
+            now,
+        )
+        .await?;
+        account_repo::update_deployment_status(db, job.account_id, DEPLOYED, now).await
+    } else {
+        let error = format!("Deployment user operation {} {}", user_op_hash, status);
+        log::warn!("{} for account {}", error, job.account_id);
+        retry_or_fail(db, &job, job.attempts, error, now).await
+    }
+}
+
+async fn retry_or_fail(
+    db: &DatabaseConnection,
+    job: &deployment_job_repo::Model,
+    attempts: i32,
+    error: String,
+    now: i64,
+) -> anyhow::Result<()> {
+    let status = if attempts >= MAX_ATTEMPTS {
+        FAILED
+    } else {
+        PENDING
+    };
+    deployment_job_repo::update_attempt(
+        db,
+        job.id.clone(),
+        status,
+        attempts,
+        now + backoff_ms(attempts),
+        Some(error),
+        now,
+    )
+    .await?;
+    if status == FAILED {
+        account_repo::update_deployment_status(db, job.account_id.clone(), FAILED, now).await?;
+    }
+    Ok(())
+}
+
+fn backoff_ms(attempts: i32) -> i64 {
+    let exponent = (attempts - 1).clamp(0, 16) as u32;
+    BASE_BACKOFF_MS
+        .saturating_mul(2_i64.pow(exponent))
+        .min(MAX_BACKOFF_MS)
+}
+
+async fn deploy_account_wallet(
+    app_state: &AppState,
+    job: &deployment_job_repo::Model,
+    now: i64,
+) -> anyhow::Result<String> {
+    let settings = &app_state.settings;
+    let account = account_repo::find_by_id(&app_state.database, job.account_id.clone())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("Account not found {}", job.account_id))?;
+    if !account.is_custodial() {
+        return Err(anyhow::anyhow!(
+            "Account {} has no custodial key to sign the deployment",
+            account.id
+        ));
+    }
+    let private_key = decrypt_private_key(
+        &settings.wallet_master_key()?,
+        &account.eoa_private_data_key,
+        &account.eoa_private_address,
+    )?;
+    let owner = private_key
+        .parse::<LocalWallet>()?
+        .with_chain_id(settings.chain_id());
+    let paymaster_tokens = match &job.paymaster_tokens {
+        Some(tokens) => serde_json::from_str::<Vec<String>>(tokens)?,
+        None => vec![],
+    };
+
+    deploy_wallet(app_state, &account, &owner, &paymaster_tokens, now).await
+}
+
+async fn deploy_wallet(
+    app_state: &AppState,
+    account: &account_repo::Model,
+    owner: &LocalWallet,
+    paymaster_tokens: &[String],
+    now: i64,
+) -> anyhow::Result<String> {
+    let wallet = app_state.wallet.as_ref();
+    let settings = &app_state.settings;
+    let zero_hash: H256 = [0u8; 32].into();
+    let mut user_op = wallet
+        .create_unsigned_deploy_wallet_user_op(0, owner.address(), zero_hash, "0x")
+        .await?;
+    let fees = app_state.gas_oracle.fees().await?;
+    user_op.max_fee_per_gas = fees.max_fee_per_gas;
+    user_op.max_priority_fee_per_gas = fees.max_priority_fee_per_gas;
+
+    wallet.estimate_user_operation_gas(&mut user_op).await?;
+
+    if !paymaster_tokens.is_empty() {
+        let paymaster = Address::from_str(&settings.contracts.paymaster())?;
+        let mut to = vec![];
+        let mut approve_call_data = vec![];
+        for token in paymaster_tokens {
+            let token = Address::from_str(token)?;
+            let decimals = app_state
+                .tokens
+                .decimals(&app_state.database, token, now)
+                .await?;
+            let allowance = parse_amount(PAYMASTER_ALLOWANCE, decimals)?;
+            let call_data = WalletInstance::approve(paymaster, allowance).map_err(|e| {
+                anyhow::anyhow!("Error encoding the approval of {:?}: {}", token, e)
+            })?;
+            to.push(token);
+            approve_call_data.push(call_data);
+        }
+        user_op.call_data = WalletInstance::execute_batch(to, approve_call_data)
+            .map_err(|e| anyhow::anyhow!("Error encoding the paymaster approvals: {}", e))?;
+        user_op.call_gas_limit = U256::from(50000 * (paymaster_tokens.len() + 1));
+    }
+    let pre_fund_ret = wallet.pre_fund(user_op.clone()).await?;
+
+    // nothing to top up when the deposit already covers the deployment
+    if !pre_fund_ret.missfund.is_zero() {
+        app_state
+            .treasury
+            .fund(
+                &app_state.database,
+                &account.id,
+                user_op.sender,
+                pre_fund_ret.missfund,
+                now,
+            )
+            .await?;
+    }
+
+    let valid_after = (now / 1000) as u64;
+    let valid_until = valid_after + VALIDITY_SECS;
+    let (packed_user_op_hash, validation_data) = wallet
+        .pack_user_op_hash(user_op.clone(), Some(valid_after), Some(valid_until))
+        .await?;
+
+    let signature = sign_message(packed_user_op_hash, owner.clone()).await?;
+    let packed_signature_ret = wallet
+        .pack_user_op_signature(signature, validation_data)
+        .await?;
+
+    user_op.signature = Bytes::from(packed_signature_ret);
+
+    let entry_point = Address::from_str(&settings.contracts.entry_point())?;
+    let user_op_hash = format!(
+        "{:?}",
+        user_op_hash(&user_op, entry_point, settings.chain_id())
+    );
+    let response = wallet.send_user_operation(user_op.clone()).await?;
+    store_user_operation(
+        &app_state.database,
+        account.id.clone(),
+        &user_op,
+        user_op_hash.clone(),
+        receipt_tracker::PENDING,
+        format!("{:?}", response),
+        now,
+    )
+    .await?;
+    Ok(user_op_hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{backoff_ms, BASE_BACKOFF_MS, MAX_BACKOFF_MS};
+
+    #[test]
+    fn backoff_doubles_up_to_the_maximum() {
+        assert_eq!(backoff_ms(1), BASE_BACKOFF_MS);
+        assert_eq!(backoff_ms(2), BASE_BACKOFF_MS * 2);
+        assert_eq!(backoff_ms(3), BASE_BACKOFF_MS * 4);
+        assert_eq!(backoff_ms(20), MAX_BACKOFF_MS);
+    }
+}
@@ -1,4 +1,5 @@
 pub mod config;
+pub mod jobs;
 pub mod models;
 pub mod operations;
 pub mod repos;
@@ -1,7 +1,6 @@
 use clutch_wallet_lib::utils::{bundler::UserOperationTransport, wallet_lib::Transaction};
 use clutch_wallet_lib::utils::wallet_lib::PreFund;
 use ethers::types::{U256, Address};
-use sea_orm::entity::prelude::*;
 use serde::{de, Deserialize, Deserializer, Serialize};
 use std::{fmt, str::FromStr};
 use utoipa::ToSchema;
@@ -18,10 +17,13 @@ pub struct ApiResponse<T, E> {
     pub payload: ApiPayload<T, E>,
 }
 
-pub fn api_error<T>(error_message: String) -> ApiResponse<T, ApiErrorResponse> {
+pub fn api_error<T>(error_code: &str, error_message: String) -> ApiResponse<T, ApiErrorResponse> {
     ApiResponse {
         status: ApiResponseStatus::Error,
-        payload: ApiPayload::Error(ApiErrorResponse { error_message }),
+        payload: ApiPayload::Error(ApiErrorResponse {
+            error_code: error_code.to_string(),
+            error_message,
+        }),
     }
 }
 
@@ -40,6 +42,7 @@ pub enum ApiResponseStatus {
 
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ApiErrorResponse {
+    pub error_code: String,
     pub error_message: String,
 }
 
@@ -62,17 +65,89 @@ pub struct VerificationResponse {
 pub struct AccountCreateRequest {
     pub email: String,
     pub code: String,
-    pub paymaster_tokens: Option<Vec<String>>
+    pub paymaster_tokens: Option<Vec<String>>,
+    pub device: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
 #[serde(default)]
 pub struct AccountCreateResponse {
     pub jwt: String,
+    pub refresh_token: String,
     pub contract_wallet_addr: String,
+    pub deployment_status: String,
 }
 
 
+// Auth API
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+#[serde(default)]
+pub struct LoginRequest {
+    pub email: String,
+    pub code: String,
+    pub device: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+#[serde(default)]
+pub struct LoginResponse {
+    pub jwt: String,
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+#[serde(default)]
+pub struct RefreshRequest {
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+#[serde(default)]
+pub struct RefreshResponse {
+    pub jwt: String,
+    pub refresh_token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Session {
+    pub id: String,
+    pub device: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: i64,
+    pub last_seen_at: i64,
+    pub current: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ListSessionsResponse {
+    pub sessions: Vec<Session>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SessionRevokeResponse {
+    pub session_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct SessionRevokeAllResponse {
+    pub revoked: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+pub struct SiweNonceResponse {
+    pub nonce: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+#[serde(default)]
+pub struct SiweLoginRequest {
+    pub message: String,
+    pub signature: String,
+    pub device: Option<String>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(rename_all = "camelCase")]
 pub struct FormatUserOpRequest {
@@ -80,7 +155,9 @@ pub struct FormatUserOpRequest {
     pub max_priority_fee_per_gas: U256,
     pub selected_address: Address,
     pub raw_txs: Vec<Transaction>,
-    pub pay_token: Address
+    pub pay_token: Address,
+    #[serde(default)]
+    pub sponsored: bool,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -105,6 +182,7 @@ pub struct AccountUpdateResponse {
     pub updated: bool,
 }
 
+// Public view of an account, key material stays in the account record
 #[derive(Serialize, Deserialize, Debug, Default)]
 #[serde(default)]
 #[derive(ToSchema)]
@@ -113,7 +191,7 @@ pub struct Account {
     pub email: String,
     pub wallet_address: String,
     pub eoa_address: String,
-    pub eoa_private_address: String,
+    pub deployment_status: String,
     pub updated_at: i64,
 }
 
@@ -128,16 +206,64 @@ pub struct SendTransactionRequest {
 #[serde(default)]
 pub struct SendTransactionResponse {
     pub status: String,
+    pub user_op_hash: String,
 }
 
-#[derive(Serialize, Deserialize, Debug, Default)]
+#[derive(Debug, Deserialize)]
+pub struct TransactionHistoryParams {
+    #[serde(default, deserialize_with = "empty_string_as_none")]
+    pub status: Option<String>,
+    pub created_after: Option<i64>,
+    pub created_before: Option<i64>,
+    pub page: Option<u64>,
+    pub page_size: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
+#[serde(default)]
+pub struct UserOperation {
+    pub user_op_hash: String,
+    pub sender: String,
+    pub nonce: String,
+    pub call_data_summary: String,
+    pub call_gas_limit: String,
+    pub verification_gas_limit: String,
+    pub pre_verification_gas: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    pub paymaster: Option<String>,
+    pub bundler_response: Option<String>,
+    pub status: String,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub actual_gas_cost: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default, ToSchema)]
 #[serde(default)]
+pub struct ListUserOperationsResponse {
+    pub user_operations: Vec<UserOperation>,
+    pub page: u64,
+    pub page_size: u64,
+    pub total: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SendType {
+    SendEth,
+    SendErc20,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
 pub struct PrefundRequest {
-    pub send_type: String,
+    pub send_type: SendType,
     pub value: Option<String>,
-    pub pay_token: Option<String>,
+    pub pay_token: Option<String>, //token contract when erc20 sending
     pub from: String,
-    pub to: String //receiver when eth sending, token contract when erc20 sending
+    pub to: String //receiver of the eth or the tokens
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -217,6 +343,11 @@ pub struct NominationDeleteResponse {
     pub nomination_id: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct NominationResendResponse {
+    pub nomination_id: String,
+}
+
 // Guardian API for Guardians
 
 #[derive(Debug, Deserialize)]
@@ -237,6 +368,31 @@ pub struct GuardianAccountParams {
 #[derive(Serialize, Deserialize, Debug, Default)]
 pub struct NominationUpdateRequest {
     pub status: String,
+    // proof of wallet ownership, needed to accept
+    pub address: Option<String>,
+    pub nonce: Option<String>,
+    pub signature: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GuardianChallengeResponse {
+    pub nonce: String,
+    pub message: String,
+    pub expires_at: i64,
+}
+
+// Invitation API, for nominated guardians holding an invitation link
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct InvitationChallengeRequest {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct InvitationUpdateRequest {
+    pub token: String,
+    #[serde(flatten)]
+    pub update: NominationUpdateRequest,
 }
 
 #[derive(Serialize, Deserialize, Debug, Default)]
@@ -302,43 +458,71 @@ pub struct AccountGuardianSettingsRequest {
     pub guardians: Vec<String>,
 }
 
-#[derive(EnumIter, DeriveActiveEnum, Debug, Clone, Serialize, Deserialize, PartialEq)]
-#[sea_orm(rs_type = "String", db_type = "String(Some(1))")]
-pub enum SigningStrategy {
-    #[sea_orm(string_value = "OneOfOne")]
-    OneOfOne,
-    #[sea_orm(string_value = "OneOfTwo")]
-    OneOfTwo,
-    #[sea_orm(string_value = "TwoOfTwo")]
-    TwoOfTwo,
-    #[sea_orm(string_value = "OneOfThree")]
-    OneOfThree,
-    #[sea_orm(string_value = "TwoOfThree")]
-    TwoOfThree,
-    #[sea_orm(string_value = "ThreeOfThree")]
-    ThreeOfThree,
+// M-of-N, `threshold` of the `total` active guardians have to sign
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SigningStrategy {
+    pub threshold: u32,
+    pub total: u32,
 }
 
 impl SigningStrategy {
-    pub fn get_signers_for(item: SigningStrategy) -> anyhow::Result<i64> {
-        match item {
-            SigningStrategy::OneOfOne => Ok(1),
-            SigningStrategy::OneOfTwo => Ok(2),
-            SigningStrategy::TwoOfTwo => Ok(2),
-            SigningStrategy::OneOfThree => Ok(3),
-            SigningStrategy::TwoOfThree => Ok(3),
-            SigningStrategy::ThreeOfThree => Ok(3),
-        }
+    pub fn new(threshold: u32, total: u32) -> Self {
+        SigningStrategy { threshold, total }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.threshold >= 1 && self.threshold <= self.total
+    }
+
+    // every strategy that the given number of guardians can support
+    pub fn all_for(guardians: u32) -> Vec<SigningStrategy> {
+        (1..=guardians)
+            .flat_map(|total| {
+                (1..=total).map(move |threshold| SigningStrategy::new(threshold, total))
+            })
+            .collect()
     }
+}
 
-    pub fn all() -> Vec<SigningStrategy> {
-        vec![
-            SigningStrategy::OneOfOne,
-            SigningStrategy::OneOfTwo,
-            SigningStrategy::TwoOfTwo,
-            SigningStrategy::OneOfThree,
-            SigningStrategy::TwoOfThree,
-            SigningStrategy::ThreeOfThree,
-        ]
+impl fmt::Display for SigningStrategy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-of-{}", self.threshold, self.total)
     }
 }
+
+// Recovery API
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RecoveryCreateRequest {
+    pub email: String,
+    pub code: String,
+    pub new_owner: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct RecoveryApprovalRequest {
+    pub signature: String,
+}
+
+// message is what every guardian signs to approve the recovery
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct Recovery {
+    pub id: String,
+    pub account_id: String,
+    pub wallet_address: String,
+    pub new_owner: String,
+    pub status: String,
+    pub message: String,
+    pub approvals: i64,
+    pub approvals_required: i64,
+    pub executable_at: i64,
+    pub user_op_hash: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct ListRecoveriesResponse {
+    pub recoveries: Vec<Recovery>,
+}
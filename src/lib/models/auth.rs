@@ -26,10 +26,19 @@ impl Keys {
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Claims {
     pub sub: String,
+    pub sid: String,
     pub company: String,
     pub exp: usize,
 }
 
+/// Claims of the token in a nomination invitation link, `sub` is the invitation.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct InvitationClaims {
+    pub sub: String,
+    pub nid: String,
+    pub exp: usize,
+}
+
 // #[derive(Debug)]
 // pub enum AuthError {
 //     WrongCredentials,
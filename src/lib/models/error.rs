@@ -0,0 +1,125 @@
+use super::api::{api_error, ApiErrorResponse, ApiResponse};
+use axum::{
+    response::{IntoResponse, Response},
+    Json,
+};
+use hyper::StatusCode;
+use std::fmt;
+use validator::ValidationErrors;
+
+/// Errors returned by the routes. Each variant maps to an HTTP status and a stable
+/// `error_code` the extension can branch on, the message is for humans only.
+#[derive(Debug)]
+pub enum ApiError {
+    NotFound(String),
+    Validation(String),
+    Unauthorized(String),
+    Forbidden(String),
+    Conflict(String),
+    RateLimited(String),
+    Upstream(String),
+    Internal(String),
+}
+
+impl ApiError {
+    pub fn status(&self) -> StatusCode {
+        match self {
+            ApiError::NotFound(_) => StatusCode::NOT_FOUND,
+            ApiError::Validation(_) => StatusCode::BAD_REQUEST,
+            ApiError::Unauthorized(_) => StatusCode::UNAUTHORIZED,
+            ApiError::Forbidden(_) => StatusCode::FORBIDDEN,
+            ApiError::Conflict(_) => StatusCode::CONFLICT,
+            ApiError::RateLimited(_) => StatusCode::TOO_MANY_REQUESTS,
+            ApiError::Upstream(_) => StatusCode::BAD_GATEWAY,
+            ApiError::Internal(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    pub fn error_code(&self) -> &'static str {
+        match self {
+            ApiError::NotFound(_) => "NOT_FOUND",
+            ApiError::Validation(_) => "VALIDATION_FAILED",
+            ApiError::Unauthorized(_) => "UNAUTHORIZED",
+            ApiError::Forbidden(_) => "FORBIDDEN",
+            ApiError::Conflict(_) => "CONFLICT",
+            ApiError::RateLimited(_) => "RATE_LIMITED",
+            ApiError::Upstream(_) => "UPSTREAM_FAILED",
+            ApiError::Internal(_) => "INTERNAL_ERROR",
+        }
+    }
+
+    pub fn message(&self) -> &str {
+        match self {
+            ApiError::NotFound(message)
+            | ApiError::Validation(message)
+            | ApiError::Unauthorized(message)
+            | ApiError::Forbidden(message)
+            | ApiError::Conflict(message)
+            | ApiError::RateLimited(message)
+            | ApiError::Upstream(message)
+            | ApiError::Internal(message) => message,
+        }
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+// Repo and operation errors are anyhow, keep the variant when an ApiError was wrapped
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        match e.downcast::<ApiError>() {
+            Ok(api_error) => api_error,
+            Err(e) => ApiError::Internal(format!("{}", e)),
+        }
+    }
+}
+
+// Sorted by field so the message is stable
+impl From<ValidationErrors> for ApiError {
+    fn from(errors: ValidationErrors) -> Self {
+        let mut field_errors = errors.field_errors().into_iter().collect::<Vec<_>>();
+        field_errors.sort_by_key(|(field, _)| *field);
+        ApiError::Validation(
+            field_errors
+                .iter()
+                .flat_map(|(_, errors)| errors.iter().map(|error| error.to_string()))
+                .collect::<Vec<String>>()
+                .join(", "),
+        )
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        let body: ApiResponse<(), ApiErrorResponse> =
+            api_error(self.error_code(), self.message().to_string());
+        (self.status(), Json(body)).into_response()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::error::ApiError;
+    use hyper::StatusCode;
+
+    #[test]
+    fn wrapped_api_error_keeps_its_variant_test() {
+        let error: ApiError = anyhow::Error::new(ApiError::Conflict("taken".to_string())).into();
+        assert_eq!(error.status(), StatusCode::CONFLICT);
+        assert_eq!(error.error_code(), "CONFLICT");
+        assert_eq!(error.message(), "taken");
+    }
+
+    #[test]
+    fn anyhow_error_is_internal_test() {
+        let error: ApiError = anyhow::anyhow!("db down").into();
+        assert_eq!(error.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert_eq!(error.message(), "db down");
+    }
+}
@@ -1,2 +1,4 @@
 pub mod api;
 pub mod auth;
+pub mod error;
+pub mod validation;
@@ -0,0 +1,195 @@
+use super::{
+    api::{
+        FormatUserOpRequest, PrefundRequest, RecoveryApprovalRequest, RecoveryCreateRequest,
+        SendTransactionRequest, SendType,
+    },
+    error::ApiError,
+};
+use email_address::EmailAddress;
+use ethers::types::Address;
+use std::{borrow::Cow, str::FromStr};
+use validator::{Validate, ValidationError, ValidationErrors};
+
+pub fn parse_address(field: &str, value: &str) -> Result<Address, ApiError> {
+    Address::from_str(value)
+        .map_err(|_| ApiError::Validation(format!("Invalid {} address {}", field, value)))
+}
+
+fn check(errors: &mut ValidationErrors, field: &'static str, result: Result<(), String>) {
+    if let Err(message) = result {
+        let mut error = ValidationError::new(field);
+        error.message = Some(Cow::from(message));
+        errors.add(field, error);
+    }
+}
+
+fn check_address(errors: &mut ValidationErrors, field: &'static str, value: &str) {
+    check(
+        errors,
+        field,
+        parse_address(field, value)
+            .map(|_| ())
+            .map_err(|e| e.to_string()),
+    );
+}
+
+fn into_result(errors: ValidationErrors) -> Result<(), ValidationErrors> {
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+// digits with at most one decimal point, the decimals are checked against the token later
+fn is_amount(value: &str) -> bool {
+    let (whole, fraction) = value.split_once('.').unwrap_or((value, ""));
+    !(whole.is_empty() && fraction.is_empty())
+        && whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+}
+
+impl Validate for PrefundRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        check_address(&mut errors, "from", &self.from);
+        check_address(&mut errors, "to", &self.to);
+        check(
+            &mut errors,
+            "value",
+            match &self.value {
+                Some(value) if is_amount(value) => Ok(()),
+                Some(value) => Err(format!("Invalid value {}", value)),
+                None => Err("Missing value".to_string()),
+            },
+        );
+        if self.send_type == SendType::SendErc20 {
+            match &self.pay_token {
+                Some(pay_token) => check_address(&mut errors, "pay_token", pay_token),
+                None => check(
+                    &mut errors,
+                    "pay_token",
+                    Err("Missing pay_token for send_erc20".to_string()),
+                ),
+            }
+        }
+        into_result(errors)
+    }
+}
+
+impl Validate for SendTransactionRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        check_address(&mut errors, "from", &self.from);
+        into_result(errors)
+    }
+}
+
+impl Validate for FormatUserOpRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if self.selected_address.is_zero() {
+            check(
+                &mut errors,
+                "selected_address",
+                Err("Missing selectedAddress".to_string()),
+            );
+        }
+        if self.raw_txs.is_empty() {
+            check(
+                &mut errors,
+                "raw_txs",
+                Err("At least one transaction is required in rawTxs".to_string()),
+            );
+        }
+        into_result(errors)
+    }
+}
+
+impl Validate for RecoveryCreateRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        if !EmailAddress::is_valid(&self.email) {
+            check(
+                &mut errors,
+                "email",
+                Err(format!("Invalid email format {}", self.email)),
+            );
+        }
+        if self.code.is_empty() {
+            check(&mut errors, "code", Err("Missing code".to_string()));
+        }
+        check(
+            &mut errors,
+            "new_owner",
+            match parse_address("new_owner", &self.new_owner) {
+                Ok(new_owner) if new_owner.is_zero() => {
+                    Err("Missing new_owner address".to_string())
+                }
+                Ok(_) => Ok(()),
+                Err(e) => Err(e.to_string()),
+            },
+        );
+        into_result(errors)
+    }
+}
+
+impl Validate for RecoveryApprovalRequest {
+    fn validate(&self) -> Result<(), ValidationErrors> {
+        let mut errors = ValidationErrors::new();
+        check(
+            &mut errors,
+            "signature",
+            match hex::decode(self.signature.trim_start_matches("0x")) {
+                Ok(signature) if !signature.is_empty() => Ok(()),
+                _ => Err("Invalid signature".to_string()),
+            },
+        );
+        into_result(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{
+        api::{PrefundRequest, SendType},
+        error::ApiError,
+    };
+    use validator::Validate;
+
+    fn prefund_request() -> PrefundRequest {
+        PrefundRequest {
+            send_type: SendType::SendErc20,
+            value: Some("1.5".to_string()),
+            pay_token: Some("0x1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b".to_string()),
+            from: "0x1cf1d4ac0f6ac1b2e3c2a5e8b6d8e1f2a3b4c5d6".to_string(),
+            to: "0x9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b".to_string(),
+        }
+    }
+
+    #[test]
+    fn prefund_request_validation_test() {
+        assert!(prefund_request().validate().is_ok());
+
+        let request = PrefundRequest {
+            pay_token: None,
+            value: Some("1,5".to_string()),
+            to: "0x123".to_string(),
+            ..prefund_request()
+        };
+        let error: ApiError = request.validate().unwrap_err().into();
+        assert_eq!(
+            error.message(),
+            "Missing pay_token for send_erc20, Invalid to address 0x123, Invalid value 1,5"
+        );
+
+        let request = PrefundRequest {
+            send_type: SendType::SendEth,
+            pay_token: None,
+            ..prefund_request()
+        };
+        assert!(request.validate().is_ok());
+    }
+}
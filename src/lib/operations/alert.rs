@@ -0,0 +1,18 @@
+use reqwest::Client;
+
+/// Logs the alert and posts it to the webhook when one is configured.
+pub async fn send_alert(webhook_url: &Option<String>, message: &str) {
+    log::warn!("ALERT: {}", message);
+
+    if let Some(url) = webhook_url {
+        let res = Client::new()
+            .post(url)
+            .json(&serde_json::json!({ "text": message }))
+            .send()
+            .await
+            .and_then(|res| res.error_for_status());
+        if let Err(e) = res {
+            log::warn!("Error posting alert to webhook: {}", e);
+        }
+    }
+}
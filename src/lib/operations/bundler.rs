@@ -0,0 +1,42 @@
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    types::{H256, U256, U64},
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct UserOperationReceipt {
+    pub user_op_hash: H256,
+    pub success: bool,
+    pub actual_gas_cost: U256,
+    pub receipt: TransactionReceipt,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionReceipt {
+    pub transaction_hash: H256,
+    pub block_number: U64,
+}
+
+/// `None` while the bundler has not seen the operation included in a block.
+pub async fn get_user_operation_receipt(
+    bundler: &str,
+    user_op_hash: &str,
+) -> anyhow::Result<Option<UserOperationReceipt>> {
+    let provider = Provider::<Http>::try_from(bundler)?;
+    provider
+        .request("eth_getUserOperationReceipt", [user_op_hash])
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn get_block_number(rpc: &str) -> anyhow::Result<u64> {
+    let provider = Provider::<Http>::try_from(rpc)?;
+    provider
+        .get_block_number()
+        .await
+        .map(|block_number| block_number.as_u64())
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -1,7 +1,67 @@
+use axum::async_trait;
 use reqwest::Client;
 use sendinblue_v3::apis::configuration::{ApiKey, Configuration};
 use sendinblue_v3::apis::smtp_api::send_transac_email;
 use sendinblue_v3::models::{SendSmtpEmail, SendSmtpEmailToInner};
+use std::fmt::Debug;
+
+#[async_trait]
+pub trait Mailer: Debug + Send + Sync {
+    async fn send_verification_code(&self, to: String, code: String) -> anyhow::Result<()>;
+
+    /// Tells someone they were nominated as a guardian, `link` accepts or rejects without a
+    /// Clutch account.
+    async fn send_nomination_invitation(
+        &self,
+        to: String,
+        nominator: String,
+        link: String,
+        reminder: bool,
+    ) -> anyhow::Result<()>;
+}
+
+#[derive(Debug, Clone)]
+pub struct SendinblueMailer {
+    pub api_key: String,
+    pub template_id: i64,
+    pub invitation_template_id: i64,
+    pub base_path: String,
+}
+
+#[async_trait]
+impl Mailer for SendinblueMailer {
+    async fn send_verification_code(&self, to: String, code: String) -> anyhow::Result<()> {
+        send_verification_code_email(
+            self.api_key.clone(),
+            self.template_id,
+            self.base_path.clone(),
+            to,
+            code,
+        )
+        .await
+    }
+
+    async fn send_nomination_invitation(
+        &self,
+        to: String,
+        nominator: String,
+        link: String,
+        reminder: bool,
+    ) -> anyhow::Result<()> {
+        send_template_email(
+            self.api_key.clone(),
+            self.invitation_template_id,
+            self.base_path.clone(),
+            to,
+            serde_json::json!({
+                "nominator": nominator,
+                "link": link,
+                "reminder": reminder,
+            }),
+        )
+        .await
+    }
+}
 
 pub async fn send_verification_code_email(
     api_key: String,
@@ -9,6 +69,25 @@ pub async fn send_verification_code_email(
     base_path: String,
     to: String,
     code: String,
+) -> anyhow::Result<()> {
+    send_template_email(
+        api_key,
+        template_id,
+        base_path,
+        to,
+        serde_json::json!({
+            "code": code,
+        }),
+    )
+    .await
+}
+
+pub async fn send_template_email(
+    api_key: String,
+    template_id: i64,
+    base_path: String,
+    to: String,
+    params: serde_json::Value,
 ) -> anyhow::Result<()> {
     let email = SendSmtpEmail {
         to: vec![SendSmtpEmailToInner {
@@ -16,9 +95,7 @@ pub async fn send_verification_code_email(
             name: None,
         }],
         template_id: Some(template_id),
-        params: Some(serde_json::json!({
-            "code": code,
-        })),
+        params: Some(params),
         ..Default::default()
     };
 
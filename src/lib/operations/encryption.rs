@@ -0,0 +1,97 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng},
+    Aes256Gcm, Nonce,
+};
+
+const NONCE_LENGTH: usize = 12;
+
+/// A custodial private key sealed with its own data key, the data key itself
+/// being sealed with the master key. Both values are hex encoded `nonce || ciphertext`.
+#[derive(Debug, Clone)]
+pub struct EncryptedKey {
+    pub data_key: String,
+    pub ciphertext: String,
+}
+
+pub fn encrypt_private_key(master_key: &str, private_key: &str) -> anyhow::Result<EncryptedKey> {
+    let master_key = decode_key(master_key)?;
+    let data_key = Aes256Gcm::generate_key(&mut OsRng);
+
+    Ok(EncryptedKey {
+        data_key: seal(&master_key, data_key.as_slice())?,
+        ciphertext: seal(data_key.as_slice(), private_key.as_bytes())?,
+    })
+}
+
+pub fn decrypt_private_key(
+    master_key: &str,
+    data_key: &str,
+    ciphertext: &str,
+) -> anyhow::Result<String> {
+    let master_key = decode_key(master_key)?;
+    let data_key = open(&master_key, data_key)?;
+    let private_key = open(&data_key, ciphertext)?;
+
+    String::from_utf8(private_key).map_err(|_| anyhow::anyhow!("Error decoding private key"))
+}
+
+fn decode_key(key: &str) -> anyhow::Result<Vec<u8>> {
+    hex::decode(key.trim_start_matches("0x"))
+        .map_err(|_| anyhow::anyhow!("Error decoding master key, expected hex"))
+}
+
+fn seal(key: &[u8], plaintext: &[u8]) -> anyhow::Result<String> {
+    let cipher =
+        Aes256Gcm::new_from_slice(key).map_err(|_| anyhow::anyhow!("Invalid encryption key"))?;
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext)
+        .map_err(|_| anyhow::anyhow!("Error encrypting key"))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(hex::encode(sealed))
+}
+
+fn open(key: &[u8], sealed: &str) -> anyhow::Result<Vec<u8>> {
+    let cipher =
+        Aes256Gcm::new_from_slice(key).map_err(|_| anyhow::anyhow!("Invalid encryption key"))?;
+    let sealed = hex::decode(sealed).map_err(|_| anyhow::anyhow!("Error decoding sealed key"))?;
+    if sealed.len() <= NONCE_LENGTH {
+        return Err(anyhow::anyhow!("Error decoding sealed key"));
+    }
+
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+    cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| anyhow::anyhow!("Error decrypting key"))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operations::encryption::{decrypt_private_key, encrypt_private_key};
+
+    const MASTER_KEY: &str = "0b5a3ac4e1b2b9a4d1f0e8c7d6b5a4f3e2d1c0b9a8f7e6d5c4b3a2f1e0d9c8b7";
+    const OTHER_MASTER_KEY: &str =
+        "1c6b4bd5f2c3cab5e2f1f9d8e7c6b5f4f3e2d1cab9f8f7e6d5c4b3f2f1eac9d8";
+    const PRIVATE_KEY: &str = "08266d3c24aaa41651c4b9bd7ca52c937afe6a535a0f735dd3c7168fe01741ee";
+
+    #[test]
+    fn encrypt_and_decrypt_private_key_test() {
+        let encrypted = encrypt_private_key(MASTER_KEY, PRIVATE_KEY).unwrap();
+        assert!(!encrypted.ciphertext.contains(PRIVATE_KEY));
+
+        let decrypted =
+            decrypt_private_key(MASTER_KEY, &encrypted.data_key, &encrypted.ciphertext).unwrap();
+        assert_eq!(decrypted, PRIVATE_KEY);
+    }
+
+    #[test]
+    fn decrypt_with_wrong_master_key_test() {
+        let encrypted = encrypt_private_key(MASTER_KEY, PRIVATE_KEY).unwrap();
+
+        let decrypted =
+            decrypt_private_key(OTHER_MASTER_KEY, &encrypted.data_key, &encrypted.ciphertext);
+        assert!(decrypted.is_err());
+    }
+}
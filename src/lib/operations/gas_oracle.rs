@@ -0,0 +1,165 @@
+use crate::config::settings::Gas;
+use axum::async_trait;
+use ethers::{
+    providers::{Http, Middleware, Provider},
+    types::{BlockNumber, FeeHistory, U256},
+};
+use std::{fmt::Debug, str::FromStr, sync::Mutex};
+
+use super::time::get_unix_timestamp_ms;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Fees {
+    pub max_fee_per_gas: U256,
+    pub max_priority_fee_per_gas: U256,
+}
+
+/// EIP-1559 fees for the user ops the service builds.
+#[async_trait]
+pub trait GasOracle: Debug + Send + Sync {
+    async fn fees(&self) -> anyhow::Result<Fees>;
+}
+
+/// Derives fees from `eth_feeHistory`, answers from a cache for `cache_ttl_ms`.
+#[derive(Debug)]
+pub struct FeeHistoryGasOracle {
+    rpc: String,
+    gas: Gas,
+    cache: Mutex<Option<(Fees, i64)>>,
+}
+
+impl FeeHistoryGasOracle {
+    pub fn new(rpc: String, gas: Gas) -> Self {
+        FeeHistoryGasOracle {
+            rpc,
+            gas,
+            cache: Mutex::new(None),
+        }
+    }
+
+    async fn fetch(&self) -> anyhow::Result<Fees> {
+        let provider = Provider::<Http>::try_from(self.rpc.as_str())?;
+        let history = provider
+            .fee_history(self.gas.blocks, BlockNumber::Latest, &[self.gas.percentile])
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        fees_from_history(&history, &self.gas)
+    }
+}
+
+#[async_trait]
+impl GasOracle for FeeHistoryGasOracle {
+    async fn fees(&self) -> anyhow::Result<Fees> {
+        let now = get_unix_timestamp_ms();
+        let cached = *self.cache.lock().unwrap();
+        if let Some((fees, fetched_at)) = cached {
+            if now - fetched_at < self.gas.cache_ttl_ms {
+                return Ok(fees);
+            }
+        }
+
+        match self.fetch().await {
+            Ok(fees) => {
+                *self.cache.lock().unwrap() = Some((fees, now));
+                Ok(fees)
+            }
+            // a stale price beats failing the request
+            Err(e) => match cached {
+                Some((fees, _)) => {
+                    log::warn!("Error fetching fee history, using cached fees: {}", e);
+                    Ok(fees)
+                }
+                None => Err(e),
+            },
+        }
+    }
+}
+
+// priority fee is the mean reward at the configured percentile, max fee leaves room for
+// the base fee to grow by `multiplier`, both kept between floor and ceiling
+pub fn fees_from_history(history: &FeeHistory, gas: &Gas) -> anyhow::Result<Fees> {
+    let floor = U256::from_str(&gas.floor)?;
+    let ceiling = U256::from_str(&gas.ceiling)?;
+    let base_fee = history
+        .base_fee_per_gas
+        .last()
+        .copied()
+        .ok_or_else(|| anyhow::anyhow!("Empty fee history"))?;
+
+    let rewards = history
+        .reward
+        .iter()
+        .filter_map(|reward| reward.first().copied())
+        .collect::<Vec<U256>>();
+    let priority_fee = if rewards.is_empty() {
+        U256::zero()
+    } else {
+        rewards
+            .iter()
+            .fold(U256::zero(), |sum, reward| sum + *reward)
+            / rewards.len()
+    };
+
+    let multiplier = U256::from((gas.multiplier * 100.0).round() as u64);
+    let max_fee = base_fee * multiplier / 100 + priority_fee;
+
+    let max_fee_per_gas = max_fee.max(floor).min(ceiling);
+    let max_priority_fee_per_gas = priority_fee.max(floor).min(max_fee_per_gas);
+    Ok(Fees {
+        max_fee_per_gas,
+        max_priority_fee_per_gas,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fees_from_history;
+    use crate::config::settings::Gas;
+    use ethers::types::{FeeHistory, U256};
+
+    fn gas() -> Gas {
+        Gas {
+            blocks: 3,
+            percentile: 50.0,
+            multiplier: 1.25,
+            floor: "1000000000".to_string(),
+            ceiling: "500000000000".to_string(),
+            cache_ttl_ms: 15000,
+        }
+    }
+
+    fn history(base_fee: u64, rewards: Vec<u64>) -> FeeHistory {
+        FeeHistory {
+            base_fee_per_gas: vec![U256::from(base_fee); rewards.len() + 1],
+            gas_used_ratio: vec![0.5; rewards.len()],
+            oldest_block: U256::from(100),
+            reward: rewards.into_iter().map(|r| vec![U256::from(r)]).collect(),
+        }
+    }
+
+    #[test]
+    fn fees_from_history_test() {
+        let fees = fees_from_history(
+            &history(
+                40_000_000_000,
+                vec![1_000_000_000, 2_000_000_000, 3_000_000_000],
+            ),
+            &gas(),
+        )
+        .unwrap();
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(2_000_000_000_u64));
+        assert_eq!(fees.max_fee_per_gas, U256::from(52_000_000_000_u64));
+    }
+
+    #[test]
+    fn fees_from_history_clamps_to_floor_and_ceiling() {
+        let fees = fees_from_history(&history(1, vec![0, 0, 0]), &gas()).unwrap();
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(1_000_000_000_u64));
+        assert_eq!(fees.max_fee_per_gas, U256::from(1_000_000_000_u64));
+
+        let fees =
+            fees_from_history(&history(1_000_000_000_000, vec![2_000_000_000]), &gas()).unwrap();
+        assert_eq!(fees.max_fee_per_gas, U256::from(500_000_000_000_u64));
+        assert_eq!(fees.max_priority_fee_per_gas, U256::from(2_000_000_000_u64));
+    }
+}
@@ -0,0 +1,79 @@
+use crate::repos::guardian_account_repo;
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+pub const AVAILABLE: &str = "AVAILABLE";
+pub const ACTIVE: &str = "ACTIVE";
+
+pub const CHALLENGE_TTL_MS: i64 = 1000 * 60 * 10; // 10 minutes
+
+/// What a guardian signs (EIP-191) with their wallet to prove they control it when accepting
+/// a nomination. The nonce is issued by the server and is good for a single acceptance.
+pub fn ownership_message(
+    guardian_id: &str,
+    nomination_id: &str,
+    nonce: &str,
+    chain_id: u64,
+) -> String {
+    format!(
+        "Prove ownership of this wallet to become a guardian\n\nGuardian: {}\nNomination: {}\nChain ID: {}\nNonce: {}",
+        guardian_id, nomination_id, chain_id, nonce
+    )
+}
+
+/// Makes the guardian available to the account that nominated them, an active guardian stays
+/// active.
+pub async fn link_guardian(
+    db: &impl ConnectionTrait,
+    guardian_id: String,
+    account_id: String,
+) -> anyhow::Result<()> {
+    match guardian_account_repo::find_guardian_by_guardian_id_and_account_id(
+        db,
+        guardian_id.clone(),
+        account_id.clone(),
+    )
+    .await?
+    {
+        Some(account_guardian) if account_guardian.status == ACTIVE => Ok(()),
+        Some(account_guardian) => {
+            guardian_account_repo::update_status_by_id(
+                db,
+                account_guardian.id,
+                AVAILABLE.to_string(),
+            )
+            .await
+        }
+        None => {
+            guardian_account_repo::create(
+                db,
+                Uuid::new_v4(),
+                guardian_id,
+                account_id,
+                AVAILABLE.to_string(),
+            )
+            .await
+        }
+    }
+}
+
+/// Removes the guardian from the account unless they are active, they have to be taken out of
+/// the guardian settings first.
+pub async fn unlink_guardian(
+    db: &impl ConnectionTrait,
+    guardian_id: String,
+    account_id: String,
+) -> anyhow::Result<()> {
+    match guardian_account_repo::find_guardian_by_guardian_id_and_account_id(
+        db,
+        guardian_id,
+        account_id,
+    )
+    .await?
+    {
+        Some(account_guardian) if account_guardian.status != ACTIVE => {
+            guardian_account_repo::delete_by_id(db, account_guardian.id).await
+        }
+        _ => Ok(()),
+    }
+}
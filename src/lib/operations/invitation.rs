@@ -0,0 +1,108 @@
+use crate::{
+    models::auth::{InvitationClaims, KEYS},
+    repos::{db::AppState, nomination_invitation_repo, nomination_repo},
+};
+use jsonwebtoken::{encode, Header};
+use uuid::Uuid;
+
+pub const INVITATION: &str = "INVITATION";
+pub const RESEND: &str = "RESEND";
+pub const REMINDER: &str = "REMINDER";
+
+/// Signs the token of an invitation link, it is only good as long as its invitation wasn't
+/// consumed and hasn't expired.
+pub fn generate_invitation_token(
+    invitation_id: String,
+    nomination_id: String,
+    expires_at: i64,
+) -> anyhow::Result<String> {
+    let claims = InvitationClaims {
+        sub: invitation_id,
+        nid: nomination_id,
+        exp: expires_at as usize,
+    };
+    encode(&Header::default(), &claims, &KEYS.encoding)
+        .map_err(|_| anyhow::anyhow!("Error creating invitation token"))
+}
+
+pub fn decode_invitation_token(token: &str) -> anyhow::Result<InvitationClaims> {
+    jsonwebtoken::decode::<InvitationClaims>(
+        token,
+        &KEYS.decoding,
+        &jsonwebtoken::Validation::default(),
+    )
+    .map(|token_data| token_data.claims)
+    .map_err(|_| anyhow::anyhow!("Error decoding invitation token"))
+}
+
+pub fn invitation_link(url: &str, token: &str) -> String {
+    format!("{}?token={}", url, token)
+}
+
+/// Emails the nominated guardian a new single use link, callers check the send limit first.
+pub async fn send_invitation(
+    app_state: &AppState,
+    nomination: &nomination_repo::Model,
+    nominator: &str,
+    kind: &str,
+    now: i64,
+) -> anyhow::Result<()> {
+    let id = Uuid::new_v4().to_string();
+    let expires_at = now + app_state.settings.invitation.ttl_secs as i64 * 1000;
+    nomination_invitation_repo::create(
+        &app_state.database,
+        nomination_invitation_repo::Model {
+            id: id.clone(),
+            nomination_id: nomination.id.clone(),
+            email: nomination.email.clone(),
+            kind: kind.to_string(),
+            created_at: now,
+            expires_at,
+            consumed_at: None,
+        },
+    )
+    .await?;
+
+    let token = generate_invitation_token(id, nomination.id.clone(), expires_at)?;
+    app_state
+        .mailer
+        .send_nomination_invitation(
+            nomination.email.clone(),
+            nominator.to_string(),
+            invitation_link(&app_state.settings.invitation.url, &token),
+            kind != INVITATION,
+        )
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_invitation_token, generate_invitation_token, invitation_link};
+
+    #[test]
+    fn invitation_token_test() {
+        let expires_at = i64::MAX / 2;
+        let token = generate_invitation_token(
+            "invitation".to_string(),
+            "nomination".to_string(),
+            expires_at,
+        )
+        .unwrap();
+
+        let claims = decode_invitation_token(&token).unwrap();
+        assert_eq!(claims.sub, "invitation");
+        assert_eq!(claims.nid, "nomination");
+        assert_eq!(claims.exp, expires_at as usize);
+
+        let tampered = format!("{}x", token);
+        assert!(decode_invitation_token(&tampered).is_err());
+    }
+
+    #[test]
+    fn invitation_link_test() {
+        assert_eq!(
+            invitation_link("http://localhost:3000/invitation", "abc"),
+            "http://localhost:3000/invitation?token=abc"
+        );
+    }
+}
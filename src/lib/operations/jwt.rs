@@ -1,12 +1,24 @@
-use crate::models::auth::{Claims, KEYS};
+use crate::{
+    models::auth::{Claims, KEYS},
+    repos::session_repo,
+};
+use ethers::utils::keccak256;
 use jsonwebtoken::{encode, Header};
+use rand::RngCore;
+use sea_orm::DatabaseConnection;
 
-use super::time::get_unix_timestamp_ms;
+pub const ACCESS_TOKEN_TTL_MS: i64 = 1000 * 60 * 15; // 15 minutes
+pub const REFRESH_TOKEN_TTL_MS: i64 = 1000 * 60 * 60 * 24 * 30; // 30 days
 
-pub async fn generate_jwt(account_id: String) -> anyhow::Result<String> {
-    let exp = get_unix_timestamp_ms() + 1000 * 60 * 60 * 24 * 30; // 30 days
+pub async fn generate_jwt(
+    account_id: String,
+    session_id: String,
+    now: i64,
+) -> anyhow::Result<String> {
+    let exp = now + ACCESS_TOKEN_TTL_MS;
     let claims = Claims {
         sub: account_id,
+        sid: session_id,
         company: "Clutch".to_string(),
         exp: exp as usize,
     };
@@ -14,20 +26,39 @@ pub async fn generate_jwt(account_id: String) -> anyhow::Result<String> {
         .map_err(|_| anyhow::anyhow!("Error creating token"))
 }
 
+pub fn generate_refresh_token() -> String {
+    let mut token = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut token);
+    hex::encode(token)
+}
+
+pub fn hash_refresh_token(refresh_token: &str) -> String {
+    hex::encode(keccak256(refresh_token.as_bytes()))
+}
+
+// the expiry is checked against the app's clock by validate_jwt_claims
 pub async fn decode_jwt(token: String) -> anyhow::Result<Claims> {
-    let token_data = jsonwebtoken::decode::<Claims>(
-        &token,
-        &KEYS.decoding,
-        &jsonwebtoken::Validation::default(),
-    )
-    .map_err(|_| anyhow::anyhow!("Error decoding token"))?;
+    let mut validation = jsonwebtoken::Validation::default();
+    validation.validate_exp = false;
+    let token_data = jsonwebtoken::decode::<Claims>(&token, &KEYS.decoding, &validation)
+        .map_err(|_| anyhow::anyhow!("Error decoding token"))?;
     Ok(token_data.claims)
 }
 
-pub async fn validate_jwt_claims(claims: Claims) -> anyhow::Result<()> {
-    let now = get_unix_timestamp_ms();
+pub async fn validate_jwt_claims(
+    db: &DatabaseConnection,
+    claims: Claims,
+    now: i64,
+) -> anyhow::Result<()> {
     if claims.exp < now as usize {
         return Err(anyhow::anyhow!("Token expired"));
     }
-    Ok(())
+
+    let session = session_repo::find_by_id(db, claims.sid.clone()).await?;
+    match session {
+        Some(s) if s.account_id == claims.sub && s.revoked_at.is_none() && s.expires_at > now => {
+            session_repo::update_last_seen(db, s.id, now).await
+        }
+        _ => Err(anyhow::anyhow!("Session revoked")),
+    }
 }
@@ -1,4 +1,19 @@
+pub mod alert;
+pub mod bundler;
 pub mod code;
 pub mod email;
+pub mod encryption;
+pub mod gas_oracle;
+pub mod guardian;
+pub mod invitation;
 pub mod jwt;
+pub mod nomination;
+pub mod paymaster;
+pub mod recovery;
+pub mod signature;
+pub mod siwe;
 pub mod time;
+pub mod token;
+pub mod treasury;
+pub mod user_operation;
+pub mod wallet_backend;
@@ -0,0 +1,152 @@
+use crate::repos::{nomination_history_repo, nomination_repo};
+use sea_orm::ConnectionTrait;
+use uuid::Uuid;
+
+pub const PENDING: &str = "PENDING";
+pub const ACCEPTED: &str = "ACCEPTED";
+pub const REJECTED: &str = "REJECTED";
+pub const EXPIRED: &str = "EXPIRED";
+pub const CANCELLED: &str = "CANCELLED";
+
+/// A pending nomination is answered, expires or is cancelled by the account, an accepted one can
+/// only be cancelled. The other states are final, the account nominates the guardian again instead.
+pub fn can_transition(from: &str, to: &str) -> bool {
+    matches!(
+        (from, to),
+        (PENDING, ACCEPTED | REJECTED | EXPIRED | CANCELLED) | (ACCEPTED, CANCELLED)
+    )
+}
+
+/// The status of the nomination at `now`, a pending nomination expires before it is swept.
+pub fn current_status(nomination: &nomination_repo::Model, now: i64) -> &str {
+    match nomination.expires_at {
+        Some(expires_at) if nomination.status == PENDING && expires_at <= now => EXPIRED,
+        _ => &nomination.status,
+    }
+}
+
+pub async fn nominate(
+    db: &impl ConnectionTrait,
+    id: Uuid,
+    email: String,
+    account_id: String,
+    guardian_id: String,
+    expires_at: i64,
+    now: i64,
+) -> anyhow::Result<()> {
+    nomination_repo::create(
+        db,
+        id,
+        email,
+        account_id,
+        guardian_id,
+        PENDING.to_string(),
+        Some(expires_at),
+    )
+    .await?;
+    record(db, id.to_string(), None, PENDING, now).await
+}
+
+/// Moves the nomination to `to` and records it, false when its status changed in the meantime.
+pub async fn transition(
+    db: &impl ConnectionTrait,
+    nomination: &nomination_repo::Model,
+    to: &str,
+    now: i64,
+) -> anyhow::Result<bool> {
+    if !can_transition(&nomination.status, to) {
+        return Err(anyhow::anyhow!(
+            "Nomination {} can't go from {} to {}",
+            nomination.id,
+            nomination.status,
+            to
+        ));
+    }
+    let updated = nomination_repo::update_status_from(
+        db,
+        nomination.id.clone(),
+        nomination.status.clone(),
+        to.to_string(),
+    )
+    .await?;
+    if updated {
+        record(
+            db,
+            nomination.id.clone(),
+            Some(nomination.status.clone()),
+            to,
+            now,
+        )
+        .await?;
+    }
+    Ok(updated)
+}
+
+async fn record(
+    db: &impl ConnectionTrait,
+    nomination_id: String,
+    from_status: Option<String>,
+    to_status: &str,
+    now: i64,
+) -> anyhow::Result<()> {
+    nomination_history_repo::create(
+        db,
+        nomination_history_repo::Model {
+            id: Uuid::new_v4().to_string(),
+            nomination_id,
+            from_status,
+            to_status: to_status.to_string(),
+            created_at: now,
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{can_transition, current_status, ACCEPTED, CANCELLED, EXPIRED, PENDING, REJECTED};
+    use crate::repos::nomination_repo;
+
+    #[test]
+    fn can_transition_test() {
+        for to in [ACCEPTED, REJECTED, EXPIRED, CANCELLED] {
+            assert!(can_transition(PENDING, to));
+        }
+        assert!(can_transition(ACCEPTED, CANCELLED));
+
+        assert!(!can_transition(ACCEPTED, REJECTED));
+        assert!(!can_transition(ACCEPTED, EXPIRED));
+        assert!(!can_transition(PENDING, PENDING));
+        for from in [REJECTED, EXPIRED, CANCELLED] {
+            for to in [PENDING, ACCEPTED, REJECTED, EXPIRED, CANCELLED] {
+                assert!(!can_transition(from, to));
+            }
+        }
+    }
+
+    #[test]
+    fn current_status_test() {
+        let nomination = nomination_repo::Model {
+            id: "nomination".to_string(),
+            email: "guardian@example.com".to_string(),
+            guardian_id: "guardian".to_string(),
+            account_id: "account".to_string(),
+            status: PENDING.to_string(),
+            expires_at: Some(100),
+        };
+        assert_eq!(current_status(&nomination, 99), PENDING);
+        assert_eq!(current_status(&nomination, 100), EXPIRED);
+
+        let accepted = nomination_repo::Model {
+            status: ACCEPTED.to_string(),
+            ..nomination.clone()
+        };
+        assert_eq!(current_status(&accepted, 100), ACCEPTED);
+
+        let without_expiry = nomination_repo::Model {
+            expires_at: None,
+            ..nomination
+        };
+        assert_eq!(current_status(&without_expiry, i64::MAX), PENDING);
+    }
+}
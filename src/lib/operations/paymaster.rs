@@ -0,0 +1,322 @@
+use crate::{repos::paymaster_policy_repo, utils::convert_to_hex};
+use clutch_wallet_lib::utils::bundler::UserOperationTransport;
+use ethers::{
+    abi::{decode, encode, ParamType, Token},
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, H256, U256},
+    utils::{id, keccak256},
+};
+
+// the EntryPoint (v0.6) reserves the verification gas three times when a paymaster has a postOp
+const PAYMASTER_VERIFICATION_GAS_MULTIPLIER: u64 = 3;
+
+/// Contracts called by `execute` or `executeBatch` calldata, None for any other calldata.
+pub fn call_targets(call_data: &Bytes) -> Option<Vec<Address>> {
+    let (selector, args) = (call_data.get(..4)?, call_data.get(4..)?);
+    if selector == id("execute(address,uint256,bytes)").as_slice() {
+        let tokens = decode(
+            &[ParamType::Address, ParamType::Uint(256), ParamType::Bytes],
+            args,
+        )
+        .ok()?;
+        tokens.into_iter().next()?.into_address().map(|to| vec![to])
+    } else if selector == id("executeBatch(address[],bytes[])").as_slice() {
+        let tokens = decode(
+            &[
+                ParamType::Array(Box::new(ParamType::Address)),
+                ParamType::Array(Box::new(ParamType::Bytes)),
+            ],
+            args,
+        )
+        .ok()?;
+        tokens
+            .into_iter()
+            .next()?
+            .into_array()?
+            .into_iter()
+            .map(|to| to.into_address())
+            .collect()
+    } else {
+        None
+    }
+}
+
+/// The most the paymaster can be charged for the user op.
+pub fn max_gas_cost(user_op: &UserOperationTransport) -> U256 {
+    user_op
+        .call_gas_limit
+        .saturating_add(
+            user_op
+                .verification_gas_limit
+                .saturating_mul(U256::from(PAYMASTER_VERIFICATION_GAS_MULTIPLIER)),
+        )
+        .saturating_add(user_op.pre_verification_gas)
+        .saturating_mul(user_op.max_fee_per_gas)
+}
+
+pub fn check_policy(
+    policy: &paymaster_policy_repo::Model,
+    targets: Option<&[Address]>,
+    max_gas_cost: U256,
+    sponsored: U256,
+    now: i64,
+) -> anyhow::Result<()> {
+    if let Some(valid_from) = policy.valid_from.filter(|valid_from| now < *valid_from) {
+        return Err(anyhow::anyhow!(
+            "Policy {} is not valid before {}",
+            policy.id,
+            valid_from
+        ));
+    }
+    if let Some(valid_until) = policy.valid_until.filter(|valid_until| now >= *valid_until) {
+        return Err(anyhow::anyhow!(
+            "Policy {} expired at {}",
+            policy.id,
+            valid_until
+        ));
+    }
+    if let Some(allowed_targets) = &policy.allowed_targets {
+        let allowed_targets = serde_json::from_str::<Vec<String>>(allowed_targets)?
+            .iter()
+            .map(|target| target.to_lowercase())
+            .collect::<Vec<String>>();
+        let targets = targets.ok_or_else(|| {
+            anyhow::anyhow!("Policy {} only sponsors calls to its targets", policy.id)
+        })?;
+        if let Some(target) = targets
+            .iter()
+            .map(|target| convert_to_hex(*target))
+            .find(|target| !allowed_targets.contains(target))
+        {
+            return Err(anyhow::anyhow!(
+                "Policy {} does not sponsor calls to {}",
+                policy.id,
+                target
+            ));
+        }
+    }
+    if let Some(gas_budget) = &policy.gas_budget {
+        let gas_budget = U256::from_dec_str(gas_budget)?;
+        if sponsored.saturating_add(max_gas_cost) > gas_budget {
+            return Err(anyhow::anyhow!(
+                "Gas cost {} would exceed the budget {} of policy {} (already sponsored {})",
+                max_gas_cost,
+                gas_budget,
+                policy.id,
+                sponsored
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// The VerifyingPaymaster (v0.6) getHash: every user op field but paymasterAndData and the
+/// signature, bound to the chain, the paymaster and the validity window.
+pub fn sponsorship_hash(
+    user_op: &UserOperationTransport,
+    chain_id: u64,
+    paymaster: Address,
+    valid_until: u64,
+    valid_after: u64,
+) -> H256 {
+    H256::from(keccak256(encode(&[
+        Token::Address(user_op.sender),
+        Token::Uint(user_op.nonce),
+        Token::FixedBytes(keccak256(&user_op.init_code).to_vec()),
+        Token::FixedBytes(keccak256(&user_op.call_data).to_vec()),
+        Token::Uint(user_op.call_gas_limit),
+        Token::Uint(user_op.verification_gas_limit),
+        Token::Uint(user_op.pre_verification_gas),
+        Token::Uint(user_op.max_fee_per_gas),
+        Token::Uint(user_op.max_priority_fee_per_gas),
+        Token::Uint(U256::from(chain_id)),
+        Token::Address(paymaster),
+        Token::Uint(U256::from(valid_until)),
+        Token::Uint(U256::from(valid_after)),
+    ])))
+}
+
+pub fn paymaster_and_data(
+    paymaster: Address,
+    valid_until: u64,
+    valid_after: u64,
+    signature: &[u8],
+) -> Bytes {
+    let validity = encode(&[
+        Token::Uint(U256::from(valid_until)),
+        Token::Uint(U256::from(valid_after)),
+    ]);
+    Bytes::from([paymaster.as_bytes(), &validity, signature].concat())
+}
+
+/// Signs the user op as it is now, any later change to its gas or fees needs a new signature.
+pub async fn sponsor(
+    user_op: &mut UserOperationTransport,
+    signer: &LocalWallet,
+    paymaster: Address,
+    chain_id: u64,
+    valid_after: u64,
+    valid_until: u64,
+) -> anyhow::Result<()> {
+    let hash = sponsorship_hash(user_op, chain_id, paymaster, valid_until, valid_after);
+    let signature = signer.sign_message(hash.as_bytes()).await?;
+    user_op.paymaster_and_data =
+        paymaster_and_data(paymaster, valid_until, valid_after, &signature.to_vec());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{call_targets, check_policy, max_gas_cost, sponsor, sponsorship_hash};
+    use crate::repos::paymaster_policy_repo;
+    use clutch_wallet_lib::utils::bundler::UserOperationTransport;
+    use ethers::{
+        abi::{encode, Token},
+        signers::{LocalWallet, Signer},
+        types::{Address, Bytes, Signature, U256},
+        utils::id,
+    };
+    use std::str::FromStr;
+
+    const TARGET: &str = "0x35e218ac80e08990cf0b868deb512f6ababf1dde";
+    const OTHER_TARGET: &str = "0x370f9c8e06c2a6ae986fc050d36d0c6a0475bb99";
+
+    fn policy() -> paymaster_policy_repo::Model {
+        paymaster_policy_repo::Model {
+            id: "policy".to_string(),
+            account_id: None,
+            gas_budget: Some("1000".to_string()),
+            allowed_targets: Some(format!(
+                "[\"{}\"]",
+                TARGET.to_uppercase().replace("0X", "0x")
+            )),
+            valid_from: Some(100),
+            valid_until: Some(200),
+            active: true,
+            created_at: 0,
+            updated_at: 0,
+        }
+    }
+
+    #[test]
+    fn call_targets_test() {
+        let target = Address::from_str(TARGET).unwrap();
+        let other_target = Address::from_str(OTHER_TARGET).unwrap();
+
+        let execute = [
+            id("execute(address,uint256,bytes)").to_vec(),
+            encode(&[
+                Token::Address(target),
+                Token::Uint(U256::zero()),
+                Token::Bytes(vec![]),
+            ]),
+        ]
+        .concat();
+        assert_eq!(call_targets(&Bytes::from(execute)), Some(vec![target]));
+
+        let execute_batch = [
+            id("executeBatch(address[],bytes[])").to_vec(),
+            encode(&[
+                Token::Array(vec![Token::Address(target), Token::Address(other_target)]),
+                Token::Array(vec![Token::Bytes(vec![]), Token::Bytes(vec![])]),
+            ]),
+        ]
+        .concat();
+        assert_eq!(
+            call_targets(&Bytes::from(execute_batch)),
+            Some(vec![target, other_target])
+        );
+
+        assert_eq!(call_targets(&Bytes::from(vec![1, 2, 3, 4, 5])), None);
+        assert_eq!(call_targets(&Bytes::default()), None);
+    }
+
+    #[test]
+    fn max_gas_cost_test() {
+        let user_op = UserOperationTransport {
+            call_gas_limit: U256::from(10),
+            verification_gas_limit: U256::from(20),
+            pre_verification_gas: U256::from(5),
+            max_fee_per_gas: U256::from(2),
+            ..Default::default()
+        };
+        assert_eq!(max_gas_cost(&user_op), U256::from(150));
+    }
+
+    #[test]
+    fn check_policy_test() {
+        let target = Address::from_str(TARGET).unwrap();
+        let other_target = Address::from_str(OTHER_TARGET).unwrap();
+        let policy = policy();
+
+        assert!(check_policy(
+            &policy,
+            Some(&[target]),
+            U256::from(400),
+            U256::from(600),
+            150
+        )
+        .is_ok());
+        assert!(check_policy(
+            &policy,
+            Some(&[target]),
+            U256::from(400),
+            U256::from(601),
+            150
+        )
+        .is_err());
+        assert!(check_policy(&policy, Some(&[target]), U256::from(1), U256::zero(), 99).is_err());
+        assert!(check_policy(&policy, Some(&[target]), U256::from(1), U256::zero(), 200).is_err());
+        assert!(check_policy(
+            &policy,
+            Some(&[target, other_target]),
+            U256::from(1),
+            U256::zero(),
+            150
+        )
+        .is_err());
+        assert!(check_policy(&policy, None, U256::from(1), U256::zero(), 150).is_err());
+
+        let unrestricted = paymaster_policy_repo::Model {
+            gas_budget: None,
+            allowed_targets: None,
+            valid_from: None,
+            valid_until: None,
+            ..policy
+        };
+        assert!(check_policy(&unrestricted, None, U256::MAX, U256::MAX, 0).is_ok());
+    }
+
+    #[tokio::test]
+    async fn sponsor_test() {
+        let signer = LocalWallet::new(&mut rand::thread_rng());
+        let paymaster = Address::from_str(TARGET).unwrap();
+        let mut user_op = UserOperationTransport {
+            nonce: U256::from(1),
+            ..Default::default()
+        };
+
+        sponsor(&mut user_op, &signer, paymaster, 1337, 100, 200)
+            .await
+            .unwrap();
+
+        let paymaster_and_data = user_op.paymaster_and_data.to_vec();
+        assert_eq!(paymaster_and_data.len(), 20 + 64 + 65);
+        assert_eq!(&paymaster_and_data[..20], paymaster.as_bytes());
+        assert_eq!(
+            U256::from_big_endian(&paymaster_and_data[20..52]),
+            U256::from(200)
+        );
+        assert_eq!(
+            U256::from_big_endian(&paymaster_and_data[52..84]),
+            U256::from(100)
+        );
+
+        let signature = Signature::try_from(&paymaster_and_data[84..]).unwrap();
+        let hash = sponsorship_hash(&user_op, 1337, paymaster, 200, 100);
+        assert_eq!(
+            signature.recover(hash.as_bytes()).unwrap(),
+            signer.address()
+        );
+    }
+}
@@ -0,0 +1,196 @@
+use crate::repos::{
+    guardian_account_repo, guardian_settings_repo, recovery_approval_repo, recovery_repo,
+};
+use ethers::{
+    abi::{encode, Token},
+    types::{Address, Bytes},
+    utils::id,
+};
+use sea_orm::DatabaseConnection;
+use std::str::FromStr;
+
+/// What each guardian signs (EIP-191) to approve a recovery. The recovery id and the chain
+/// keep a signature from being replayed for any other recovery.
+pub fn recovery_message(recovery_id: &str, wallet: &str, new_owner: &str, chain_id: u64) -> String {
+    format!(
+        "Approve the recovery of wallet {} to the new owner {}\n\nRecovery: {}\nChain ID: {}",
+        wallet, new_owner, recovery_id, chain_id
+    )
+}
+
+/// The security control module call that rotates the owner key of `wallet`, authorized by the
+/// guardians' signatures instead of the current owner.
+pub fn reset_owner_call_data(wallet: Address, new_owner: Address, guardians: Bytes) -> Bytes {
+    let reset_owner = [
+        id("resetOwner(address)").to_vec(),
+        encode(&[Token::Address(new_owner)]),
+    ]
+    .concat();
+    Bytes::from(
+        [
+            id("executeRecovery(address,bytes,bytes)").to_vec(),
+            encode(&[
+                Token::Address(wallet),
+                Token::Bytes(reset_owner),
+                Token::Bytes(guardians.to_vec()),
+            ]),
+        ]
+        .concat(),
+    )
+}
+
+/// The guardians' approvals encoded as `(address[] signers, bytes[] signatures)`, ordered by
+/// signer so the module can reject a guardian counted twice.
+pub fn guardian_signatures(approvals: &[recovery_approval_repo::Model]) -> anyhow::Result<Bytes> {
+    let mut signatures = approvals
+        .iter()
+        .map(|approval| -> anyhow::Result<(Address, Vec<u8>)> {
+            let signer = Address::from_str(&approval.signer)?;
+            let signature = hex::decode(approval.signature.trim_start_matches("0x"))?;
+            Ok((signer, signature))
+        })
+        .collect::<anyhow::Result<Vec<_>>>()?;
+    signatures.sort_by_key(|(signer, _)| *signer);
+    Ok(Bytes::from(encode(&[
+        Token::Array(
+            signatures
+                .iter()
+                .map(|(signer, _)| Token::Address(*signer))
+                .collect(),
+        ),
+        Token::Array(
+            signatures
+                .into_iter()
+                .map(|(_, signature)| Token::Bytes(signature))
+                .collect(),
+        ),
+    ])))
+}
+
+pub async fn required_approvals(
+    db: &DatabaseConnection,
+    account_id: String,
+) -> anyhow::Result<i64> {
+    let settings = guardian_settings_repo::find_for_account_id(db, account_id.clone())
+        .await?
+        .ok_or_else(|| anyhow::anyhow!("No guardian settings for account {}", account_id))?;
+    Ok(settings.signers().threshold as i64)
+}
+
+/// Approvals that still count, from guardians active on the account, and how many are required.
+pub async fn approval_status(
+    db: &DatabaseConnection,
+    recovery: &recovery_repo::Model,
+) -> anyhow::Result<(i64, i64)> {
+    let required = required_approvals(db, recovery.account_id.clone()).await?;
+    let approvals = active_approvals(db, recovery).await?.len() as i64;
+    Ok((approvals, required))
+}
+
+/// The approvals of guardians that are still active on the account.
+pub async fn active_approvals(
+    db: &DatabaseConnection,
+    recovery: &recovery_repo::Model,
+) -> anyhow::Result<Vec<recovery_approval_repo::Model>> {
+    let active_guardian_ids = guardian_account_repo::find_all_active_guardians_by_account_id(
+        db,
+        recovery.account_id.clone(),
+    )
+    .await?
+    .into_iter()
+    .map(|guardian| guardian.guardian_id)
+    .collect::<Vec<String>>();
+    Ok(
+        recovery_approval_repo::find_all_by_recovery_id(db, recovery.id.clone())
+            .await?
+            .into_iter()
+            .filter(|approval| active_guardian_ids.contains(&approval.guardian_id))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{guardian_signatures, recovery_message, reset_owner_call_data};
+    use crate::repos::recovery_approval_repo;
+    use ethers::{
+        abi::{decode, ParamType},
+        types::{Address, Bytes},
+        utils::id,
+    };
+    use std::str::FromStr;
+
+    const WALLET: &str = "0x35e218ac80e08990cf0b868deb512f6ababf1dde";
+    const NEW_OWNER: &str = "0x370f9c8e06c2a6ae986fc050d36d0c6a0475bb99";
+
+    #[test]
+    fn recovery_message_test() {
+        let message = recovery_message("recovery", WALLET, NEW_OWNER, 1337);
+        assert!(message.contains(WALLET));
+        assert!(message.contains(NEW_OWNER));
+        assert_ne!(message, recovery_message("other", WALLET, NEW_OWNER, 1337));
+        assert_ne!(message, recovery_message("recovery", WALLET, NEW_OWNER, 1));
+    }
+
+    #[test]
+    fn reset_owner_call_data_test() {
+        let wallet = Address::from_str(WALLET).unwrap();
+        let new_owner = Address::from_str(NEW_OWNER).unwrap();
+        let guardians = Bytes::from(vec![1, 2, 3]);
+        let call_data = reset_owner_call_data(wallet, new_owner, guardians.clone());
+        assert_eq!(&call_data[..4], &id("executeRecovery(address,bytes,bytes)"));
+
+        let tokens = decode(
+            &[ParamType::Address, ParamType::Bytes, ParamType::Bytes],
+            &call_data[4..],
+        )
+        .unwrap();
+        assert_eq!(tokens[0].clone().into_address(), Some(wallet));
+        assert_eq!(tokens[2].clone().into_bytes(), Some(guardians.to_vec()));
+        let reset_owner = tokens[1].clone().into_bytes().unwrap();
+        assert_eq!(&reset_owner[..4], &id("resetOwner(address)"));
+        let tokens = decode(&[ParamType::Address], &reset_owner[4..]).unwrap();
+        assert_eq!(tokens[0].clone().into_address(), Some(new_owner));
+    }
+
+    fn approval(signer: &str, signature: &str) -> recovery_approval_repo::Model {
+        recovery_approval_repo::Model {
+            id: signer.to_string(),
+            recovery_id: "recovery".to_string(),
+            guardian_id: signer.to_string(),
+            signer: signer.to_string(),
+            signature: signature.to_string(),
+            created_at: 0,
+        }
+    }
+
+    #[test]
+    fn guardian_signatures_test() {
+        let approvals = vec![approval(WALLET, "0x0102"), approval(NEW_OWNER, "0x03")];
+        let guardians = guardian_signatures(&approvals).unwrap();
+
+        let tokens = decode(
+            &[
+                ParamType::Array(Box::new(ParamType::Address)),
+                ParamType::Array(Box::new(ParamType::Bytes)),
+            ],
+            &guardians,
+        )
+        .unwrap();
+        let signers = tokens[0].clone().into_array().unwrap();
+        let signatures = tokens[1].clone().into_array().unwrap();
+        // ordered by signer, whatever the order of the approvals
+        assert_eq!(
+            signers[0].clone().into_address(),
+            Some(Address::from_str(WALLET).unwrap())
+        );
+        assert_eq!(signatures[0].clone().into_bytes(), Some(vec![1, 2]));
+        assert_eq!(
+            signers[1].clone().into_address(),
+            Some(Address::from_str(NEW_OWNER).unwrap())
+        );
+        assert_eq!(signatures[1].clone().into_bytes(), Some(vec![3]));
+
+        assert!(guardian_signatures(&[approval(WALLET, "0xzz")]).is_err());
+    }
+}
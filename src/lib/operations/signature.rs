@@ -0,0 +1,95 @@
+use ethers::{
+    abi::{encode, Token},
+    prelude::*,
+    providers::Provider,
+    types::{Address, Signature},
+    utils::hash_message,
+};
+
+// bytes4(keccak256("isValidSignature(bytes32,bytes)"))
+const EIP1271_MAGIC_VALUE: [u8; 4] = [0x16, 0x26, 0xba, 0x7e];
+
+/// Recovers the EOA that produced a personal_sign (EIP-191) signature over `message`.
+pub fn recover_signer(message: &str, signature: &[u8]) -> anyhow::Result<Address> {
+    let signature =
+        Signature::try_from(signature).map_err(|_| anyhow::anyhow!("Invalid signature"))?;
+    signature
+        .recover(message)
+        .map_err(|_| anyhow::anyhow!("Invalid signature"))
+}
+
+/// Asks a contract wallet whether it accepts `signature` for `message` via EIP-1271.
+pub async fn is_valid_contract_signature(
+    rpc: &str,
+    contract: Address,
+    message: &str,
+    signature: &[u8],
+) -> anyhow::Result<bool> {
+    let provider = Provider::<Http>::try_from(rpc)?;
+    let mut data = EIP1271_MAGIC_VALUE.to_vec();
+    data.extend(encode(&[
+        Token::FixedBytes(hash_message(message).as_bytes().to_vec()),
+        Token::Bytes(signature.to_vec()),
+    ]));
+    let tx = TransactionRequest::new().to(contract).data(data);
+
+    // wallets revert on signatures they don't recognise
+    match provider.call(&tx.into(), None).await {
+        Ok(result) => Ok(result.len() >= 4 && result[..4] == EIP1271_MAGIC_VALUE),
+        Err(_) => Ok(false),
+    }
+}
+
+/// Whether `address` signed `message`, either as an EOA or as a contract wallet.
+pub async fn is_valid_signature(
+    rpc: &str,
+    address: Address,
+    message: &str,
+    signature: &[u8],
+) -> anyhow::Result<bool> {
+    if let Ok(signer) = recover_signer(message, signature) {
+        if signer == address {
+            return Ok(true);
+        }
+    }
+    is_valid_contract_signature(rpc, address, message, signature).await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operations::signature::{is_valid_signature, recover_signer};
+    use ethers::signers::{LocalWallet, Signer};
+
+    const PRIVATE_KEY: &str = "08266d3c24aaa41651c4b9bd7ca52c937afe6a535a0f735dd3c7168fe01741ee";
+    const RPC: &str = "http://127.0.0.1:1";
+
+    #[tokio::test]
+    async fn recover_signer_test() {
+        let wallet = PRIVATE_KEY.parse::<LocalWallet>().unwrap();
+        let signature = wallet.sign_message("hello").await.unwrap();
+
+        let signer = recover_signer("hello", &signature.to_vec()).unwrap();
+        assert_eq!(signer, wallet.address());
+
+        let signer = recover_signer("goodbye", &signature.to_vec()).unwrap();
+        assert_ne!(signer, wallet.address());
+    }
+
+    #[tokio::test]
+    async fn is_valid_signature_test() {
+        let wallet = PRIVATE_KEY.parse::<LocalWallet>().unwrap();
+        let signature = wallet.sign_message("hello").await.unwrap();
+
+        let valid = is_valid_signature(RPC, wallet.address(), "hello", &signature.to_vec());
+        assert!(valid.await.unwrap());
+
+        // no contract wallet answers on the rpc either
+        let valid = is_valid_signature(RPC, wallet.address(), "goodbye", &signature.to_vec());
+        assert!(!valid.await.unwrap());
+    }
+
+    #[test]
+    fn recover_signer_with_invalid_signature_test() {
+        assert!(recover_signer("hello", &[0u8; 10]).is_err());
+    }
+}
@@ -0,0 +1,215 @@
+use chrono::{DateTime, FixedOffset, Utc};
+use ethers::types::Address;
+use rand::{distributions::Alphanumeric, Rng};
+use std::str::FromStr;
+
+pub const NONCE_TTL_MS: i64 = 1000 * 60 * 10; // 10 minutes
+
+const PREAMBLE: &str = " wants you to sign in with your Ethereum account:";
+
+/// An EIP-4361 Sign-In With Ethereum message.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SiweMessage {
+    pub domain: String,
+    pub address: Address,
+    pub statement: Option<String>,
+    pub uri: String,
+    pub version: String,
+    pub chain_id: u64,
+    pub nonce: String,
+    pub issued_at: DateTime<FixedOffset>,
+    pub expiration_time: Option<DateTime<FixedOffset>>,
+    pub not_before: Option<DateTime<FixedOffset>>,
+    pub request_id: Option<String>,
+    pub resources: Vec<String>,
+}
+
+impl SiweMessage {
+    pub fn validate(&self, domain: &str, chain_id: u64, now: DateTime<Utc>) -> anyhow::Result<()> {
+        if self.domain != domain {
+            return Err(anyhow::anyhow!("Invalid SIWE domain {}", self.domain));
+        }
+        if self.chain_id != chain_id {
+            return Err(anyhow::anyhow!("Invalid SIWE chain id {}", self.chain_id));
+        }
+        if let Some(expiration_time) = self.expiration_time {
+            if expiration_time <= now {
+                return Err(anyhow::anyhow!("SIWE message expired"));
+            }
+        }
+        if let Some(not_before) = self.not_before {
+            if not_before > now {
+                return Err(anyhow::anyhow!("SIWE message not yet valid"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for SiweMessage {
+    type Err = anyhow::Error;
+
+    fn from_str(message: &str) -> anyhow::Result<Self> {
+        let mut lines = message.lines().peekable();
+
+        let domain = lines
+            .next()
+            .and_then(|line| line.strip_suffix(PREAMBLE))
+            .ok_or_else(|| anyhow::anyhow!("Invalid SIWE message, missing preamble"))?
+            .to_string();
+        let address = lines
+            .next()
+            .and_then(|line| Address::from_str(line).ok())
+            .ok_or_else(|| anyhow::anyhow!("Invalid SIWE message, missing address"))?;
+
+        while lines.peek() == Some(&"") {
+            lines.next();
+        }
+        let statement = match lines.peek() {
+            Some(line) if !line.starts_with("URI: ") => {
+                let statement = line.to_string();
+                lines.next();
+                while lines.peek() == Some(&"") {
+                    lines.next();
+                }
+                Some(statement)
+            }
+            _ => None,
+        };
+
+        let uri = tag(lines.next(), "URI")?;
+        let version = tag(lines.next(), "Version")?;
+        if version != "1" {
+            return Err(anyhow::anyhow!("Invalid SIWE version {}", version));
+        }
+        let chain_id = tag(lines.next(), "Chain ID")?
+            .parse::<u64>()
+            .map_err(|_| anyhow::anyhow!("Invalid SIWE message, bad chain id"))?;
+        let nonce = tag(lines.next(), "Nonce")?;
+        if nonce.len() < 8 || !nonce.chars().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(anyhow::anyhow!("Invalid SIWE message, bad nonce"));
+        }
+        let issued_at = timestamp(&tag(lines.next(), "Issued At")?)?;
+
+        let mut expiration_time = None;
+        let mut not_before = None;
+        let mut request_id = None;
+        let mut resources = vec![];
+        while let Some(line) = lines.next() {
+            match line.split_once(": ") {
+                Some(("Expiration Time", value)) => expiration_time = Some(timestamp(value)?),
+                Some(("Not Before", value)) => not_before = Some(timestamp(value)?),
+                Some(("Request ID", value)) => request_id = Some(value.to_string()),
+                _ if line == "Resources:" => {
+                    while let Some(resource) = lines.next_if(|l| l.starts_with("- ")) {
+                        resources.push(resource[2..].to_string());
+                    }
+                }
+                _ => return Err(anyhow::anyhow!("Invalid SIWE message, unexpected {}", line)),
+            }
+        }
+
+        Ok(SiweMessage {
+            domain,
+            address,
+            statement,
+            uri,
+            version,
+            chain_id,
+            nonce,
+            issued_at,
+            expiration_time,
+            not_before,
+            request_id,
+            resources,
+        })
+    }
+}
+
+pub fn generate_nonce() -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(16)
+        .map(char::from)
+        .collect()
+}
+
+fn tag(line: Option<&str>, name: &str) -> anyhow::Result<String> {
+    line.and_then(|l| l.strip_prefix(&format!("{}: ", name)))
+        .map(|value| value.to_string())
+        .ok_or_else(|| anyhow::anyhow!("Invalid SIWE message, missing {}", name))
+}
+
+fn timestamp(value: &str) -> anyhow::Result<DateTime<FixedOffset>> {
+    DateTime::parse_from_rfc3339(value)
+        .map_err(|_| anyhow::anyhow!("Invalid SIWE message, bad timestamp {}", value))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operations::siwe::{generate_nonce, SiweMessage};
+    use chrono::{TimeZone, Utc};
+
+    const MESSAGE: &str = "clutch.app wants you to sign in with your Ethereum account:
+0xC02aaA39b223FE8D0A0e5C4F27eAD9083C756Cc2
+
+Sign in to Clutch
+
+URI: https://clutch.app
+Version: 1
+Chain ID: 80001
+Nonce: 32891756abcdefgh
+Issued At: 2023-05-01T10:00:00Z
+Expiration Time: 2023-05-01T10:10:00Z
+Resources:
+- https://clutch.app/terms";
+
+    #[test]
+    fn parse_siwe_message_test() {
+        let message = MESSAGE.parse::<SiweMessage>().unwrap();
+        assert_eq!(message.domain, "clutch.app");
+        assert_eq!(message.statement, Some("Sign in to Clutch".to_string()));
+        assert_eq!(message.chain_id, 80001);
+        assert_eq!(message.nonce, "32891756abcdefgh");
+        assert!(message.expiration_time.is_some());
+        assert_eq!(message.resources, vec!["https://clutch.app/terms"]);
+    }
+
+    #[test]
+    fn parse_siwe_message_without_statement_test() {
+        let message = MESSAGE
+            .replace("Sign in to Clutch\n\n", "")
+            .parse::<SiweMessage>()
+            .unwrap();
+        assert_eq!(message.statement, None);
+        assert_eq!(message.uri, "https://clutch.app");
+    }
+
+    #[test]
+    fn parse_invalid_siwe_message_test() {
+        assert!("not a siwe message".parse::<SiweMessage>().is_err());
+        assert!(MESSAGE
+            .replace("Version: 1", "Version: 2")
+            .parse::<SiweMessage>()
+            .is_err());
+    }
+
+    #[test]
+    fn validate_siwe_message_test() {
+        let message = MESSAGE.parse::<SiweMessage>().unwrap();
+        let now = Utc.with_ymd_and_hms(2023, 5, 1, 10, 5, 0).unwrap();
+        let later = Utc.with_ymd_and_hms(2023, 5, 1, 10, 15, 0).unwrap();
+
+        assert!(message.validate("clutch.app", 80001, now).is_ok());
+        assert!(message.validate("evil.app", 80001, now).is_err());
+        assert!(message.validate("clutch.app", 1, now).is_err());
+        assert!(message.validate("clutch.app", 80001, later).is_err());
+    }
+
+    #[test]
+    fn generate_nonce_test() {
+        let nonce = generate_nonce();
+        assert_eq!(nonce.len(), 16);
+        assert!(nonce.chars().all(|c| c.is_ascii_alphanumeric()));
+    }
+}
@@ -1,6 +1,21 @@
 use chrono::Utc;
+use std::fmt::Debug;
 
 pub fn get_unix_timestamp_ms() -> i64 {
     let now = Utc::now();
     now.timestamp_millis()
 }
+
+/// Source of the current time for the routes, so tests can pin it.
+pub trait Clock: Debug + Send + Sync {
+    fn now_ms(&self) -> i64;
+}
+
+#[derive(Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_ms(&self) -> i64 {
+        get_unix_timestamp_ms()
+    }
+}
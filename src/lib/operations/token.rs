@@ -0,0 +1,198 @@
+use crate::{config::settings, repos::token_repo, utils::convert_to_hex};
+use axum::async_trait;
+use ethers::{
+    abi::{decode, ParamType},
+    prelude::*,
+    providers::Provider,
+    types::{Address, Bytes, TransactionRequest, U256},
+    utils::id,
+};
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+use std::{fmt::Debug, str::FromStr, sync::Arc};
+use uuid::Uuid;
+
+pub const NATIVE_DECIMALS: u8 = 18;
+
+/// Reads ERC-20 metadata from the token contract itself.
+#[async_trait]
+pub trait TokenContract: Debug + Send + Sync {
+    async fn decimals(&self, token: Address) -> anyhow::Result<u8>;
+}
+
+#[derive(Debug)]
+pub struct RpcTokenContract {
+    rpc: String,
+}
+
+impl RpcTokenContract {
+    pub fn new(rpc: String) -> Self {
+        RpcTokenContract { rpc }
+    }
+}
+
+#[async_trait]
+impl TokenContract for RpcTokenContract {
+    async fn decimals(&self, token: Address) -> anyhow::Result<u8> {
+        let provider = Provider::<Http>::try_from(self.rpc.as_str())?;
+        let tx = TransactionRequest::new()
+            .to(token)
+            .data(Bytes::from(id("decimals()").to_vec()));
+        let output = provider
+            .call(&tx.into(), None)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?;
+        decode(&[ParamType::Uint(8)], &output)?
+            .into_iter()
+            .next()
+            .and_then(|decimals| decimals.into_uint())
+            .filter(|decimals| *decimals <= U256::from(u8::MAX))
+            .map(|decimals| decimals.as_u32() as u8)
+            .ok_or_else(|| anyhow::anyhow!("Invalid decimals returned by {:?}", token))
+    }
+}
+
+/// Parses a decimal amount like "1.5" into base units, refusing more precision than the token has.
+pub fn parse_amount(amount: &str, decimals: u8) -> anyhow::Result<U256> {
+    let (whole, fraction) = amount.split_once('.').unwrap_or((amount, ""));
+    if whole.is_empty() && fraction.is_empty()
+        || !whole
+            .chars()
+            .chain(fraction.chars())
+            .all(|c| c.is_ascii_digit())
+    {
+        return Err(anyhow::anyhow!("Invalid amount {}", amount));
+    }
+    if fraction.len() > decimals as usize {
+        return Err(anyhow::anyhow!(
+            "Amount {} has more than {} decimals",
+            amount,
+            decimals
+        ));
+    }
+    let units = format!("{}{:0<width$}", whole, fraction, width = decimals as usize);
+    U256::from_dec_str(if units.is_empty() { "0" } else { &units })
+        .map_err(|_| anyhow::anyhow!("Amount {} is too large", amount))
+}
+
+/// Tokens known on the configured chain, seeded from the config. Tokens missing from the
+/// registry are looked up on chain once and cached in the tokens table.
+#[derive(Debug)]
+pub struct TokenRegistry {
+    contract: Arc<dyn TokenContract>,
+    chain_id: u64,
+}
+
+impl TokenRegistry {
+    pub fn new(contract: Arc<dyn TokenContract>, chain_id: u64) -> Self {
+        TokenRegistry { contract, chain_id }
+    }
+
+    pub async fn seed(
+        &self,
+        db: &DatabaseConnection,
+        tokens: &[settings::Token],
+        now: i64,
+    ) -> anyhow::Result<()> {
+        for token in tokens {
+            let address = convert_to_hex(Address::from_str(&token.address)?);
+            match token_repo::find_by_address_and_chain_id(
+                db,
+                address.clone(),
+                self.chain_id as i64,
+            )
+            .await?
+            {
+                Some(existing) => {
+                    token_repo::update(
+                        db,
+                        existing.id,
+                        Some(token.symbol.clone()),
+                        token.decimals as i32,
+                        token.paymaster,
+                        now,
+                    )
+                    .await?
+                }
+                None => {
+                    token_repo::create(
+                        db,
+                        token_repo::Model {
+                            id: Uuid::new_v4().to_string(),
+                            address,
+                            chain_id: self.chain_id as i64,
+                            symbol: Some(token.symbol.clone()),
+                            decimals: token.decimals as i32,
+                            paymaster_supported: token.paymaster,
+                            created_at: now,
+                            updated_at: now,
+                        },
+                    )
+                    .await?
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// The registered token, None when it is not in the registry.
+    pub async fn find(
+        &self,
+        db: &impl ConnectionTrait,
+        token: Address,
+    ) -> anyhow::Result<Option<token_repo::Model>> {
+        token_repo::find_by_address_and_chain_id(db, convert_to_hex(token), self.chain_id as i64)
+            .await
+    }
+
+    pub async fn decimals(
+        &self,
+        db: &DatabaseConnection,
+        token: Address,
+        now: i64,
+    ) -> anyhow::Result<u8> {
+        if let Some(registered) = self.find(db, token).await? {
+            return Ok(registered.decimals as u8);
+        }
+
+        let decimals = self.contract.decimals(token).await?;
+        token_repo::create(
+            db,
+            token_repo::Model {
+                id: Uuid::new_v4().to_string(),
+                address: convert_to_hex(token),
+                chain_id: self.chain_id as i64,
+                symbol: None,
+                decimals: decimals as i32,
+                paymaster_supported: false,
+                created_at: now,
+                updated_at: now,
+            },
+        )
+        .await?;
+        Ok(decimals)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_amount;
+    use ethers::types::U256;
+
+    #[test]
+    fn parse_amount_test() {
+        assert_eq!(parse_amount("1.5", 6).unwrap(), U256::from(1_500_000));
+        assert_eq!(parse_amount("100", 6).unwrap(), U256::from(100_000_000));
+        assert_eq!(parse_amount(".25", 2).unwrap(), U256::from(25));
+        assert_eq!(
+            parse_amount("1", 18).unwrap(),
+            U256::from(1_000_000_000_000_000_000_u64)
+        );
+        assert_eq!(parse_amount("7", 0).unwrap(), U256::from(7));
+
+        assert!(parse_amount("1.0000001", 6).is_err());
+        assert!(parse_amount("-1", 6).is_err());
+        assert!(parse_amount("1e6", 6).is_err());
+        assert!(parse_amount(".", 6).is_err());
+        assert!(parse_amount("", 6).is_err());
+    }
+}
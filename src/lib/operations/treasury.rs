@@ -0,0 +1,276 @@
+use crate::{
+    config::settings, operations::alert::send_alert, repos::treasury_funding_repo,
+    utils::convert_to_hex,
+};
+use axum::async_trait;
+use ethers::{
+    prelude::*,
+    providers::Provider,
+    types::{Address, H256, U256},
+};
+use sea_orm::DatabaseConnection;
+use std::{
+    fmt::Debug,
+    sync::{Arc, Mutex},
+};
+use uuid::Uuid;
+
+pub const SENT: &str = "SENT";
+pub const FAILED: &str = "FAILED";
+
+const DAY_MS: i64 = 1000 * 60 * 60 * 24;
+// a low balance is alerted at most once within this window
+const LOW_BALANCE_ALERT_COOLDOWN_MS: i64 = 1000 * 60 * 60;
+
+/// The hot wallet new wallets are prefunded from.
+#[async_trait]
+pub trait FundingWallet: Debug + Send + Sync {
+    fn address(&self) -> Address;
+
+    async fn balance(&self) -> anyhow::Result<U256>;
+
+    /// Sends `amount` to `to` and waits for the transfer to be mined.
+    async fn transfer(&self, to: Address, amount: U256) -> anyhow::Result<H256>;
+}
+
+#[derive(Debug)]
+pub struct RpcFundingWallet {
+    rpc: String,
+    signer: LocalWallet,
+}
+
+impl RpcFundingWallet {
+    pub fn new(rpc: String, private_key: &str, chain_id: u64) -> anyhow::Result<Self> {
+        let signer = private_key.parse::<LocalWallet>()?.with_chain_id(chain_id);
+        Ok(RpcFundingWallet { rpc, signer })
+    }
+}
+
+#[async_trait]
+impl FundingWallet for RpcFundingWallet {
+    fn address(&self) -> Address {
+        self.signer.address()
+    }
+
+    async fn balance(&self) -> anyhow::Result<U256> {
+        let provider = Provider::<Http>::try_from(self.rpc.as_str())?;
+        provider
+            .get_balance(self.signer.address(), None)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    async fn transfer(&self, to: Address, amount: U256) -> anyhow::Result<H256> {
+        let provider = Provider::<Http>::try_from(self.rpc.as_str())?;
+        let client = SignerMiddleware::new(provider, self.signer.clone());
+        let tx = TransactionRequest::new().to(to).value(amount);
+        client
+            .send_transaction(tx, None)
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .await
+            .map_err(|e| anyhow::anyhow!(e))?
+            .map(|receipt| receipt.transaction_hash)
+            .ok_or_else(|| anyhow::anyhow!("Funding transfer to {:?} was dropped", to))
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Limits {
+    pub reserve: U256,
+    pub per_account_cap: U256,
+    pub daily_cap: U256,
+    pub low_balance_threshold: U256,
+}
+
+impl Limits {
+    pub fn from_settings(treasury: &settings::Treasury) -> anyhow::Result<Self> {
+        Ok(Limits {
+            reserve: U256::from_dec_str(&treasury.reserve)?,
+            per_account_cap: U256::from_dec_str(&treasury.per_account_cap)?,
+            daily_cap: U256::from_dec_str(&treasury.daily_cap)?,
+            low_balance_threshold: U256::from_dec_str(&treasury.low_balance_threshold)?,
+        })
+    }
+}
+
+pub fn check_limits(
+    limits: &Limits,
+    balance: U256,
+    amount: U256,
+    funded_to_account: U256,
+    funded_today: U256,
+) -> anyhow::Result<()> {
+    if balance < amount.saturating_add(limits.reserve) {
+        return Err(anyhow::anyhow!(
+            "Treasury balance {} cannot fund {} without crossing the reserve {}",
+            balance,
+            amount,
+            limits.reserve
+        ));
+    }
+    if funded_to_account.saturating_add(amount) > limits.per_account_cap {
+        return Err(anyhow::anyhow!(
+            "Funding {} would exceed the per account cap {} (already funded {})",
+            amount,
+            limits.per_account_cap,
+            funded_to_account
+        ));
+    }
+    if funded_today.saturating_add(amount) > limits.daily_cap {
+        return Err(anyhow::anyhow!(
+            "Funding {} would exceed the daily cap {} (already funded {})",
+            amount,
+            limits.daily_cap,
+            funded_today
+        ));
+    }
+    Ok(())
+}
+
+fn is_alert_due(last_alert_at: Option<i64>, now: i64) -> bool {
+    match last_alert_at {
+        Some(last_alert_at) => now - last_alert_at >= LOW_BALANCE_ALERT_COOLDOWN_MS,
+        None => true,
+    }
+}
+
+/// Funds wallets from the `FundingWallet` within the configured limits, every transfer is
+/// recorded in the treasury_fundings ledger.
+#[derive(Debug)]
+pub struct Treasury {
+    wallet: Arc<dyn FundingWallet>,
+    limits: Limits,
+    alert_webhook_url: Option<String>,
+    last_alert_at: Mutex<Option<i64>>,
+}
+
+impl Treasury {
+    pub fn new(
+        wallet: Arc<dyn FundingWallet>,
+        treasury: &settings::Treasury,
+    ) -> anyhow::Result<Self> {
+        Ok(Treasury {
+            wallet,
+            limits: Limits::from_settings(treasury)?,
+            alert_webhook_url: treasury.alert_webhook_url.clone(),
+            last_alert_at: Mutex::new(None),
+        })
+    }
+
+    pub async fn fund(
+        &self,
+        db: &DatabaseConnection,
+        account_id: &str,
+        to: Address,
+        amount: U256,
+        now: i64,
+    ) -> anyhow::Result<H256> {
+        let balance = self.wallet.balance().await?;
+        let funded_to_account = total(
+            treasury_funding_repo::find_all_by_account_id_and_status(
+                db,
+                account_id.to_string(),
+                SENT,
+            )
+            .await?,
+        )?;
+        let funded_today = total(
+            treasury_funding_repo::find_all_by_status_created_after(db, SENT, now - DAY_MS).await?,
+        )?;
+        check_limits(
+            &self.limits,
+            balance,
+            amount,
+            funded_to_account,
+            funded_today,
+        )?;
+
+        let transfer = self.wallet.transfer(to, amount).await;
+        let (status, transaction_hash, error) = match &transfer {
+            Ok(hash) => (SENT, Some(format!("{:?}", hash)), None),
+            Err(e) => (FAILED, None, Some(e.to_string())),
+        };
+        treasury_funding_repo::create(
+            db,
+            treasury_funding_repo::Model {
+                id: Uuid::new_v4().to_string(),
+                account_id: account_id.to_string(),
+                recipient: convert_to_hex(to),
+                amount: amount.to_string(),
+                transaction_hash,
+                status: status.to_string(),
+                error,
+                created_at: now,
+            },
+        )
+        .await?;
+        let hash = transfer?;
+
+        let remaining = balance.saturating_sub(amount);
+        if remaining < self.limits.low_balance_threshold && self.claim_alert(now) {
+            send_alert(
+                &self.alert_webhook_url,
+                &format!(
+                    "Treasury {} balance is low: {} (threshold {})",
+                    convert_to_hex(self.wallet.address()),
+                    remaining,
+                    self.limits.low_balance_threshold
+                ),
+            )
+            .await;
+        }
+        Ok(hash)
+    }
+
+    fn claim_alert(&self, now: i64) -> bool {
+        let mut last_alert_at = self
+            .last_alert_at
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if !is_alert_due(*last_alert_at, now) {
+            return false;
+        }
+        *last_alert_at = Some(now);
+        true
+    }
+}
+
+fn total(fundings: Vec<treasury_funding_repo::Model>) -> anyhow::Result<U256> {
+    fundings.iter().try_fold(U256::zero(), |sum, funding| {
+        Ok(sum.saturating_add(U256::from_dec_str(&funding.amount)?))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check_limits, is_alert_due, Limits, LOW_BALANCE_ALERT_COOLDOWN_MS};
+    use ethers::types::U256;
+
+    fn limits() -> Limits {
+        Limits {
+            reserve: U256::from(100),
+            per_account_cap: U256::from(10),
+            daily_cap: U256::from(25),
+            low_balance_threshold: U256::from(500),
+        }
+    }
+
+    #[test]
+    fn check_limits_test() {
+        let amount = U256::from(10);
+        assert!(check_limits(&limits(), U256::from(110), amount, 0.into(), 15.into()).is_ok());
+        // reserve
+        assert!(check_limits(&limits(), U256::from(109), amount, 0.into(), 0.into()).is_err());
+        // per account cap
+        assert!(check_limits(&limits(), U256::from(1000), amount, 1.into(), 0.into()).is_err());
+        // daily cap
+        assert!(check_limits(&limits(), U256::from(1000), amount, 0.into(), 16.into()).is_err());
+    }
+    #[test]
+    fn is_alert_due_test() {
+        assert!(is_alert_due(None, 0));
+        assert!(!is_alert_due(Some(0), LOW_BALANCE_ALERT_COOLDOWN_MS - 1));
+        assert!(is_alert_due(Some(0), LOW_BALANCE_ALERT_COOLDOWN_MS));
+    }
+}
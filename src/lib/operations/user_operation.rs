@@ -0,0 +1,127 @@
+use crate::{repos::user_operation_repo, utils::convert_to_hex};
+use clutch_wallet_lib::utils::bundler::UserOperationTransport;
+use ethers::{
+    abi::{encode, Token},
+    types::{Address, Bytes, H256, U256},
+    utils::keccak256,
+};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+/// The EntryPoint (v0.6) userOpHash: keccak256(abi.encode(keccak256(pack(userOp)), entryPoint, chainId)).
+pub fn user_op_hash(user_op: &UserOperationTransport, entry_point: Address, chain_id: u64) -> H256 {
+    let packed = encode(&[
+        Token::Address(user_op.sender),
+        Token::Uint(user_op.nonce),
+        Token::FixedBytes(keccak256(&user_op.init_code).to_vec()),
+        Token::FixedBytes(keccak256(&user_op.call_data).to_vec()),
+        Token::Uint(user_op.call_gas_limit),
+        Token::Uint(user_op.verification_gas_limit),
+        Token::Uint(user_op.pre_verification_gas),
+        Token::Uint(user_op.max_fee_per_gas),
+        Token::Uint(user_op.max_priority_fee_per_gas),
+        Token::FixedBytes(keccak256(&user_op.paymaster_and_data).to_vec()),
+    ]);
+
+    H256::from(keccak256(encode(&[
+        Token::FixedBytes(keccak256(packed).to_vec()),
+        Token::Address(entry_point),
+        Token::Uint(U256::from(chain_id)),
+    ])))
+}
+
+/// Function selector and size of the calldata, enough to tell operations apart in history.
+pub fn call_data_summary(call_data: &Bytes) -> String {
+    match call_data.get(..4) {
+        Some(selector) => format!("0x{} ({} bytes)", hex::encode(selector), call_data.len()),
+        None => format!("0x{} ({} bytes)", hex::encode(call_data), call_data.len()),
+    }
+}
+
+pub fn paymaster(paymaster_and_data: &Bytes) -> Option<Address> {
+    paymaster_and_data.get(..20).map(Address::from_slice)
+}
+
+/// Records a user op sent for the account, the receipt tracker follows it while it's pending.
+pub async fn store_user_operation(
+    db: &DatabaseConnection,
+    account_id: String,
+    user_op: &UserOperationTransport,
+    user_op_hash: String,
+    status: &str,
+    bundler_response: String,
+    now: i64,
+) -> anyhow::Result<()> {
+    user_operation_repo::create(
+        db,
+        user_operation_repo::Model {
+            id: Uuid::new_v4().to_string(),
+            account_id,
+            user_op_hash,
+            sender: convert_to_hex(user_op.sender),
+            nonce: user_op.nonce.to_string(),
+            call_data_summary: call_data_summary(&user_op.call_data),
+            call_gas_limit: user_op.call_gas_limit.to_string(),
+            verification_gas_limit: user_op.verification_gas_limit.to_string(),
+            pre_verification_gas: user_op.pre_verification_gas.to_string(),
+            max_fee_per_gas: user_op.max_fee_per_gas.to_string(),
+            max_priority_fee_per_gas: user_op.max_priority_fee_per_gas.to_string(),
+            paymaster: paymaster(&user_op.paymaster_and_data).map(convert_to_hex),
+            bundler_response: Some(bundler_response),
+            status: status.to_string(),
+            transaction_hash: None,
+            block_number: None,
+            actual_gas_cost: None,
+            created_at: now,
+            updated_at: now,
+        },
+    )
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::operations::user_operation::{call_data_summary, paymaster, user_op_hash};
+    use clutch_wallet_lib::utils::bundler::UserOperationTransport;
+    use ethers::types::{Address, Bytes, U256};
+    use std::str::FromStr;
+
+    const ENTRY_POINT: &str = "0x5FF137D4b0FDCD49DcA30c7CF57E578a026d2789";
+
+    #[test]
+    fn user_op_hash_ignores_signature_test() {
+        let entry_point = Address::from_str(ENTRY_POINT).unwrap();
+        let mut user_op = UserOperationTransport {
+            nonce: U256::from(1),
+            ..Default::default()
+        };
+        let hash = user_op_hash(&user_op, entry_point, 80001);
+
+        user_op.signature = Bytes::from(vec![1u8; 65]);
+        assert_eq!(user_op_hash(&user_op, entry_point, 80001), hash);
+
+        assert_ne!(user_op_hash(&user_op, entry_point, 1), hash);
+        user_op.nonce = U256::from(2);
+        assert_ne!(user_op_hash(&user_op, entry_point, 80001), hash);
+    }
+
+    #[test]
+    fn call_data_summary_test() {
+        let call_data = Bytes::from(vec![0xb6, 0x1d, 0x27, 0xf6, 0, 0, 0, 1]);
+        assert_eq!(call_data_summary(&call_data), "0xb61d27f6 (8 bytes)");
+        assert_eq!(call_data_summary(&Bytes::default()), "0x (0 bytes)");
+    }
+
+    #[test]
+    fn paymaster_test() {
+        let entry_point = Address::from_str(ENTRY_POINT).unwrap();
+        let mut paymaster_and_data = entry_point.as_bytes().to_vec();
+        paymaster_and_data.extend([0u8; 32]);
+
+        assert_eq!(
+            paymaster(&Bytes::from(paymaster_and_data)),
+            Some(entry_point)
+        );
+        assert_eq!(paymaster(&Bytes::default()), None);
+    }
+}
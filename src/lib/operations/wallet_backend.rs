@@ -0,0 +1,135 @@
+use axum::async_trait;
+use clutch_wallet_lib::utils::{
+    bundler::UserOperationTransport,
+    wallet_lib::{PreFund, Transaction, WalletLib},
+};
+use ethers::types::{Address, H256, U256};
+use std::fmt::Debug;
+
+/// The wallet and bundler calls the routes make, so tests can swap the live
+/// `WalletLib` for an in-memory fake.
+#[async_trait]
+pub trait WalletBackend: Debug + Send + Sync {
+    async fn create_unsigned_deploy_wallet_user_op(
+        &self,
+        index: u64,
+        owner: Address,
+        guardian_hash: H256,
+        call_data: &str,
+    ) -> anyhow::Result<UserOperationTransport>;
+
+    async fn estimate_user_operation_gas(
+        &self,
+        user_op: &mut UserOperationTransport,
+    ) -> anyhow::Result<()>;
+
+    async fn pre_fund(&self, user_op: UserOperationTransport) -> anyhow::Result<PreFund>;
+
+    async fn pack_user_op_hash(
+        &self,
+        user_op: UserOperationTransport,
+        valid_after: Option<u64>,
+        valid_until: Option<u64>,
+    ) -> anyhow::Result<(Vec<u8>, U256)>;
+
+    async fn pack_user_op_signature(
+        &self,
+        signature: Vec<u8>,
+        validation_data: U256,
+    ) -> anyhow::Result<Vec<u8>>;
+
+    async fn send_user_operation(&self, user_op: UserOperationTransport) -> anyhow::Result<bool>;
+
+    async fn from_transaction(
+        &self,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        from: Address,
+        txs: Vec<Transaction>,
+    ) -> anyhow::Result<UserOperationTransport>;
+}
+
+// WalletLib methods take &mut self, each call works on its own clone
+#[async_trait]
+impl WalletBackend for WalletLib {
+    async fn create_unsigned_deploy_wallet_user_op(
+        &self,
+        index: u64,
+        owner: Address,
+        guardian_hash: H256,
+        call_data: &str,
+    ) -> anyhow::Result<UserOperationTransport> {
+        let mut wallet_lib = self.clone();
+        wallet_lib
+            .create_unsigned_deploy_wallet_user_op(index, owner, guardian_hash, call_data, None)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        user_op: &mut UserOperationTransport,
+    ) -> anyhow::Result<()> {
+        let mut wallet_lib = self.clone();
+        wallet_lib
+            .estimate_user_operation_gas(user_op, None)
+            .await
+            .map(|_| ())
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    async fn pre_fund(&self, user_op: UserOperationTransport) -> anyhow::Result<PreFund> {
+        let mut wallet_lib = self.clone();
+        wallet_lib
+            .pre_fund(user_op)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    async fn pack_user_op_hash(
+        &self,
+        user_op: UserOperationTransport,
+        valid_after: Option<u64>,
+        valid_until: Option<u64>,
+    ) -> anyhow::Result<(Vec<u8>, U256)> {
+        let mut wallet_lib = self.clone();
+        wallet_lib
+            .pack_user_op_hash(user_op, valid_after, valid_until)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    async fn pack_user_op_signature(
+        &self,
+        signature: Vec<u8>,
+        validation_data: U256,
+    ) -> anyhow::Result<Vec<u8>> {
+        let mut wallet_lib = self.clone();
+        wallet_lib
+            .pack_user_op_signature(signature, validation_data, None)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    async fn send_user_operation(&self, user_op: UserOperationTransport) -> anyhow::Result<bool> {
+        let mut wallet_lib = self.clone();
+        wallet_lib
+            .send_user_operation(user_op)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+
+    async fn from_transaction(
+        &self,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        from: Address,
+        txs: Vec<Transaction>,
+    ) -> anyhow::Result<UserOperationTransport> {
+        let mut wallet_lib = self.clone();
+        wallet_lib
+            .from_transaction(max_fee_per_gas, max_priority_fee_per_gas, from, txs, None)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e))
+    }
+}
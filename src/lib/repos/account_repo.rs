@@ -11,10 +11,21 @@ pub struct Model {
     pub email: String,
     pub wallet_address: String,
     pub eoa_address: String,
+    #[serde(skip_serializing)]
     pub eoa_private_address: String,
+    #[serde(skip_serializing)]
+    pub eoa_private_data_key: String,
+    pub deployment_status: String,
     pub updated_at: i64,
 }
 
+impl Model {
+    // the server holds the owner key until a recovery hands the wallet to another owner
+    pub fn is_custodial(&self) -> bool {
+        !self.eoa_private_data_key.is_empty()
+    }
+}
+
 #[derive(Copy, Clone, Debug, EnumIter)]
 pub enum Relation {}
 
@@ -27,12 +38,13 @@ impl RelationTrait for Relation {
 impl ActiveModelBehavior for ActiveModel {}
 
 pub async fn create(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     id: Uuid,
     email: String,
     wallet_address: String,
     eoa_address: String,
     eoa_private: String,
+    eoa_private_data_key: String,
     updated_at: i64,
 ) -> anyhow::Result<()> {
     let model = ActiveModel {
@@ -41,6 +53,8 @@ pub async fn create(
         wallet_address: Set(wallet_address.to_owned()),
         eoa_address: Set(eoa_address.to_owned()),
         eoa_private_address: Set(eoa_private.to_owned()),
+        eoa_private_data_key: Set(eoa_private_data_key.to_owned()),
+        deployment_status: Set("PENDING".to_owned()),
         updated_at: Set(updated_at.to_owned()),
     };
 
@@ -62,7 +76,10 @@ pub async fn find_by_id(
         .map_err(|e| anyhow::anyhow!(e))
 }
 
-pub async fn find_by_email(db: &DatabaseConnection, email: &str) -> anyhow::Result<Option<Model>> {
+pub async fn find_by_email(
+    db: &impl ConnectionTrait,
+    email: &str,
+) -> anyhow::Result<Option<Model>> {
     Entity::find()
         .filter(Column::Email.eq(email))
         .one(db)
@@ -81,6 +98,17 @@ pub async fn find_by_wallet_address(
         .map_err(|e| anyhow::anyhow!(e))
 }
 
+pub async fn find_by_eoa_address(
+    db: &DatabaseConnection,
+    eoa_address: String,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::EoaAddress.eq(eoa_address))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
 pub async fn find_all_by_wallet_address(
     db: &DatabaseConnection,
     wallet_address: String,
@@ -125,6 +153,17 @@ pub async fn find_all_by_account_ids(
         .map_err(|e| anyhow::anyhow!(e))
 }
 
+pub async fn find_all_with_unencrypted_private_keys(
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::EoaPrivateDataKey.eq(""))
+        .filter(Column::EoaPrivateAddress.ne(""))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
 pub async fn find_all(db: &DatabaseConnection) -> anyhow::Result<Vec<Model>> {
     Entity::find().all(db).await.map_err(|e| anyhow::anyhow!(e))
 }
@@ -150,3 +189,54 @@ pub async fn update(
         .map_err(|e| anyhow::anyhow!(e))?;
     Ok(())
 }
+
+pub async fn update_private_key(
+    db: &DatabaseConnection,
+    id: String,
+    eoa_private: String,
+    eoa_private_data_key: String,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::EoaPrivateAddress, Expr::value(eoa_private))
+        .col_expr(Column::EoaPrivateDataKey, Expr::value(eoa_private_data_key))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+    Ok(())
+}
+
+pub async fn update_deployment_status(
+    db: &DatabaseConnection,
+    id: String,
+    deployment_status: &str,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::DeploymentStatus, Expr::value(deployment_status))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+    Ok(())
+}
+
+// the wallet owner after a recovery rotated its key, the old custodial key is dropped with it
+pub async fn update_recovered_owner(
+    db: &DatabaseConnection,
+    id: String,
+    eoa_address: String,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::EoaAddress, Expr::value(eoa_address))
+        .col_expr(Column::EoaPrivateAddress, Expr::value(""))
+        .col_expr(Column::EoaPrivateDataKey, Expr::value(""))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+    Ok(())
+}
@@ -1,14 +1,25 @@
 use sea_orm::entity::prelude::*;
 use sea_orm::Database;
 
-use crate::config::settings::Settings;
-use clutch_wallet_lib::utils::wallet_lib::WalletLib;
+use crate::{
+    config::settings::Settings,
+    operations::{
+        email::Mailer, gas_oracle::GasOracle, time::Clock, token::TokenRegistry,
+        treasury::Treasury, wallet_backend::WalletBackend,
+    },
+};
+use std::sync::Arc;
 
 #[derive(Debug, Clone)]
 pub struct AppState {
     pub settings: Settings,
     pub database: DatabaseConnection,
-    pub wallet_lib: WalletLib,
+    pub wallet: Arc<dyn WalletBackend>,
+    pub gas_oracle: Arc<dyn GasOracle>,
+    pub treasury: Arc<Treasury>,
+    pub tokens: Arc<TokenRegistry>,
+    pub mailer: Arc<dyn Mailer>,
+    pub clock: Arc<dyn Clock>,
 }
 
 pub async fn db_connect(connection_url: String) -> DatabaseConnection {
@@ -0,0 +1,134 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{sea_query::Expr, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "deployment_jobs")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub account_id: String,
+    pub paymaster_tokens: Option<String>,
+    pub status: String,
+    pub attempts: i32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub user_op_hash: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &impl ConnectionTrait, deployment_job: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(deployment_job.id),
+        account_id: Set(deployment_job.account_id),
+        paymaster_tokens: Set(deployment_job.paymaster_tokens),
+        status: Set(deployment_job.status),
+        attempts: Set(deployment_job.attempts),
+        next_attempt_at: Set(deployment_job.next_attempt_at),
+        last_error: Set(deployment_job.last_error),
+        user_op_hash: Set(deployment_job.user_op_hash),
+        created_at: Set(deployment_job.created_at),
+        updated_at: Set(deployment_job.updated_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_account_id(
+    db: &DatabaseConnection,
+    account_id: String,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_user_op_hash_and_status(
+    db: &DatabaseConnection,
+    user_op_hash: String,
+    status: &str,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::UserOpHash.eq(user_op_hash))
+        .filter(Column::Status.eq(status))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+// oldest first, jobs waiting out their backoff are left alone
+pub async fn find_all_due(
+    db: &DatabaseConnection,
+    status: &str,
+    now: i64,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Status.eq(status))
+        .filter(Column::NextAttemptAt.lte(now))
+        .order_by_asc(Column::NextAttemptAt)
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn update_attempt(
+    db: &DatabaseConnection,
+    id: String,
+    status: &str,
+    attempts: i32,
+    next_attempt_at: i64,
+    last_error: Option<String>,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::Status, Expr::value(status))
+        .col_expr(Column::Attempts, Expr::value(attempts))
+        .col_expr(Column::NextAttemptAt, Expr::value(next_attempt_at))
+        .col_expr(Column::LastError, Expr::value(last_error))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map(|_| ())
+}
+
+// the deployment user op was accepted by the bundler, the receipt tracker settles it
+pub async fn update_submitted(
+    db: &DatabaseConnection,
+    id: String,
+    status: &str,
+    attempts: i32,
+    user_op_hash: String,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::Status, Expr::value(status))
+        .col_expr(Column::Attempts, Expr::value(attempts))
+        .col_expr(Column::UserOpHash, Expr::value(user_op_hash))
+        .col_expr(Column::LastError, Expr::value(Option::<String>::None))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map(|_| ())
+}
@@ -25,7 +25,7 @@ impl RelationTrait for Relation {
 impl ActiveModelBehavior for ActiveModel {}
 
 pub async fn create(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     id: Uuid,
     guardian_id: String,
     account_id: String,
@@ -57,7 +57,7 @@ pub async fn find_all_accounts_by_guardian_id(
 }
 
 pub async fn find_guardian_by_guardian_id_and_account_id(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     guardian_id: String,
     account_id: String,
 ) -> anyhow::Result<Option<Model>> {
@@ -83,7 +83,7 @@ pub async fn find_all_accounts_by_guardian_id_and_account_id(
 }
 
 pub async fn find_all_guardians_by_account_id(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     account_id: String,
 ) -> anyhow::Result<Vec<Model>> {
     Entity::find()
@@ -94,7 +94,7 @@ pub async fn find_all_guardians_by_account_id(
 }
 
 pub async fn find_all_guardians_by_account_id_and_status(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     account_id: String,
     status: String,
 ) -> anyhow::Result<Vec<Model>> {
@@ -107,13 +107,13 @@ pub async fn find_all_guardians_by_account_id_and_status(
 }
 
 pub async fn find_all_active_guardians_by_account_id(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     account_id: String,
 ) -> anyhow::Result<Vec<Model>> {
     find_all_guardians_by_account_id_and_status(db, account_id, "ACTIVE".to_owned()).await
 }
 
-pub async fn delete_by_id(db: &DatabaseConnection, id: String) -> anyhow::Result<()> {
+pub async fn delete_by_id(db: &impl ConnectionTrait, id: String) -> anyhow::Result<()> {
     Entity::delete_many()
         .filter(Column::Id.eq(id))
         .exec(db)
@@ -123,7 +123,7 @@ pub async fn delete_by_id(db: &DatabaseConnection, id: String) -> anyhow::Result
 }
 
 pub async fn find_all_guardians_for_account_by_ids(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     ids: Vec<String>,
     account_id: String,
 ) -> anyhow::Result<Vec<Model>> {
@@ -136,7 +136,7 @@ pub async fn find_all_guardians_for_account_by_ids(
 }
 
 pub async fn update_all_guardians_for_account_to_status(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     account_id: String,
     status: String,
 ) -> anyhow::Result<()> {
@@ -150,7 +150,7 @@ pub async fn update_all_guardians_for_account_to_status(
 }
 
 pub async fn update_guardians_for_account_to_status(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     account_id: String,
     ids: Vec<String>,
     status: String,
@@ -164,3 +164,17 @@ pub async fn update_guardians_for_account_to_status(
         .map_err(|e| anyhow::anyhow!(e))
         .map(|_| ())
 }
+
+pub async fn update_status_by_id(
+    db: &impl ConnectionTrait,
+    id: String,
+    status: String,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .filter(Column::Id.eq(id))
+        .col_expr(Column::Status, Expr::value(status))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map(|_| ())
+}
@@ -0,0 +1,64 @@
+use sea_orm::Set;
+use sea_orm::{entity::prelude::*, sea_query::Expr};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "guardian_challenges")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub nonce: String,
+    pub guardian_id: String,
+    pub nomination_id: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub consumed_at: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, model: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        nonce: Set(model.nonce),
+        guardian_id: Set(model.guardian_id),
+        nomination_id: Set(model.nomination_id),
+        created_at: Set(model.created_at),
+        expires_at: Set(model.expires_at),
+        consumed_at: Set(model.consumed_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+// a challenge is only good once, for the guardian and nomination it was issued for
+pub async fn consume(
+    db: &impl ConnectionTrait,
+    nonce: String,
+    guardian_id: String,
+    nomination_id: String,
+    now: i64,
+) -> anyhow::Result<bool> {
+    Entity::update_many()
+        .col_expr(Column::ConsumedAt, Expr::value(now))
+        .filter(Column::Nonce.eq(nonce))
+        .filter(Column::GuardianId.eq(guardian_id))
+        .filter(Column::NominationId.eq(nomination_id))
+        .filter(Column::ConsumedAt.is_null())
+        .filter(Column::ExpiresAt.gt(now))
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected == 1)
+        .map_err(|e| anyhow::anyhow!(e))
+}
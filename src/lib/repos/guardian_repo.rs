@@ -1,5 +1,5 @@
-use sea_orm::entity::prelude::*;
 use sea_orm::Set;
+use sea_orm::{entity::prelude::*, sea_query::Expr, Condition};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -11,6 +11,9 @@ pub struct Model {
     pub email: String,
     pub account_id: Option<String>,
     pub wallet_address: Option<String>,
+    // the wallet the guardian proved to control when accepting a nomination
+    pub verified_address: Option<String>,
+    pub verified_at: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -25,7 +28,7 @@ impl RelationTrait for Relation {
 impl ActiveModelBehavior for ActiveModel {}
 
 pub async fn create(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     id: Uuid,
     email: String,
     account_id: Option<String>,
@@ -36,6 +39,8 @@ pub async fn create(
         email: Set(email.to_owned()),
         account_id: Set(account_id.to_owned()),
         wallet_address: Set(wallet_address.to_owned()),
+        verified_address: Set(None),
+        verified_at: Set(None),
     };
 
     Entity::insert(model)
@@ -46,7 +51,7 @@ pub async fn create(
 }
 
 pub async fn find_by_email(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     email: String,
 ) -> anyhow::Result<Option<Model>> {
     Entity::find()
@@ -79,7 +84,7 @@ pub async fn find_by_id(
 }
 
 pub async fn find_all_by_ids(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     guardian_ids: Vec<String>,
 ) -> anyhow::Result<Vec<Model>> {
     Entity::find()
@@ -88,3 +93,29 @@ pub async fn find_all_by_ids(
         .await
         .map_err(|e| anyhow::anyhow!(e))
 }
+
+// a guardian guards every account with the one wallet they verified first, false when they
+// verified another one
+pub async fn update_verified_address(
+    db: &impl ConnectionTrait,
+    id: String,
+    verified_address: String,
+    verified_at: i64,
+) -> anyhow::Result<bool> {
+    Entity::update_many()
+        .col_expr(
+            Column::VerifiedAddress,
+            Expr::value(verified_address.clone()),
+        )
+        .col_expr(Column::VerifiedAt, Expr::value(verified_at))
+        .filter(Column::Id.eq(id))
+        .filter(
+            Condition::any()
+                .add(Column::VerifiedAddress.is_null())
+                .add(Column::VerifiedAddress.eq(verified_address)),
+        )
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected == 1)
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -10,8 +10,15 @@ use crate::models::api::SigningStrategy;
 pub struct Model {
     #[sea_orm(primary_key)]
     pub id: String,
-    pub signers: SigningStrategy,
     pub account_id: String,
+    pub threshold: i32,
+    pub total: i32,
+}
+
+impl Model {
+    pub fn signers(&self) -> SigningStrategy {
+        SigningStrategy::new(self.threshold as u32, self.total as u32)
+    }
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -26,15 +33,16 @@ impl RelationTrait for Relation {
 impl ActiveModelBehavior for ActiveModel {}
 
 pub async fn create(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     id: Uuid,
     signers: SigningStrategy,
     account_id: String,
 ) -> anyhow::Result<()> {
     let model = ActiveModel {
         id: Set(id.to_string()),
-        signers: Set(signers.to_owned()),
         account_id: Set(account_id.to_owned()),
+        threshold: Set(signers.threshold as i32),
+        total: Set(signers.total as i32),
     };
 
     Entity::insert(model)
@@ -45,7 +53,7 @@ pub async fn create(
 }
 
 pub async fn find_for_account_id(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     account_id: String,
 ) -> anyhow::Result<Option<Model>> {
     Entity::find()
@@ -56,12 +64,13 @@ pub async fn find_for_account_id(
 }
 
 pub async fn update_settings_for_account_id(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     account_id: String,
     signers: SigningStrategy,
 ) -> anyhow::Result<()> {
     Entity::update_many()
-        .col_expr(Column::Signers, Expr::value(signers.to_owned()))
+        .col_expr(Column::Threshold, Expr::value(signers.threshold as i32))
+        .col_expr(Column::Total, Expr::value(signers.total as i32))
         .filter(Column::AccountId.eq(account_id))
         .exec(db)
         .await
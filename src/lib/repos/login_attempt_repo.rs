@@ -0,0 +1,66 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "login_attempts")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub email: String,
+    pub account_id: Option<String>,
+    pub method: String,
+    pub success: bool,
+    pub reason: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(
+    db: &DatabaseConnection,
+    id: Uuid,
+    email: String,
+    account_id: Option<String>,
+    method: String,
+    success: bool,
+    reason: Option<String>,
+    created_at: i64,
+) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(id.to_string()),
+        email: Set(email.to_owned()),
+        account_id: Set(account_id.to_owned()),
+        method: Set(method.to_owned()),
+        success: Set(success),
+        reason: Set(reason.to_owned()),
+        created_at: Set(created_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_email(
+    db: &DatabaseConnection,
+    email: String,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Email.eq(email))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -1,6 +1,10 @@
+use crate::operations::encryption::encrypt_private_key;
+use crate::repos::account_repo;
+use ethers::signers::LocalWallet;
 use refinery::config::Config;
 use refinery::config::ConfigDbType;
 use refinery::embed_migrations;
+use sea_orm::DatabaseConnection;
 use std::fs;
 use std::fs::File;
 use std::path::Path;
@@ -25,3 +29,43 @@ pub fn migrate(url: &str) {
     let mut c = Config::new(ConfigDbType::Sqlite).set_db_path(url);
     migrations::runner().run(&mut c).unwrap();
 }
+
+// Accounts created before envelope encryption store the EOA private key as plain hex,
+// seal those with a fresh data key so no plaintext keys remain in the database. Accounts
+// already sealed aren't loaded, one that fails is logged and left for the next start.
+pub async fn encrypt_private_keys(db: &DatabaseConnection, master_key: &str) -> anyhow::Result<()> {
+    let accounts = account_repo::find_all_with_unencrypted_private_keys(db).await?;
+
+    for account in accounts {
+        if let Err(e) = encrypt_account_private_key(db, master_key, &account).await {
+            log::warn!(
+                "Error encrypting private key for account {}: {}",
+                account.id,
+                e
+            );
+        }
+    }
+    Ok(())
+}
+
+async fn encrypt_account_private_key(
+    db: &DatabaseConnection,
+    master_key: &str,
+    account: &account_repo::Model,
+) -> anyhow::Result<()> {
+    // anything but a plain hex key, like a partly encrypted one, would be sealed as garbage
+    account
+        .eoa_private_address
+        .parse::<LocalWallet>()
+        .map_err(|_| anyhow::anyhow!("Stored private key is not a plain hex key"))?;
+    let encrypted = encrypt_private_key(master_key, &account.eoa_private_address)?;
+    account_repo::update_private_key(
+        db,
+        account.id.clone(),
+        encrypted.ciphertext,
+        encrypted.data_key,
+    )
+    .await?;
+    log::info!("encrypted private key for account {}", account.id);
+    Ok(())
+}
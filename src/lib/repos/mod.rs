@@ -1,8 +1,23 @@
 pub mod account_repo;
 pub mod db;
+pub mod deployment_job_repo;
 pub mod guardian_account_repo;
+pub mod guardian_challenge_repo;
 pub mod guardian_repo;
 pub mod guardian_settings_repo;
+pub mod login_attempt_repo;
 pub mod migration;
+pub mod nomination_history_repo;
+pub mod nomination_invitation_repo;
 pub mod nomination_repo;
+pub mod paymaster_policy_repo;
+pub mod paymaster_sponsorship_repo;
+pub mod recovery_approval_repo;
+pub mod recovery_repo;
+pub mod session_repo;
+pub mod siwe_nonce_repo;
+pub mod token_repo;
+pub mod treasury_funding_repo;
+pub mod unit_of_work;
+pub mod user_operation_repo;
 pub mod verification_repo;
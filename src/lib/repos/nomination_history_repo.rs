@@ -0,0 +1,54 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "nomination_history")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub nomination_id: String,
+    // None when the nomination was created
+    pub from_status: Option<String>,
+    pub to_status: String,
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &impl ConnectionTrait, history: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(history.id),
+        nomination_id: Set(history.nomination_id),
+        from_status: Set(history.from_status),
+        to_status: Set(history.to_status),
+        created_at: Set(history.created_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_nomination_id(
+    db: &DatabaseConnection,
+    nomination_id: String,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::NominationId.eq(nomination_id))
+        .order_by_asc(Column::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,90 @@
+use sea_orm::{entity::prelude::*, sea_query::Expr};
+use sea_orm::{QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "nomination_invitations")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub nomination_id: String,
+    pub email: String,
+    pub kind: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub consumed_at: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, invitation: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(invitation.id),
+        nomination_id: Set(invitation.nomination_id),
+        email: Set(invitation.email),
+        kind: Set(invitation.kind),
+        created_at: Set(invitation.created_at),
+        expires_at: Set(invitation.expires_at),
+        consumed_at: Set(invitation.consumed_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_id(db: &DatabaseConnection, id: String) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::Id.eq(id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_latest_by_nomination_id(
+    db: &DatabaseConnection,
+    nomination_id: String,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::NominationId.eq(nomination_id))
+        .order_by_desc(Column::CreatedAt)
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn count_by_email_created_after(
+    db: &DatabaseConnection,
+    email: &str,
+    created_after: i64,
+) -> anyhow::Result<u64> {
+    Entity::find()
+        .filter(Column::Email.eq(email))
+        .filter(Column::CreatedAt.gt(created_after))
+        .count(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn consume(db: &impl ConnectionTrait, id: String, now: i64) -> anyhow::Result<bool> {
+    Entity::update_many()
+        .col_expr(Column::ConsumedAt, Expr::value(now))
+        .filter(Column::Id.eq(id))
+        .filter(Column::ConsumedAt.is_null())
+        .filter(Column::ExpiresAt.gt(now))
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected == 1)
+        .map_err(|e| anyhow::anyhow!(e))
+}
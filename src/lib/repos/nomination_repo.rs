@@ -13,6 +13,7 @@ pub struct Model {
     pub guardian_id: String,
     pub account_id: String,
     pub status: String,
+    pub expires_at: Option<i64>,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -27,12 +28,13 @@ impl RelationTrait for Relation {
 impl ActiveModelBehavior for ActiveModel {}
 
 pub async fn create(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     id: Uuid,
     email: String,
     account_id: String,
     guardian_id: String,
     status: String,
+    expires_at: Option<i64>,
 ) -> anyhow::Result<()> {
     let model = ActiveModel {
         id: Set(id.to_string()),
@@ -40,6 +42,7 @@ pub async fn create(
         account_id: Set(account_id.to_owned()),
         guardian_id: Set(guardian_id.to_owned()),
         status: Set(status.to_owned()),
+        expires_at: Set(expires_at),
     };
 
     Entity::insert(model)
@@ -62,20 +65,6 @@ pub async fn find_by_account_and_id(
         .map_err(|e| anyhow::anyhow!(e))
 }
 
-pub async fn delete_by_account_and_id(
-    db: &DatabaseConnection,
-    account_id: String,
-    nomination_id: String,
-) -> anyhow::Result<()> {
-    Entity::delete_many()
-        .filter(Column::AccountId.eq(account_id))
-        .filter(Column::Id.eq(nomination_id))
-        .exec(db)
-        .await
-        .map_err(|e| anyhow::anyhow!(e))
-        .map(|_| ())
-}
-
 pub async fn find_all_by_account(
     db: &DatabaseConnection,
     account_id: String,
@@ -163,18 +152,65 @@ pub async fn find_all_by_guardian_and_status(
         .map_err(|e| anyhow::anyhow!(e))
 }
 
-pub async fn update_status_by_guardian_id(
+pub async fn find_by_id(
     db: &DatabaseConnection,
     nomination_id: String,
-    guardian_id: String,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::Id.eq(nomination_id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_status(
+    db: &DatabaseConnection,
     status: String,
-) -> anyhow::Result<()> {
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Status.eq(status))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+// only moves the nomination out of `from`, false when another request changed it first
+pub async fn update_status_from(
+    db: &impl ConnectionTrait,
+    nomination_id: String,
+    from: String,
+    to: String,
+) -> anyhow::Result<bool> {
     Entity::update_many()
-        .col_expr(Column::Status, Expr::value(status))
+        .col_expr(Column::Status, Expr::value(to))
         .filter(Column::Id.eq(nomination_id))
-        .filter(Column::GuardianId.eq(guardian_id))
+        .filter(Column::Status.eq(from))
         .exec(db)
         .await
+        .map(|res| res.rows_affected > 0)
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_account_and_guardian_and_statuses(
+    db: &impl ConnectionTrait,
+    account_id: String,
+    guardian_id: String,
+    statuses: Vec<&str>,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::GuardianId.eq(guardian_id))
+        .filter(Column::Status.is_in(statuses))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_expired(db: &DatabaseConnection, now: i64) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Status.eq("PENDING"))
+        .filter(Column::ExpiresAt.lte(now))
+        .all(db)
+        .await
         .map_err(|e| anyhow::anyhow!(e))
-        .map(|_| ())
 }
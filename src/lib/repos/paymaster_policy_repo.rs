@@ -0,0 +1,71 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{Condition, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+/// A sponsorship policy of the verifying paymaster. Policies without an account apply to every
+/// account, the gas budget is per account and in wei, allowed targets is a JSON array of
+/// addresses and the validity window is in unix ms.
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "paymaster_policies")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub account_id: Option<String>,
+    pub gas_budget: Option<String>,
+    pub allowed_targets: Option<String>,
+    pub valid_from: Option<i64>,
+    pub valid_until: Option<i64>,
+    pub active: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, policy: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(policy.id),
+        account_id: Set(policy.account_id),
+        gas_budget: Set(policy.gas_budget),
+        allowed_targets: Set(policy.allowed_targets),
+        valid_from: Set(policy.valid_from),
+        valid_until: Set(policy.valid_until),
+        active: Set(policy.active),
+        created_at: Set(policy.created_at),
+        updated_at: Set(policy.updated_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+/// Active policies of the account followed by the ones applying to every account.
+pub async fn find_all_active_for_account(
+    db: &DatabaseConnection,
+    account_id: String,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Active.eq(true))
+        .filter(
+            Condition::any()
+                .add(Column::AccountId.eq(account_id))
+                .add(Column::AccountId.is_null()),
+        )
+        .order_by_desc(Column::AccountId)
+        .order_by_asc(Column::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,56 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "paymaster_sponsorships")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub policy_id: String,
+    pub account_id: String,
+    pub user_op_hash: String,
+    pub max_gas_cost: String,
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, sponsorship: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(sponsorship.id),
+        policy_id: Set(sponsorship.policy_id),
+        account_id: Set(sponsorship.account_id),
+        user_op_hash: Set(sponsorship.user_op_hash),
+        max_gas_cost: Set(sponsorship.max_gas_cost),
+        created_at: Set(sponsorship.created_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_policy_id_and_account_id(
+    db: &DatabaseConnection,
+    policy_id: String,
+    account_id: String,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::PolicyId.eq(policy_id))
+        .filter(Column::AccountId.eq(account_id))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,68 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "recovery_approvals")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub recovery_id: String,
+    pub guardian_id: String,
+    pub signer: String,
+    pub signature: String,
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, approval: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(approval.id),
+        recovery_id: Set(approval.recovery_id),
+        guardian_id: Set(approval.guardian_id),
+        signer: Set(approval.signer),
+        signature: Set(approval.signature),
+        created_at: Set(approval.created_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_recovery_id(
+    db: &DatabaseConnection,
+    recovery_id: String,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::RecoveryId.eq(recovery_id))
+        .order_by_asc(Column::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_recovery_id_and_guardian_id(
+    db: &DatabaseConnection,
+    recovery_id: String,
+    guardian_id: String,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::RecoveryId.eq(recovery_id))
+        .filter(Column::GuardianId.eq(guardian_id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,158 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{sea_query::Expr, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "recoveries")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub account_id: String,
+    pub new_owner: String,
+    pub status: String,
+    pub executable_at: i64,
+    pub user_op_hash: Option<String>,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &impl ConnectionTrait, recovery: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(recovery.id),
+        account_id: Set(recovery.account_id),
+        new_owner: Set(recovery.new_owner),
+        status: Set(recovery.status),
+        executable_at: Set(recovery.executable_at),
+        user_op_hash: Set(recovery.user_op_hash),
+        last_error: Set(recovery.last_error),
+        created_at: Set(recovery.created_at),
+        updated_at: Set(recovery.updated_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_id(db: &DatabaseConnection, id: String) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::Id.eq(id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_account_id_and_id(
+    db: &DatabaseConnection,
+    account_id: String,
+    id: String,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::Id.eq(id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_account_id_and_statuses(
+    db: &impl ConnectionTrait,
+    account_id: String,
+    statuses: Vec<&str>,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::Status.is_in(statuses))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+// newest first
+pub async fn find_all_by_account_id(
+    db: &DatabaseConnection,
+    account_id: String,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .order_by_desc(Column::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_account_ids_and_status(
+    db: &DatabaseConnection,
+    account_ids: Vec<String>,
+    status: &str,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.is_in(account_ids))
+        .filter(Column::Status.eq(status))
+        .order_by_asc(Column::CreatedAt)
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_user_op_hash_and_status(
+    db: &DatabaseConnection,
+    user_op_hash: String,
+    status: &str,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::UserOpHash.eq(user_op_hash))
+        .filter(Column::Status.eq(status))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+// recoveries still inside their timelock are left alone
+pub async fn find_all_due(
+    db: &DatabaseConnection,
+    status: &str,
+    now: i64,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Status.eq(status))
+        .filter(Column::ExecutableAt.lte(now))
+        .order_by_asc(Column::ExecutableAt)
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn update_status(
+    db: &DatabaseConnection,
+    id: String,
+    status: &str,
+    user_op_hash: Option<String>,
+    last_error: Option<String>,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::Status, Expr::value(status))
+        .col_expr(Column::UserOpHash, Expr::value(user_op_hash))
+        .col_expr(Column::LastError, Expr::value(last_error))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map(|_| ())
+}
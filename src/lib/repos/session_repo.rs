@@ -0,0 +1,166 @@
+use sea_orm::Set;
+use sea_orm::{entity::prelude::*, sea_query::Expr};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "sessions")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub account_id: String,
+    #[serde(skip_serializing)]
+    pub refresh_token_hash: String,
+    pub device: Option<String>,
+    pub user_agent: Option<String>,
+    pub created_at: i64,
+    pub last_seen_at: i64,
+    pub expires_at: i64,
+    pub revoked_at: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(
+    db: &DatabaseConnection,
+    id: Uuid,
+    account_id: String,
+    refresh_token_hash: String,
+    device: Option<String>,
+    user_agent: Option<String>,
+    created_at: i64,
+    expires_at: i64,
+) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(id.to_string()),
+        account_id: Set(account_id.to_owned()),
+        refresh_token_hash: Set(refresh_token_hash.to_owned()),
+        device: Set(device.to_owned()),
+        user_agent: Set(user_agent.to_owned()),
+        created_at: Set(created_at),
+        last_seen_at: Set(created_at),
+        expires_at: Set(expires_at),
+        revoked_at: Set(None),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_id(db: &DatabaseConnection, id: String) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::Id.eq(id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_active_by_refresh_token_hash(
+    db: &DatabaseConnection,
+    refresh_token_hash: String,
+    now: i64,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::RefreshTokenHash.eq(refresh_token_hash))
+        .filter(Column::RevokedAt.is_null())
+        .filter(Column::ExpiresAt.gt(now))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_active_by_account_id(
+    db: &DatabaseConnection,
+    account_id: String,
+    now: i64,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::RevokedAt.is_null())
+        .filter(Column::ExpiresAt.gt(now))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn rotate_refresh_token(
+    db: &DatabaseConnection,
+    id: String,
+    refresh_token_hash: String,
+    new_refresh_token_hash: String,
+    last_seen_at: i64,
+    expires_at: i64,
+) -> anyhow::Result<bool> {
+    Entity::update_many()
+        .col_expr(
+            Column::RefreshTokenHash,
+            Expr::value(new_refresh_token_hash),
+        )
+        .col_expr(Column::LastSeenAt, Expr::value(last_seen_at))
+        .col_expr(Column::ExpiresAt, Expr::value(expires_at))
+        .filter(Column::Id.eq(id))
+        .filter(Column::RefreshTokenHash.eq(refresh_token_hash))
+        .filter(Column::RevokedAt.is_null())
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected == 1)
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn update_last_seen(
+    db: &DatabaseConnection,
+    id: String,
+    last_seen_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::LastSeenAt, Expr::value(last_seen_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map(|_| ())
+}
+
+pub async fn revoke_by_account_and_id(
+    db: &DatabaseConnection,
+    account_id: String,
+    id: String,
+    revoked_at: i64,
+) -> anyhow::Result<bool> {
+    Entity::update_many()
+        .col_expr(Column::RevokedAt, Expr::value(revoked_at))
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::Id.eq(id))
+        .filter(Column::RevokedAt.is_null())
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected == 1)
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn revoke_all_by_account(
+    db: &DatabaseConnection,
+    account_id: String,
+    revoked_at: i64,
+) -> anyhow::Result<u64> {
+    Entity::update_many()
+        .col_expr(Column::RevokedAt, Expr::value(revoked_at))
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::RevokedAt.is_null())
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected)
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,56 @@
+use sea_orm::Set;
+use sea_orm::{entity::prelude::*, sea_query::Expr};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "siwe_nonces")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub nonce: String,
+    pub created_at: i64,
+    pub expires_at: i64,
+    pub consumed_at: Option<i64>,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(
+    db: &DatabaseConnection,
+    nonce: String,
+    created_at: i64,
+    expires_at: i64,
+) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        nonce: Set(nonce.to_owned()),
+        created_at: Set(created_at),
+        expires_at: Set(expires_at),
+        consumed_at: Set(None),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn consume(db: &DatabaseConnection, nonce: String, now: i64) -> anyhow::Result<bool> {
+    Entity::update_many()
+        .col_expr(Column::ConsumedAt, Expr::value(now))
+        .filter(Column::Nonce.eq(nonce))
+        .filter(Column::ConsumedAt.is_null())
+        .filter(Column::ExpiresAt.gt(now))
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected == 1)
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,80 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{sea_query::Expr, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "tokens")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub address: String,
+    pub chain_id: i64,
+    pub symbol: Option<String>,
+    pub decimals: i32,
+    pub paymaster_supported: bool,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, token: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(token.id),
+        address: Set(token.address),
+        chain_id: Set(token.chain_id),
+        symbol: Set(token.symbol),
+        decimals: Set(token.decimals),
+        paymaster_supported: Set(token.paymaster_supported),
+        created_at: Set(token.created_at),
+        updated_at: Set(token.updated_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_by_address_and_chain_id(
+    db: &impl ConnectionTrait,
+    address: String,
+    chain_id: i64,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::Address.eq(address))
+        .filter(Column::ChainId.eq(chain_id))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn update(
+    db: &DatabaseConnection,
+    id: String,
+    symbol: Option<String>,
+    decimals: i32,
+    paymaster_supported: bool,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::Symbol, Expr::value(symbol))
+        .col_expr(Column::Decimals, Expr::value(decimals))
+        .col_expr(Column::PaymasterSupported, Expr::value(paymaster_supported))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -0,0 +1,73 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::Set;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "treasury_fundings")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub account_id: String,
+    pub recipient: String,
+    pub amount: String,
+    pub transaction_hash: Option<String>,
+    pub status: String,
+    pub error: Option<String>,
+    pub created_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, funding: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(funding.id),
+        account_id: Set(funding.account_id),
+        recipient: Set(funding.recipient),
+        amount: Set(funding.amount),
+        transaction_hash: Set(funding.transaction_hash),
+        status: Set(funding.status),
+        error: Set(funding.error),
+        created_at: Set(funding.created_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_account_id_and_status(
+    db: &DatabaseConnection,
+    account_id: String,
+    status: &str,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::Status.eq(status))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_status_created_after(
+    db: &DatabaseConnection,
+    status: &str,
+    created_after: i64,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Status.eq(status))
+        .filter(Column::CreatedAt.gte(created_after))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
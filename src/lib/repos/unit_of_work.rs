@@ -0,0 +1,26 @@
+use sea_orm::{DatabaseConnection, DatabaseTransaction, TransactionTrait};
+
+/// Repo calls made with `db()` are committed together by `commit`. Dropping the unit of work
+/// without committing, like returning early on an error, rolls all of them back.
+pub struct UnitOfWork {
+    txn: DatabaseTransaction,
+}
+
+impl UnitOfWork {
+    pub async fn begin(db: &DatabaseConnection) -> anyhow::Result<UnitOfWork> {
+        db.begin()
+            .await
+            .map(|txn| UnitOfWork { txn })
+            .map_err(|e| anyhow::anyhow!(e))
+    }
+
+    /// Reads through it see the writes of the unit of work, write only through it until it is
+    /// committed, sqlite can't take another writer in the meantime.
+    pub fn db(&self) -> &DatabaseTransaction {
+        &self.txn
+    }
+
+    pub async fn commit(self) -> anyhow::Result<()> {
+        self.txn.commit().await.map_err(|e| anyhow::anyhow!(e))
+    }
+}
@@ -0,0 +1,166 @@
+use sea_orm::entity::prelude::*;
+use sea_orm::{sea_query::Expr, QueryOrder, Set};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Debug, PartialEq, DeriveEntityModel, Serialize, Deserialize)]
+#[sea_orm(table_name = "user_operations")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: String,
+    pub account_id: String,
+    pub user_op_hash: String,
+    pub sender: String,
+    pub nonce: String,
+    pub call_data_summary: String,
+    pub call_gas_limit: String,
+    pub verification_gas_limit: String,
+    pub pre_verification_gas: String,
+    pub max_fee_per_gas: String,
+    pub max_priority_fee_per_gas: String,
+    pub paymaster: Option<String>,
+    pub bundler_response: Option<String>,
+    pub status: String,
+    pub transaction_hash: Option<String>,
+    pub block_number: Option<i64>,
+    pub actual_gas_cost: Option<String>,
+    pub created_at: i64,
+    pub updated_at: i64,
+}
+
+#[derive(Copy, Clone, Debug, EnumIter)]
+pub enum Relation {}
+
+impl RelationTrait for Relation {
+    fn def(&self) -> RelationDef {
+        panic!("No RelationDef")
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+pub async fn create(db: &DatabaseConnection, user_operation: Model) -> anyhow::Result<()> {
+    let model = ActiveModel {
+        id: Set(user_operation.id),
+        account_id: Set(user_operation.account_id),
+        user_op_hash: Set(user_operation.user_op_hash),
+        sender: Set(user_operation.sender),
+        nonce: Set(user_operation.nonce),
+        call_data_summary: Set(user_operation.call_data_summary),
+        call_gas_limit: Set(user_operation.call_gas_limit),
+        verification_gas_limit: Set(user_operation.verification_gas_limit),
+        pre_verification_gas: Set(user_operation.pre_verification_gas),
+        max_fee_per_gas: Set(user_operation.max_fee_per_gas),
+        max_priority_fee_per_gas: Set(user_operation.max_priority_fee_per_gas),
+        paymaster: Set(user_operation.paymaster),
+        bundler_response: Set(user_operation.bundler_response),
+        status: Set(user_operation.status),
+        transaction_hash: Set(user_operation.transaction_hash),
+        block_number: Set(user_operation.block_number),
+        actual_gas_cost: Set(user_operation.actual_gas_cost),
+        created_at: Set(user_operation.created_at),
+        updated_at: Set(user_operation.updated_at),
+    };
+
+    Entity::insert(model)
+        .exec_without_returning(db)
+        .await
+        .map(|_| ())
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+// newest first, returns the requested page and the total number of matches
+pub async fn find_page_by_account(
+    db: &DatabaseConnection,
+    account_id: String,
+    status: Option<String>,
+    created_after: Option<i64>,
+    created_before: Option<i64>,
+    page: u64,
+    page_size: u64,
+) -> anyhow::Result<(Vec<Model>, u64)> {
+    let mut query = Entity::find().filter(Column::AccountId.eq(account_id));
+    if let Some(status) = status {
+        query = query.filter(Column::Status.eq(status));
+    }
+    if let Some(created_after) = created_after {
+        query = query.filter(Column::CreatedAt.gte(created_after));
+    }
+    if let Some(created_before) = created_before {
+        query = query.filter(Column::CreatedAt.lt(created_before));
+    }
+
+    let paginator = query
+        .order_by_desc(Column::CreatedAt)
+        .paginate(db, page_size);
+    let total = paginator
+        .num_items()
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+    let user_operations = paginator
+        .fetch_page(page)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))?;
+    Ok((user_operations, total))
+}
+
+pub async fn find_by_account_and_user_op_hash(
+    db: &DatabaseConnection,
+    account_id: String,
+    user_op_hash: String,
+) -> anyhow::Result<Option<Model>> {
+    Entity::find()
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::UserOpHash.eq(user_op_hash))
+        .one(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn find_all_by_statuses(
+    db: &DatabaseConnection,
+    statuses: Vec<&str>,
+) -> anyhow::Result<Vec<Model>> {
+    Entity::find()
+        .filter(Column::Status.is_in(statuses))
+        .all(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+}
+
+pub async fn update_status(
+    db: &DatabaseConnection,
+    id: String,
+    status: &str,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::Status, Expr::value(status))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map(|_| ())
+}
+
+pub async fn update_receipt(
+    db: &DatabaseConnection,
+    id: String,
+    status: &str,
+    transaction_hash: String,
+    block_number: i64,
+    actual_gas_cost: String,
+    updated_at: i64,
+) -> anyhow::Result<()> {
+    Entity::update_many()
+        .col_expr(Column::Status, Expr::value(status))
+        .col_expr(Column::TransactionHash, Expr::value(transaction_hash))
+        .col_expr(Column::BlockNumber, Expr::value(block_number))
+        .col_expr(Column::ActualGasCost, Expr::value(actual_gas_cost))
+        .col_expr(Column::UpdatedAt, Expr::value(updated_at))
+        .filter(Column::Id.eq(id))
+        .exec(db)
+        .await
+        .map_err(|e| anyhow::anyhow!(e))
+        .map(|_| ())
+}
@@ -1,5 +1,5 @@
-use sea_orm::entity::prelude::*;
 use sea_orm::Set;
+use sea_orm::{entity::prelude::*, sea_query::Expr};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
@@ -11,6 +11,8 @@ pub struct Model {
     pub email: String,
     pub code: String,
     pub expires_at: i64,
+    pub consumed_at: Option<i64>,
+    pub created_at: i64,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter)]
@@ -30,12 +32,15 @@ pub async fn create(
     email: &str,
     code: &str,
     expires_at: i64,
+    created_at: i64,
 ) -> anyhow::Result<()> {
     let model = ActiveModel {
         id: Set(id.to_string()),
         email: Set(email.to_owned()),
         code: Set(code.to_owned()),
         expires_at: Set(expires_at.to_owned()),
+        consumed_at: Set(None),
+        created_at: Set(created_at),
     };
 
     Entity::insert(model)
@@ -45,9 +50,14 @@ pub async fn create(
         .map_err(|e| anyhow::anyhow!(e))
 }
 
-pub async fn count_by_email(db: &DatabaseConnection, email: &str) -> anyhow::Result<u64> {
+pub async fn count_by_email_created_after(
+    db: &DatabaseConnection,
+    email: &str,
+    created_after: i64,
+) -> anyhow::Result<u64> {
     Entity::find()
         .filter(Column::Email.eq(email))
+        .filter(Column::CreatedAt.gt(created_after))
         .count(db)
         .await
         .map_err(|e| anyhow::anyhow!(e))
@@ -65,14 +75,30 @@ pub async fn find_all_by_email(
 }
 
 pub async fn find_by_email_and_code(
-    db: &DatabaseConnection,
+    db: &impl ConnectionTrait,
     email: &str,
     code: &str,
 ) -> anyhow::Result<Option<Model>> {
     Entity::find()
         .filter(Column::Email.eq(email))
         .filter(Column::Code.eq(code))
+        .filter(Column::ConsumedAt.is_null())
         .one(db)
         .await
         .map_err(|e| anyhow::anyhow!(e))
 }
+
+pub async fn consume(
+    db: &impl ConnectionTrait,
+    id: String,
+    consumed_at: i64,
+) -> anyhow::Result<bool> {
+    Entity::update_many()
+        .col_expr(Column::ConsumedAt, Expr::value(consumed_at))
+        .filter(Column::Id.eq(id))
+        .filter(Column::ConsumedAt.is_null())
+        .exec(db)
+        .await
+        .map(|r| r.rows_affected == 1)
+        .map_err(|e| anyhow::anyhow!(e))
+}
@@ -1,14 +1,18 @@
 use crate::{
-    models::api::{
-        api_error, api_success, AccountGuardian, AccountGuardianDeleteResponse,
-        AccountGuardianParams, ApiErrorResponse, ApiResponse, ListAccountGuardiansResponse,
+    models::{
+        api::{
+            api_success, AccountGuardian, AccountGuardianDeleteResponse, AccountGuardianParams,
+            ApiErrorResponse, ApiResponse, ListAccountGuardiansResponse,
+        },
+        error::ApiError,
     },
-    operations::jwt::{decode_jwt, validate_jwt_claims},
+    operations::nomination::{transition, ACCEPTED, CANCELLED},
     repos::{
         account_repo,
         db::AppState,
         guardian_account_repo::{self, Model},
-        guardian_repo,
+        guardian_repo, nomination_repo,
+        unit_of_work::UnitOfWork,
     },
 };
 use axum::{
@@ -16,11 +20,9 @@ use axum::{
     routing::{delete, get},
     Json, Router,
 };
-use axum_auth::AuthBearer;
-use hyper::StatusCode;
-use sea_orm::DatabaseConnection;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
 
-use super::guardian_settings_api;
+use super::{extractors::AuthenticatedAccount, guardian_settings_api};
 
 pub fn routes<S>(app_state: &AppState) -> Router<S> {
     Router::new()
@@ -32,91 +34,102 @@ pub fn routes<S>(app_state: &AppState) -> Router<S> {
 
 async fn remove_account_guardian(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Path(guardian_id): Path<String>,
-) -> Result<Json<ApiResponse<AccountGuardianDeleteResponse, ApiErrorResponse>>, StatusCode> {
-    match try_delete_guardian(app_state, token, guardian_id).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<AccountGuardianDeleteResponse, ApiErrorResponse>>, ApiError> {
+    try_delete_guardian(app_state, account, guardian_id)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_delete_guardian(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     guardian_id: String,
-) -> anyhow::Result<AccountGuardianDeleteResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => {
-            guardian_repo::find_by_id(&app_state.database, guardian_id.clone()).await?;
-            let account_guardian =
-                guardian_account_repo::find_guardian_by_guardian_id_and_account_id(
-                    &app_state.database,
-                    guardian_id.clone(),
+) -> Result<AccountGuardianDeleteResponse, ApiError> {
+    guardian_repo::find_by_id(&app_state.database, guardian_id.clone()).await?;
+    let account_guardian = guardian_account_repo::find_guardian_by_guardian_id_and_account_id(
+        &app_state.database,
+        guardian_id.clone(),
+        acc.id.clone(),
+    )
+    .await?;
+    match account_guardian {
+        Some(ag) => {
+            if ag.status == "AVAILABLE" {
+                let uow = UnitOfWork::begin(&app_state.database).await?;
+                guardian_account_repo::delete_by_id(uow.db(), ag.id).await?;
+                cancel_accepted_nominations(
+                    uow.db(),
                     acc.id,
+                    guardian_id.clone(),
+                    app_state.clock.now_ms(),
                 )
                 .await?;
-            match account_guardian {
-                Some(ag) => {
-                    if ag.status == "AVAILABLE" {
-                        guardian_account_repo::delete_by_id(&app_state.database, ag.id).await?;
-                        Ok(AccountGuardianDeleteResponse {
-                            guardian_id: guardian_id.clone(),
-                        })
-                    } else {
-                        Err(anyhow::anyhow!("Guardian must not be ACTIVE"))
-                    }
-                }
-                None => Err(anyhow::anyhow!("Guardian not found for account")),
+                uow.commit().await?;
+                Ok(AccountGuardianDeleteResponse {
+                    guardian_id: guardian_id.clone(),
+                })
+            } else {
+                Err(ApiError::Conflict(
+                    "Guardian must not be ACTIVE".to_string(),
+                ))
             }
         }
-        None => Err(anyhow::anyhow!("Account not found")),
+        None => Err(ApiError::NotFound(
+            "Guardian not found for account".to_string(),
+        )),
     }
 }
 
+// the account can nominate the guardian again once they are removed
+async fn cancel_accepted_nominations(
+    db: &impl ConnectionTrait,
+    account_id: String,
+    guardian_id: String,
+    now: i64,
+) -> anyhow::Result<()> {
+    let nominations = nomination_repo::find_all_by_account_and_guardian_and_statuses(
+        db,
+        account_id,
+        guardian_id,
+        vec![ACCEPTED],
+    )
+    .await?;
+    for nomination in nominations {
+        transition(db, &nomination, CANCELLED, now).await?;
+    }
+    Ok(())
+}
+
 async fn get_guardians(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Query(params): Query<AccountGuardianParams>,
-) -> Result<Json<ApiResponse<ListAccountGuardiansResponse, ApiErrorResponse>>, StatusCode> {
-    match try_get_guardians(app_state, token, &params).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<ListAccountGuardiansResponse, ApiErrorResponse>>, ApiError> {
+    try_get_guardians(app_state, account, &params)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_get_guardians(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     params: &AccountGuardianParams,
-) -> anyhow::Result<ListAccountGuardiansResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => match &params.guardian_id {
-            Some(guardian_id) => {
-                find_all_account_guardians_by_guardian_id(
-                    &app_state,
-                    acc.id,
-                    guardian_id.to_owned(),
-                )
+) -> Result<ListAccountGuardiansResponse, ApiError> {
+    let guardians = match &params.guardian_id {
+        Some(guardian_id) => {
+            find_all_account_guardians_by_guardian_id(&app_state, acc.id, guardian_id.to_owned())
                 .await
+        }
+        None => match &params.status {
+            Some(status) => {
+                find_all_account_guardians_by_status(&app_state, acc.id, status.to_owned()).await
             }
-            None => match &params.status {
-                Some(status) => {
-                    find_all_account_guardians_by_status(&app_state, acc.id, status.to_owned())
-                        .await
-                }
-                None => find_all_account_guardians(&app_state, acc.id).await,
-            },
+            None => find_all_account_guardians(&app_state, acc.id).await,
         },
-
-        None => Err(anyhow::anyhow!("Account not found")),
-    }
+    };
+    Ok(guardians?)
 }
 
 async fn find_all_account_guardians_by_guardian_id(
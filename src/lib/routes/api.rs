@@ -3,7 +3,7 @@ use axum::{response::Html, routing::get, Router};
 use hyper::{StatusCode, Uri};
 use utoipa_swagger_ui::SwaggerUi;
 use utoipa::OpenApi;
-use super::{account_api, guardian_api, transaction_api, verification_api};
+use super::{account_api, auth_api, guardian_api, invitation_api, transaction_api, verification_api};
 use crate::models::api;
 use clutch_wallet_lib::utils::bundler;
 
@@ -11,8 +11,8 @@ use clutch_wallet_lib::utils::bundler;
 #[openapi(
     info(description = "Clutch Api description", title = "Clutch Account Abstraction Wallet"),
     components(schemas(api::Account, api::VerificationRequest, api::VerificationResponse, api::AccountCreateRequest, api::AccountCreateResponse,
-    api::SendTransactionRequest, api::SendTransactionResponse, bundler::UserOperationTransport)),
-    paths(verification_api::create_verification, account_api::create_account, transaction_api::send_transaction)
+    api::SendTransactionRequest, api::SendTransactionResponse, bundler::UserOperationTransport, api::LoginRequest, api::LoginResponse, api::SiweNonceResponse, api::SiweLoginRequest)),
+    paths(verification_api::create_verification, account_api::create_account, transaction_api::send_transaction, auth_api::login, auth_api::siwe_nonce, auth_api::siwe_login)
 )]
 struct ApiDoc;
 
@@ -22,7 +22,9 @@ pub fn router(app_state: AppState) -> Router {
         .route("/", get(handler))
         .nest("/email", verification_api::routes(&app_state))
         .nest("/accounts", account_api::routes(&app_state))
+        .nest("/auth", auth_api::routes(&app_state))
         .nest("/guardian", guardian_api::routes(&app_state))
+        .nest("/invitations", invitation_api::routes(&app_state))
         .nest("/transaction", transaction_api::routes(&app_state))
         .merge(SwaggerUi::new("/swagger-ui").url("/api-docs/openapi.json", ApiDoc::openapi()))
         .fallback(fallback)
@@ -0,0 +1,431 @@
+use super::{account_api::validate_code, extractors::AuthenticatedAccount};
+use crate::{
+    models::{
+        api::{
+            api_success, ApiErrorResponse, ApiResponse, ListSessionsResponse, LoginRequest,
+            LoginResponse, RefreshRequest, RefreshResponse, Session, SessionRevokeAllResponse,
+            SessionRevokeResponse, SiweLoginRequest, SiweNonceResponse,
+        },
+        auth::Claims,
+        error::ApiError,
+    },
+    operations::{
+        jwt::{generate_jwt, generate_refresh_token, hash_refresh_token, REFRESH_TOKEN_TTL_MS},
+        signature::{is_valid_contract_signature, recover_signer},
+        siwe::{generate_nonce, SiweMessage, NONCE_TTL_MS},
+    },
+    repos::{account_repo, db::AppState, login_attempt_repo, session_repo, siwe_nonce_repo},
+    utils::convert_to_hex,
+};
+use axum::{
+    extract::{Path, State},
+    headers::UserAgent,
+    routing::{delete, get, post},
+    Json, Router, TypedHeader,
+};
+use chrono::{TimeZone, Utc};
+use email_address::EmailAddress;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+pub fn routes<S>(app_state: &AppState) -> Router<S> {
+    Router::new()
+        .route("/login", post(login))
+        .route("/siwe/nonce", get(siwe_nonce))
+        .route("/siwe", post(siwe_login))
+        .route("/refresh", post(refresh))
+        .route("/sessions", get(get_sessions).delete(revoke_all_sessions))
+        .route("/sessions/:session_id", delete(revoke_session))
+        .with_state(app_state.to_owned())
+}
+
+#[utoipa::path(
+  post,
+  path="/auth/login",
+  responses(
+    (status = 200, description = "Logged in successfully", body=LoginResponse)
+  )
+)]
+async fn login(
+    app_state: State<AppState>,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    Json(req): Json<LoginRequest>,
+) -> Result<Json<ApiResponse<LoginResponse, ApiErrorResponse>>, ApiError> {
+    try_login(&app_state, &req, to_user_agent(user_agent))
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_login(
+    app_state: &State<AppState>,
+    req: &LoginRequest,
+    user_agent: Option<String>,
+) -> Result<LoginResponse, ApiError> {
+    if EmailAddress::is_valid(&req.email) {
+        let account = match account_repo::find_by_email(&app_state.database, &req.email).await? {
+            Some(acc) => validate_code(
+                &app_state.database,
+                req.email.clone(),
+                req.code.clone(),
+                app_state.clock.now_ms(),
+            )
+            .await
+            .map(|_| acc),
+            None => Err(ApiError::NotFound(format!(
+                "Error no account found for email: {}",
+                req.email
+            ))),
+        };
+        record_login_attempt(
+            &app_state.database,
+            req.email.clone(),
+            "EMAIL_CODE",
+            &account,
+            app_state.clock.now_ms(),
+        )
+        .await?;
+        // the attempt keeps the reason, the response doesn't tell whether the email has an account
+        let account =
+            account.map_err(|_| ApiError::Unauthorized("Invalid email or code".to_string()))?;
+
+        let (jwt, refresh_token) = create_session(
+            &app_state.database,
+            account.id,
+            req.device.clone(),
+            user_agent,
+            app_state.clock.now_ms(),
+        )
+        .await?;
+        Ok(LoginResponse { jwt, refresh_token })
+    } else {
+        Err(ApiError::Validation(format!(
+            "Invalid email format {}",
+            req.email
+        )))
+    }
+}
+
+#[utoipa::path(
+  get,
+  path="/auth/siwe/nonce",
+  responses(
+    (status = 200, description = "Nonce to embed in a SIWE message", body=SiweNonceResponse)
+  )
+)]
+async fn siwe_nonce(
+    app_state: State<AppState>,
+) -> Result<Json<ApiResponse<SiweNonceResponse, ApiErrorResponse>>, ApiError> {
+    try_siwe_nonce(&app_state)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_siwe_nonce(app_state: &State<AppState>) -> Result<SiweNonceResponse, ApiError> {
+    let nonce = generate_nonce();
+    let now = app_state.clock.now_ms();
+    siwe_nonce_repo::create(&app_state.database, nonce.clone(), now, now + NONCE_TTL_MS).await?;
+    Ok(SiweNonceResponse { nonce })
+}
+
+#[utoipa::path(
+  post,
+  path="/auth/siwe",
+  responses(
+    (status = 200, description = "Logged in successfully", body=LoginResponse)
+  )
+)]
+async fn siwe_login(
+    app_state: State<AppState>,
+    user_agent: Option<TypedHeader<UserAgent>>,
+    Json(req): Json<SiweLoginRequest>,
+) -> Result<Json<ApiResponse<LoginResponse, ApiErrorResponse>>, ApiError> {
+    try_siwe_login(&app_state, &req, to_user_agent(user_agent))
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_siwe_login(
+    app_state: &State<AppState>,
+    req: &SiweLoginRequest,
+    user_agent: Option<String>,
+) -> Result<LoginResponse, ApiError> {
+    let message = req
+        .message
+        .parse::<SiweMessage>()
+        .map_err(|e| ApiError::Validation(format!("{}", e)))?;
+    let address = convert_to_hex(message.address);
+
+    let account = find_siwe_account(app_state, &message, req).await;
+    record_login_attempt(
+        &app_state.database,
+        account
+            .as_ref()
+            .map_or_else(|_| address.clone(), |acc| acc.email.clone()),
+        "SIWE",
+        &account,
+        app_state.clock.now_ms(),
+    )
+    .await?;
+    // the attempt keeps the reason, the response doesn't tell whether the address has an account
+    let account =
+        account.map_err(|_| ApiError::Unauthorized("Invalid signature or address".to_string()))?;
+
+    let (jwt, refresh_token) = create_session(
+        &app_state.database,
+        account.id,
+        req.device.clone(),
+        user_agent,
+        app_state.clock.now_ms(),
+    )
+    .await?;
+    Ok(LoginResponse { jwt, refresh_token })
+}
+
+// the nonce is used up and the signature checked before the address is looked up
+async fn find_siwe_account(
+    app_state: &State<AppState>,
+    message: &SiweMessage,
+    req: &SiweLoginRequest,
+) -> Result<account_repo::Model, ApiError> {
+    let consumed = siwe_nonce_repo::consume(
+        &app_state.database,
+        message.nonce.clone(),
+        app_state.clock.now_ms(),
+    )
+    .await?;
+    if !consumed {
+        return Err(ApiError::Unauthorized(format!(
+            "Invalid SIWE nonce {}",
+            message.nonce
+        )));
+    }
+    let now = Utc
+        .timestamp_millis_opt(app_state.clock.now_ms())
+        .single()
+        .ok_or_else(|| ApiError::Internal("Invalid current time".to_string()))?;
+    message
+        .validate(
+            &app_state.settings.siwe.domain,
+            app_state.settings.chain_id(),
+            now,
+        )
+        .map_err(|e| ApiError::Unauthorized(format!("{}", e)))?;
+    verify_siwe_signature(app_state, message, req).await?;
+
+    let address = convert_to_hex(message.address);
+    match account_repo::find_by_eoa_address(&app_state.database, address.clone()).await? {
+        Some(acc) => Ok(acc),
+        None => account_repo::find_by_wallet_address(&app_state.database, address.clone())
+            .await?
+            .ok_or_else(|| {
+                ApiError::NotFound(format!("Error no account found for address: {}", address))
+            }),
+    }
+}
+
+// an eoa signs for itself, any other address is asked whether it accepts the signature as a
+// contract wallet
+async fn verify_siwe_signature(
+    app_state: &State<AppState>,
+    message: &SiweMessage,
+    req: &SiweLoginRequest,
+) -> Result<(), ApiError> {
+    let signature = hex::decode(req.signature.trim_start_matches("0x"))
+        .map_err(|_| ApiError::Unauthorized("Invalid signature".to_string()))?;
+
+    let valid = recover_signer(&req.message, &signature)
+        .map_err(|e| ApiError::Unauthorized(format!("{}", e)))?
+        == message.address
+        || is_valid_contract_signature(
+            &app_state.settings.rpc(),
+            message.address,
+            &req.message,
+            &signature,
+        )
+        .await
+        .map_err(|e| ApiError::Upstream(format!("{}", e)))?;
+
+    if valid {
+        Ok(())
+    } else {
+        Err(ApiError::Unauthorized("Invalid signature".to_string()))
+    }
+}
+
+async fn record_login_attempt(
+    db: &DatabaseConnection,
+    email: String,
+    method: &str,
+    account: &Result<account_repo::Model, ApiError>,
+    now: i64,
+) -> Result<(), ApiError> {
+    let (account_id, reason) = match account {
+        Ok(acc) => (Some(acc.id.clone()), None),
+        Err(e) => (None, Some(format!("{}", e))),
+    };
+    login_attempt_repo::create(
+        db,
+        Uuid::new_v4(),
+        email,
+        account_id,
+        method.to_string(),
+        account.is_ok(),
+        reason,
+        now,
+    )
+    .await?;
+    Ok(())
+}
+
+async fn refresh(
+    app_state: State<AppState>,
+    Json(req): Json<RefreshRequest>,
+) -> Result<Json<ApiResponse<RefreshResponse, ApiErrorResponse>>, ApiError> {
+    try_refresh(&app_state, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_refresh(
+    app_state: &State<AppState>,
+    req: &RefreshRequest,
+) -> Result<RefreshResponse, ApiError> {
+    let now = app_state.clock.now_ms();
+    let refresh_token_hash = hash_refresh_token(&req.refresh_token);
+    let session = session_repo::find_active_by_refresh_token_hash(
+        &app_state.database,
+        refresh_token_hash.clone(),
+        now,
+    )
+    .await?
+    .ok_or_else(|| ApiError::Unauthorized("Invalid refresh token".to_string()))?;
+
+    let refresh_token = generate_refresh_token();
+    let rotated = session_repo::rotate_refresh_token(
+        &app_state.database,
+        session.id.clone(),
+        refresh_token_hash,
+        hash_refresh_token(&refresh_token),
+        now,
+        now + REFRESH_TOKEN_TTL_MS,
+    )
+    .await?;
+    if !rotated {
+        return Err(ApiError::Unauthorized("Invalid refresh token".to_string()));
+    }
+
+    let jwt = generate_jwt(session.account_id, session.id, now).await?;
+    Ok(RefreshResponse { jwt, refresh_token })
+}
+
+async fn get_sessions(
+    app_state: State<AppState>,
+    AuthenticatedAccount { claims, .. }: AuthenticatedAccount,
+) -> Result<Json<ApiResponse<ListSessionsResponse, ApiErrorResponse>>, ApiError> {
+    try_get_sessions(&app_state, claims)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_get_sessions(
+    app_state: &State<AppState>,
+    claims: Claims,
+) -> Result<ListSessionsResponse, ApiError> {
+    let sessions = session_repo::find_all_active_by_account_id(
+        &app_state.database,
+        claims.sub.clone(),
+        app_state.clock.now_ms(),
+    )
+    .await?
+    .iter()
+    .map(|s| Session {
+        id: s.id.clone(),
+        device: s.device.clone(),
+        user_agent: s.user_agent.clone(),
+        created_at: s.created_at,
+        last_seen_at: s.last_seen_at,
+        current: s.id == claims.sid,
+    })
+    .collect();
+    Ok(ListSessionsResponse { sessions })
+}
+
+async fn revoke_session(
+    app_state: State<AppState>,
+    AuthenticatedAccount { claims, .. }: AuthenticatedAccount,
+    Path(session_id): Path<String>,
+) -> Result<Json<ApiResponse<SessionRevokeResponse, ApiErrorResponse>>, ApiError> {
+    try_revoke_session(&app_state, claims, session_id)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_revoke_session(
+    app_state: &State<AppState>,
+    claims: Claims,
+    session_id: String,
+) -> Result<SessionRevokeResponse, ApiError> {
+    let revoked = session_repo::revoke_by_account_and_id(
+        &app_state.database,
+        claims.sub,
+        session_id.clone(),
+        app_state.clock.now_ms(),
+    )
+    .await?;
+    if revoked {
+        Ok(SessionRevokeResponse { session_id })
+    } else {
+        Err(ApiError::NotFound("Session not found".to_string()))
+    }
+}
+
+async fn revoke_all_sessions(
+    app_state: State<AppState>,
+    AuthenticatedAccount { claims, .. }: AuthenticatedAccount,
+) -> Result<Json<ApiResponse<SessionRevokeAllResponse, ApiErrorResponse>>, ApiError> {
+    try_revoke_all_sessions(&app_state, claims)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_revoke_all_sessions(
+    app_state: &State<AppState>,
+    claims: Claims,
+) -> Result<SessionRevokeAllResponse, ApiError> {
+    let revoked = session_repo::revoke_all_by_account(
+        &app_state.database,
+        claims.sub,
+        app_state.clock.now_ms(),
+    )
+    .await?;
+    Ok(SessionRevokeAllResponse { revoked })
+}
+
+pub async fn create_session(
+    db: &DatabaseConnection,
+    account_id: String,
+    device: Option<String>,
+    user_agent: Option<String>,
+    created_at: i64,
+) -> anyhow::Result<(String, String)> {
+    let session_id = Uuid::new_v4();
+    let refresh_token = generate_refresh_token();
+    session_repo::create(
+        db,
+        session_id,
+        account_id.clone(),
+        hash_refresh_token(&refresh_token),
+        device,
+        user_agent,
+        created_at,
+        created_at + REFRESH_TOKEN_TTL_MS,
+    )
+    .await?;
+
+    let jwt = generate_jwt(account_id, session_id.to_string(), created_at).await?;
+    Ok((jwt, refresh_token))
+}
+
+pub fn to_user_agent(user_agent: Option<TypedHeader<UserAgent>>) -> Option<String> {
+    user_agent.map(|TypedHeader(user_agent)| user_agent.as_str().to_string())
+}
@@ -0,0 +1,80 @@
+use crate::{
+    models::{auth::Claims, error::ApiError},
+    operations::jwt::{decode_jwt, validate_jwt_claims},
+    repos::{account_repo, db::AppState},
+};
+use axum::{
+    async_trait,
+    body::HttpBody,
+    extract::{FromRef, FromRequest, FromRequestParts},
+    headers::{authorization::Bearer, Authorization},
+    http::{request::Parts, Request},
+    BoxError, Json, TypedHeader,
+};
+use serde::de::DeserializeOwned;
+use validator::Validate;
+
+/// The account behind the bearer token of a request, rejecting with 401 when the
+/// token is missing, invalid or its session is revoked and 404 when the account is gone.
+pub struct AuthenticatedAccount {
+    pub account: account_repo::Model,
+    pub claims: Claims,
+}
+
+#[async_trait]
+impl<S> FromRequestParts<S> for AuthenticatedAccount
+where
+    AppState: FromRef<S>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request_parts(parts: &mut Parts, state: &S) -> Result<Self, Self::Rejection> {
+        let TypedHeader(Authorization(bearer)) =
+            TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
+                .await
+                .map_err(|_| ApiError::Unauthorized("Missing bearer token".to_string()))?;
+        let app_state = AppState::from_ref(state);
+
+        let claims = decode_jwt(bearer.token().to_string())
+            .await
+            .map_err(|e| ApiError::Unauthorized(format!("{}", e)))?;
+        validate_jwt_claims(
+            &app_state.database,
+            claims.clone(),
+            app_state.clock.now_ms(),
+        )
+        .await
+        .map_err(|e| ApiError::Unauthorized(format!("{}", e)))?;
+
+        let account = account_repo::find_by_id(&app_state.database, claims.sub.clone())
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Account not found".to_string()))?;
+
+        Ok(AuthenticatedAccount { account, claims })
+    }
+}
+
+/// A JSON body that is validated before the handler runs, rejecting with 400 when it does not
+/// parse or fails validation instead of axum's plain text 422.
+pub struct ValidatedJson<T>(pub T);
+
+#[async_trait]
+impl<T, S, B> FromRequest<S, B> for ValidatedJson<T>
+where
+    T: DeserializeOwned + Validate,
+    B: HttpBody + Send + 'static,
+    B::Data: Send,
+    B::Error: Into<BoxError>,
+    S: Send + Sync,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: Request<B>, state: &S) -> Result<Self, Self::Rejection> {
+        let Json(value) = Json::<T>::from_request(req, state)
+            .await
+            .map_err(|e| ApiError::Validation(e.body_text()))?;
+        value.validate()?;
+        Ok(ValidatedJson(value))
+    }
+}
@@ -1,156 +1,338 @@
 use crate::{
-    models::api::{
-        api_error, api_success, ApiErrorResponse, ApiResponse, GuardianAccount,
-        GuardianAccountParams, GuardianNominationParams, ListGuardianAccountsResponse,
-        ListNominationsResponse, Nomination, NominationUpdateRequest, NominationUpdateResponse,
+    jobs::recovery::PENDING,
+    models::{
+        api::{
+            api_success, ApiErrorResponse, ApiResponse, GuardianAccount, GuardianAccountParams,
+            GuardianChallengeResponse, GuardianNominationParams, ListGuardianAccountsResponse,
+            ListNominationsResponse, ListRecoveriesResponse, Nomination, NominationUpdateRequest,
+            NominationUpdateResponse, Recovery, RecoveryApprovalRequest,
+        },
+        error::ApiError,
+        validation::parse_address,
+    },
+    operations::{
+        guardian::{link_guardian, ownership_message, unlink_guardian, CHALLENGE_TTL_MS},
+        nomination::{can_transition, current_status, transition, ACCEPTED, REJECTED},
+        recovery::recovery_message,
+        signature::{is_valid_contract_signature, is_valid_signature, recover_signer},
+        siwe::generate_nonce,
     },
-    operations::jwt::{decode_jwt, validate_jwt_claims},
-    repos::{account_repo, db::AppState, guardian_account_repo, guardian_repo, nomination_repo},
+    repos::{
+        account_repo, db::AppState, guardian_account_repo, guardian_challenge_repo, guardian_repo,
+        nomination_repo, recovery_approval_repo, recovery_repo, unit_of_work::UnitOfWork,
+    },
+    utils::convert_to_hex,
 };
 use axum::{
     extract::{Path, Query, State},
-    routing::{get, put},
+    routing::{get, post, put},
     Json, Router,
 };
-use axum_auth::AuthBearer;
-use hyper::StatusCode;
+use ethers::types::Address;
+use sea_orm::ConnectionTrait;
 use uuid::Uuid;
 
+use super::{
+    extractors::{AuthenticatedAccount, ValidatedJson},
+    recovery_api::to_recovery,
+};
+
 pub fn routes<S>(app_state: &AppState) -> Router<S> {
     Router::new()
         .route("/nominations", get(get_nominations))
         .route("/accounts", get(get_accounts))
         .route("/nomination/:nomination_id", put(update_status))
+        .route(
+            "/nomination/:nomination_id/challenge",
+            post(create_challenge),
+        )
+        .route("/recoveries", get(get_recoveries))
+        .route("/recoveries/:recovery_id/approve", post(approve_recovery))
         .with_state(app_state.to_owned())
 }
 
 async fn update_status(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Path(nomination_id): Path<String>,
     Json(req): Json<NominationUpdateRequest>,
-) -> Result<Json<ApiResponse<NominationUpdateResponse, ApiErrorResponse>>, StatusCode> {
-    match try_update_status(app_state, token, nomination_id, req.status).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<NominationUpdateResponse, ApiErrorResponse>>, ApiError> {
+    try_update_status(app_state, account, nomination_id, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_update_status(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     nomination_id: String,
-    status: String,
-) -> anyhow::Result<NominationUpdateResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => {
-            let guardian = guardian_repo::find_by_account_id(&app_state.database, acc.id).await?;
-            match guardian {
-                Some(g) => {
-                    let nominations = nomination_repo::find_all_by_guardian_and_nomination_id(
-                        &app_state.database,
-                        g.id.clone(),
-                        nomination_id.clone(),
-                    )
-                    .await?;
-                    match nominations.len() {
-                        0 => Err(anyhow::anyhow!("Nomination not found")),
-                        _ => {
-                            let nomination = nominations.get(0).unwrap();
-                            validate_nomination_status(status.clone(), nomination.status.clone())
-                                .await?;
-
-                            guardian_account_repo::create(
-                                &app_state.database,
-                                Uuid::new_v4(),
-                                g.id.clone(),
-                                nomination.account_id.clone(),
-                                "AVAILABLE".to_string(),
-                            )
-                            .await?;
-
-                            nomination_repo::update_status_by_guardian_id(
-                                &app_state.database,
-                                nomination.id.clone(),
-                                g.id,
-                                status.clone().to_uppercase(),
-                            )
-                            .await?;
-                            Ok(NominationUpdateResponse {
-                                nomination_id: nomination.id.clone(),
-                                status: status.clone().to_uppercase(),
-                            })
-                        }
-                    }
+    req: &NominationUpdateRequest,
+) -> Result<NominationUpdateResponse, ApiError> {
+    let status = req.status.clone();
+    let guardian = guardian_repo::find_by_account_id(&app_state.database, acc.id).await?;
+    match guardian {
+        Some(g) => {
+            let nominations = nomination_repo::find_all_by_guardian_and_nomination_id(
+                &app_state.database,
+                g.id.clone(),
+                nomination_id.clone(),
+            )
+            .await?;
+            match nominations.len() {
+                0 => Err(ApiError::NotFound("Nomination not found".to_string())),
+                _ => {
+                    let nomination = nominations.get(0).unwrap();
+                    let uow = UnitOfWork::begin(&app_state.database).await?;
+                    let verified_address =
+                        verify_nomination_update(&app_state, uow.db(), &g, nomination, req).await?;
+                    update_nomination(&app_state, uow, &g, nomination, &status, verified_address)
+                        .await
                 }
-                None => Err(anyhow::anyhow!("Guardian not found")),
             }
         }
-        None => Err(anyhow::anyhow!("Account not found")),
+        None => Err(ApiError::NotFound("Guardian not found".to_string())),
     }
 }
 
-async fn validate_nomination_status(
-    requested_status: String,
-    current_status: String,
-) -> anyhow::Result<()> {
-    match requested_status.to_uppercase().as_str() {
-        "PENDING" => Err(anyhow::anyhow!(
-            "Invalid status must be ACCEPTED or REJECTED"
-        )),
-        "ACCEPTED" => {
-            if current_status == "REJECTED" {
-                Err(anyhow::anyhow!("Nomination already rejected"))
-            } else {
-                Ok(())
-            }
+// checks the status change and, to accept, the guardian's proof of wallet ownership. A challenge
+// sent along is checked first, a reused one is refused as such even after the acceptance. The
+// challenge is used up in `db`, the unit of work the nomination is then updated in
+pub(crate) async fn verify_nomination_update(
+    app_state: &State<AppState>,
+    db: &impl ConnectionTrait,
+    guardian: &guardian_repo::Model,
+    nomination: &nomination_repo::Model,
+    req: &NominationUpdateRequest,
+) -> Result<Option<Address>, ApiError> {
+    let accepting = req.status.to_uppercase() == ACCEPTED;
+    let now = app_state.clock.now_ms();
+    if accepting && req.nonce.is_some() {
+        let address = verify_ownership(app_state, db, guardian, nomination.id.clone(), req).await?;
+        validate_nomination_status(req.status.clone(), nomination, now).await?;
+        return Ok(Some(address));
+    }
+
+    validate_nomination_status(req.status.clone(), nomination, now).await?;
+    if accepting {
+        let address = verify_ownership(app_state, db, guardian, nomination.id.clone(), req).await?;
+        Ok(Some(address))
+    } else {
+        Ok(None)
+    }
+}
+
+// the nomination, the verified address and the guardian's link to the account change together
+// in `uow`, nothing is written when it fails
+pub(crate) async fn update_nomination(
+    app_state: &State<AppState>,
+    uow: UnitOfWork,
+    guardian: &guardian_repo::Model,
+    nomination: &nomination_repo::Model,
+    status: &str,
+    verified_address: Option<Address>,
+) -> Result<NominationUpdateResponse, ApiError> {
+    let status = status.to_uppercase();
+    let now = app_state.clock.now_ms();
+    if !transition(uow.db(), nomination, &status, now).await? {
+        return Err(ApiError::Conflict(format!(
+            "Nomination {} was updated in the meantime",
+            nomination.id
+        )));
+    }
+
+    if let Some(address) = verified_address {
+        let verified = guardian_repo::update_verified_address(
+            uow.db(),
+            guardian.id.clone(),
+            convert_to_hex(address),
+            now,
+        )
+        .await?;
+        if !verified {
+            return Err(ApiError::Conflict(
+                "Guardian already verified another wallet, accept with that one".to_string(),
+            ));
         }
-        "REJECTED" => {
-            if current_status == "ACCEPTED" {
-                Err(anyhow::anyhow!("Nomination already accepted"))
-            } else {
-                Ok(())
-            }
+    }
+    if status == ACCEPTED {
+        link_guardian(uow.db(), guardian.id.clone(), nomination.account_id.clone()).await?;
+    } else {
+        unlink_guardian(uow.db(), guardian.id.clone(), nomination.account_id.clone()).await?;
+    }
+    uow.commit().await?;
+
+    Ok(NominationUpdateResponse {
+        nomination_id: nomination.id.clone(),
+        status,
+    })
+}
+
+// the guardian signs the challenge issued for the nomination with the wallet they guard with,
+// the signature is checked before the challenge is used up so no RPC is made once `db` writes
+async fn verify_ownership(
+    app_state: &State<AppState>,
+    db: &impl ConnectionTrait,
+    guardian: &guardian_repo::Model,
+    nomination_id: String,
+    req: &NominationUpdateRequest,
+) -> Result<Address, ApiError> {
+    let (address, nonce, signature) = match (&req.address, &req.nonce, &req.signature) {
+        (Some(address), Some(nonce), Some(signature)) => (address, nonce, signature),
+        _ => {
+            return Err(ApiError::Validation(
+                "Accepting a nomination needs the address, nonce and signature of a challenge"
+                    .to_string(),
+            ))
         }
-        _ => Err(anyhow::anyhow!(
-            "Invalid status must be ACCEPTED or REJECTED"
-        )),
+    };
+    let address = parse_address("guardian", address)?;
+    let signature = hex::decode(signature.trim_start_matches("0x"))
+        .map_err(|_| ApiError::Validation("Invalid signature".to_string()))?;
+
+    let message = ownership_message(
+        &guardian.id,
+        &nomination_id,
+        nonce,
+        app_state.settings.chain_id(),
+    );
+    let valid = is_valid_signature(&app_state.settings.rpc(), address, &message, &signature)
+        .await
+        .map_err(|e| ApiError::Upstream(format!("{}", e)))?;
+    if !valid {
+        return Err(ApiError::Forbidden(format!(
+            "Signature is not from {}",
+            convert_to_hex(address)
+        )));
+    }
+
+    let consumed = guardian_challenge_repo::consume(
+        db,
+        nonce.clone(),
+        guardian.id.clone(),
+        nomination_id,
+        app_state.clock.now_ms(),
+    )
+    .await?;
+    if !consumed {
+        return Err(ApiError::Forbidden(format!(
+            "Invalid or expired challenge {}",
+            nonce
+        )));
+    }
+    Ok(address)
+}
+
+async fn create_challenge(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    Path(nomination_id): Path<String>,
+) -> Result<Json<ApiResponse<GuardianChallengeResponse, ApiErrorResponse>>, ApiError> {
+    try_create_challenge(&app_state, account, nomination_id)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_create_challenge(
+    app_state: &State<AppState>,
+    acc: account_repo::Model,
+    nomination_id: String,
+) -> Result<GuardianChallengeResponse, ApiError> {
+    let db = &app_state.database;
+    let guardian = guardian_repo::find_by_account_id(db, acc.id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Guardian not found".to_string()))?;
+    let nomination = nomination_repo::find_all_by_guardian_and_nomination_id(
+        db,
+        guardian.id.clone(),
+        nomination_id,
+    )
+    .await?
+    .into_iter()
+    .next()
+    .ok_or_else(|| ApiError::NotFound("Nomination not found".to_string()))?;
+    issue_challenge(app_state, &guardian, &nomination).await
+}
+
+pub(crate) async fn issue_challenge(
+    app_state: &State<AppState>,
+    guardian: &guardian_repo::Model,
+    nomination: &nomination_repo::Model,
+) -> Result<GuardianChallengeResponse, ApiError> {
+    let now = app_state.clock.now_ms();
+    validate_nomination_status(ACCEPTED.to_string(), nomination, now).await?;
+
+    let nonce = generate_nonce();
+    let expires_at = now + CHALLENGE_TTL_MS;
+    guardian_challenge_repo::create(
+        &app_state.database,
+        guardian_challenge_repo::Model {
+            nonce: nonce.clone(),
+            guardian_id: guardian.id.clone(),
+            nomination_id: nomination.id.clone(),
+            created_at: now,
+            expires_at,
+            consumed_at: None,
+        },
+    )
+    .await?;
+    Ok(GuardianChallengeResponse {
+        message: ownership_message(
+            &guardian.id,
+            &nomination.id,
+            &nonce,
+            app_state.settings.chain_id(),
+        ),
+        nonce,
+        expires_at,
+    })
+}
+
+// a guardian answers a pending nomination, expired ones can't be answered before they are swept
+async fn validate_nomination_status(
+    requested_status: String,
+    nomination: &nomination_repo::Model,
+    now: i64,
+) -> Result<(), ApiError> {
+    let requested_status = requested_status.to_uppercase();
+    if requested_status != ACCEPTED && requested_status != REJECTED {
+        return Err(ApiError::Validation(
+            "Invalid status must be ACCEPTED or REJECTED".to_string(),
+        ));
+    }
+    let current_status = current_status(nomination, now);
+    if can_transition(current_status, &requested_status) {
+        Ok(())
+    } else {
+        Err(ApiError::Conflict(format!(
+            "Nomination already {}",
+            current_status.to_lowercase()
+        )))
     }
 }
 
 async fn get_accounts(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Query(params): Query<GuardianAccountParams>,
-) -> Result<Json<ApiResponse<ListGuardianAccountsResponse, ApiErrorResponse>>, StatusCode> {
-    match try_get_accounts(app_state, token, &params).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<ListGuardianAccountsResponse, ApiErrorResponse>>, ApiError> {
+    try_get_accounts(app_state, account, &params)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_get_accounts(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     params: &GuardianAccountParams,
-) -> anyhow::Result<ListGuardianAccountsResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => match &params.account_id {
-            Some(account_id) => {
-                find_all_guardian_accounts_by_account_id(&app_state, acc.id, account_id.to_owned())
-                    .await
-            }
-            None => find_all_guardian_accounts(&app_state, acc.id).await,
-        },
-
-        None => Err(anyhow::anyhow!("Account not found")),
-    }
+) -> Result<ListGuardianAccountsResponse, ApiError> {
+    let accounts = match &params.account_id {
+        Some(account_id) => {
+            find_all_guardian_accounts_by_account_id(&app_state, acc.id, account_id.to_owned())
+                .await
+        }
+        None => find_all_guardian_accounts(&app_state, acc.id).await,
+    };
+    Ok(accounts?)
 }
 
 async fn find_all_guardian_accounts_by_account_id(
@@ -230,42 +412,32 @@ async fn find_all_guardian_accounts(
 
 async fn get_nominations(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Query(params): Query<GuardianNominationParams>,
-) -> Result<Json<ApiResponse<ListNominationsResponse, ApiErrorResponse>>, StatusCode> {
-    match try_get_nominations(app_state, token, &params).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<ListNominationsResponse, ApiErrorResponse>>, ApiError> {
+    try_get_nominations(app_state, account, &params)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_get_nominations(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     params: &GuardianNominationParams,
-) -> anyhow::Result<ListNominationsResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => match &params.nomination_id {
-            Some(nomination_id) => {
-                find_all_nominations_by_id(&app_state, acc.id, nomination_id.clone()).await
-            }
-            None => match &params.status {
-                Some(status) => {
-                    find_all_nominations_by_status(
-                        &app_state,
-                        acc.id,
-                        status.clone().to_uppercase(),
-                    )
+) -> Result<ListNominationsResponse, ApiError> {
+    let nominations = match &params.nomination_id {
+        Some(nomination_id) => {
+            find_all_nominations_by_id(&app_state, acc.id, nomination_id.clone()).await
+        }
+        None => match &params.status {
+            Some(status) => {
+                find_all_nominations_by_status(&app_state, acc.id, status.clone().to_uppercase())
                     .await
-                }
-                None => find_all_nominations(&app_state, acc.id).await,
-            },
+            }
+            None => find_all_nominations(&app_state, acc.id).await,
         },
-        None => Err(anyhow::anyhow!("Account not found")),
-    }
+    };
+    Ok(nominations?)
 }
 
 async fn find_all_nominations_by_id(
@@ -356,3 +528,165 @@ async fn find_all_nominations(
         }),
     }
 }
+
+async fn get_recoveries(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+) -> Result<Json<ApiResponse<ListRecoveriesResponse, ApiErrorResponse>>, ApiError> {
+    try_get_recoveries(&app_state, account)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+// pending recoveries of the accounts the caller is an active guardian of
+async fn try_get_recoveries(
+    app_state: &State<AppState>,
+    acc: account_repo::Model,
+) -> Result<ListRecoveriesResponse, ApiError> {
+    let db = &app_state.database;
+    let guardian = match guardian_repo::find_by_account_id(db, acc.id).await? {
+        Some(g) => g,
+        None => return Ok(ListRecoveriesResponse { recoveries: vec![] }),
+    };
+    let account_ids = guardian_account_repo::find_all_accounts_by_guardian_id(db, guardian.id)
+        .await?
+        .into_iter()
+        .filter(|ga| ga.status == "ACTIVE")
+        .map(|ga| ga.account_id)
+        .collect::<Vec<String>>();
+
+    let mut recoveries = vec![];
+    for recovery in
+        recovery_repo::find_all_by_account_ids_and_status(db, account_ids, PENDING).await?
+    {
+        let account = account_repo::find_by_id(db, recovery.account_id.clone())
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Account not found".to_string()))?;
+        recoveries.push(to_recovery(app_state, &account, &recovery).await?);
+    }
+    Ok(ListRecoveriesResponse { recoveries })
+}
+
+async fn approve_recovery(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    Path(recovery_id): Path<String>,
+    ValidatedJson(req): ValidatedJson<RecoveryApprovalRequest>,
+) -> Result<Json<ApiResponse<Recovery, ApiErrorResponse>>, ApiError> {
+    try_approve_recovery(&app_state, account, recovery_id, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_approve_recovery(
+    app_state: &State<AppState>,
+    acc: account_repo::Model,
+    recovery_id: String,
+    req: &RecoveryApprovalRequest,
+) -> Result<Recovery, ApiError> {
+    let db = &app_state.database;
+    let guardian = guardian_repo::find_by_account_id(db, acc.id.clone())
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Guardian not found".to_string()))?;
+    let recovery = recovery_repo::find_by_id(db, recovery_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Recovery not found".to_string()))?;
+    let is_active_guardian = guardian_account_repo::find_guardian_by_guardian_id_and_account_id(
+        db,
+        guardian.id.clone(),
+        recovery.account_id.clone(),
+    )
+    .await?
+    .map_or(false, |ga| ga.status == "ACTIVE");
+    if !is_active_guardian {
+        return Err(ApiError::Forbidden(
+            "Guardian is not an active guardian of the account".to_string(),
+        ));
+    }
+    if recovery.status != PENDING {
+        return Err(ApiError::Conflict(format!(
+            "Recovery can't be approved with state: {}, must be in state PENDING",
+            recovery.status
+        )));
+    }
+    if recovery_approval_repo::find_by_recovery_id_and_guardian_id(
+        db,
+        recovery.id.clone(),
+        guardian.id.clone(),
+    )
+    .await?
+    .is_some()
+    {
+        return Err(ApiError::Conflict(
+            "Recovery already approved by guardian".to_string(),
+        ));
+    }
+
+    let account = account_repo::find_by_id(db, recovery.account_id.clone())
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Account not found".to_string()))?;
+    let message = recovery_message(
+        &recovery.id,
+        &account.wallet_address,
+        &recovery.new_owner,
+        app_state.settings.chain_id(),
+    );
+    let signature = hex::decode(req.signature.trim_start_matches("0x"))
+        .map_err(|_| ApiError::Validation("Invalid signature".to_string()))?;
+    let signer = verify_guardian_signature(app_state, &guardian, &message, &signature).await?;
+
+    recovery_approval_repo::create(
+        db,
+        recovery_approval_repo::Model {
+            id: Uuid::new_v4().to_string(),
+            recovery_id: recovery.id.clone(),
+            guardian_id: guardian.id,
+            signer: convert_to_hex(signer),
+            signature: format!("0x{}", hex::encode(&signature)),
+            created_at: app_state.clock.now_ms(),
+        },
+    )
+    .await?;
+    to_recovery(app_state, &account, &recovery).await
+}
+
+// the guardian signs with the wallet they proved to control when accepting the nomination, a
+// guardian who never did can't approve. Returns the address that signed
+async fn verify_guardian_signature(
+    app_state: &State<AppState>,
+    guardian: &guardian_repo::Model,
+    message: &str,
+    signature: &[u8],
+) -> Result<Address, ApiError> {
+    let address = match &guardian.verified_address {
+        Some(address) => parse_address("guardian", address)?,
+        None => {
+            return Err(ApiError::Forbidden(
+                "Guardian has no verified wallet to approve with".to_string(),
+            ))
+        }
+    };
+    if recover_signer(message, signature).map_or(false, |signer| signer == address) {
+        return Ok(address);
+    }
+
+    // a wallet that can't be asked doesn't approve, the guardian can try again
+    let valid = is_valid_contract_signature(&app_state.settings.rpc(), address, message, signature)
+        .await
+        .unwrap_or_else(|e| {
+            log::warn!(
+                "Error checking the signature of guardian {} with {}: {}",
+                guardian.id,
+                convert_to_hex(address),
+                e
+            );
+            false
+        });
+    if valid {
+        Ok(address)
+    } else {
+        Err(ApiError::Forbidden(
+            "Signature is not from a wallet of the guardian".to_string(),
+        ))
+    }
+}
@@ -1,21 +1,25 @@
 use crate::{
-    models::api::{
-        api_error, api_success, AccountGuardianSettingsRequest, AccountGuardianSettingsResponse,
-        ApiErrorResponse, ApiResponse, SigningStrategy,
+    models::{
+        api::{
+            api_success, AccountGuardianSettingsRequest, AccountGuardianSettingsResponse,
+            ApiErrorResponse, ApiResponse, SigningStrategy,
+        },
+        error::ApiError,
+    },
+    repos::{
+        account_repo, db::AppState, guardian_account_repo, guardian_repo, guardian_settings_repo,
+        unit_of_work::UnitOfWork,
     },
-    operations::jwt::{decode_jwt, validate_jwt_claims},
-    repos::{account_repo, db::AppState, guardian_account_repo, guardian_settings_repo},
 };
 use axum::{
     extract::State,
     routing::{get, put},
     Json, Router,
 };
-use axum_auth::AuthBearer;
-use hyper::StatusCode;
-use sea_orm::DatabaseConnection;
+use sea_orm::{ConnectionTrait, DatabaseConnection};
+use uuid::Uuid;
 
-use super::account_guardians_api::to_account_guardians;
+use super::{account_guardians_api::to_account_guardians, extractors::AuthenticatedAccount};
 
 pub fn routes<S>(app_state: &AppState) -> Router<S> {
     Router::new()
@@ -26,76 +30,87 @@ pub fn routes<S>(app_state: &AppState) -> Router<S> {
 
 async fn update_guardian_settings(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Json(req): Json<AccountGuardianSettingsRequest>,
-) -> Result<Json<ApiResponse<AccountGuardianSettingsResponse, ApiErrorResponse>>, StatusCode> {
-    match try_update_guardian_settings(app_state, token, req).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<AccountGuardianSettingsResponse, ApiErrorResponse>>, ApiError> {
+    try_update_guardian_settings(app_state, account, req)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_update_guardian_settings(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     req: AccountGuardianSettingsRequest,
-) -> anyhow::Result<AccountGuardianSettingsResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => {
-            validate_guardian_quantity(req.signers.clone(), req.guardians.clone()).await?;
-            validate_guardians_for_account(
-                &app_state.database,
-                req.guardians.clone(),
-                acc.id.clone(),
-            )
-            .await?;
-
+) -> Result<AccountGuardianSettingsResponse, ApiError> {
+    validate_guardian_quantity(req.signers, req.guardians.clone()).await?;
+
+    // the guardians are checked in the same transaction, the account keeps its active
+    // guardians when any of the updates fails
+    let uow = UnitOfWork::begin(&app_state.database).await?;
+    validate_guardians_for_account(uow.db(), req.guardians.clone(), acc.id.clone()).await?;
+    validate_guardians_verified(uow.db(), req.guardians.clone(), acc.id.clone()).await?;
+    match guardian_settings_repo::find_for_account_id(uow.db(), acc.id.clone()).await? {
+        Some(_) => {
             guardian_settings_repo::update_settings_for_account_id(
-                &app_state.database,
-                acc.id.clone(),
-                req.signers.clone(),
-            )
-            .await?;
-
-            guardian_account_repo::update_all_guardians_for_account_to_status(
-                &app_state.database,
-                acc.id.clone(),
-                "AVAILABLE".to_string(),
-            )
-            .await?;
-            guardian_account_repo::update_guardians_for_account_to_status(
-                &app_state.database,
+                uow.db(),
                 acc.id.clone(),
-                req.guardians.clone(),
-                "ACTIVE".to_string(),
+                req.signers,
             )
-            .await?;
-            let active_guardians = guardian_account_repo::find_all_active_guardians_by_account_id(
-                &app_state.database,
-                acc.id.clone(),
-            )
-            .await?;
-            let active_guardian_accounts =
-                to_account_guardians(&app_state.database, active_guardians).await?;
-
-            Ok(AccountGuardianSettingsResponse {
-                signers: SigningStrategy::OneOfOne,
-                active_guardians: active_guardian_accounts,
-                signing_strategies: SigningStrategy::all(),
-            })
+            .await?
+        }
+        None => {
+            guardian_settings_repo::create(uow.db(), Uuid::new_v4(), req.signers, acc.id.clone())
+                .await?
         }
-        None => Err(anyhow::anyhow!("Account not found")),
     }
+
+    guardian_account_repo::update_all_guardians_for_account_to_status(
+        uow.db(),
+        acc.id.clone(),
+        "AVAILABLE".to_string(),
+    )
+    .await?;
+    guardian_account_repo::update_guardians_for_account_to_status(
+        uow.db(),
+        acc.id.clone(),
+        req.guardians.clone(),
+        "ACTIVE".to_string(),
+    )
+    .await?;
+    uow.commit().await?;
+    let active_guardians = guardian_account_repo::find_all_active_guardians_by_account_id(
+        &app_state.database,
+        acc.id.clone(),
+    )
+    .await?;
+    let active_guardian_accounts =
+        to_account_guardians(&app_state.database, active_guardians).await?;
+    let settings = guardian_settings_repo::find_for_account_id(&app_state.database, acc.id.clone())
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Settings not found".to_string()))?;
+
+    Ok(AccountGuardianSettingsResponse {
+        signers: settings.signers(),
+        active_guardians: active_guardian_accounts,
+        signing_strategies: signing_strategies(&app_state.database, acc.id.clone()).await?,
+    })
 }
 
-async fn validate_guardians_for_account(
+// what the account's guardians, active or available, can support
+async fn signing_strategies(
     db: &DatabaseConnection,
+    account_id: String,
+) -> Result<Vec<SigningStrategy>, ApiError> {
+    let guardians = guardian_account_repo::find_all_guardians_by_account_id(db, account_id).await?;
+    Ok(SigningStrategy::all_for(guardians.len() as u32))
+}
+
+async fn validate_guardians_for_account(
+    db: &impl ConnectionTrait,
     account_guardian_ids: Vec<String>,
     account_id: String,
-) -> anyhow::Result<()> {
+) -> Result<(), ApiError> {
     let account_guardians = guardian_account_repo::find_all_guardians_for_account_by_ids(
         db,
         account_guardian_ids.clone(),
@@ -109,14 +124,53 @@ async fn validate_guardians_for_account(
         .map(|g| g.id.clone())
         .collect::<Vec<String>>();
     if account_guardians.len() != account_guardian_ids.len() {
-        return Err(anyhow::anyhow!(
+        return Err(ApiError::Validation(format!(
             "Invalid account guardians supplied: {:?}, valid guardians are: {:?}",
             account_guardian_ids
                 .iter()
                 .filter(|id| !valid_ids.contains(id))
                 .collect::<Vec<&String>>(),
             valid_ids
-        ));
+        )));
+    }
+
+    Ok(())
+}
+
+// only guardians that proved to control their wallet can be made active
+async fn validate_guardians_verified(
+    db: &impl ConnectionTrait,
+    account_guardian_ids: Vec<String>,
+    account_id: String,
+) -> Result<(), ApiError> {
+    let account_guardians = guardian_account_repo::find_all_guardians_for_account_by_ids(
+        db,
+        account_guardian_ids,
+        account_id,
+    )
+    .await?;
+    let verified_guardian_ids = guardian_repo::find_all_by_ids(
+        db,
+        account_guardians
+            .iter()
+            .map(|ag| ag.guardian_id.clone())
+            .collect(),
+    )
+    .await?
+    .into_iter()
+    .filter(|g| g.verified_address.is_some())
+    .map(|g| g.id)
+    .collect::<Vec<String>>();
+    let unverified = account_guardians
+        .iter()
+        .filter(|ag| !verified_guardian_ids.contains(&ag.guardian_id))
+        .map(|ag| ag.id.clone())
+        .collect::<Vec<String>>();
+    if !unverified.is_empty() {
+        return Err(ApiError::Validation(format!(
+            "Guardians haven't proven ownership of their wallet: {:?}",
+            unverified
+        )));
     }
 
     Ok(())
@@ -125,13 +179,19 @@ async fn validate_guardians_for_account(
 async fn validate_guardian_quantity(
     signer: SigningStrategy,
     account_guardian_ids: Vec<String>,
-) -> anyhow::Result<()> {
-    let required_quantity = SigningStrategy::get_signers_for(signer.clone())?;
+) -> Result<(), ApiError> {
+    if !signer.is_valid() {
+        return Err(ApiError::Validation(format!(
+            "Invalid signing strategy {}, the threshold must be between 1 and {}",
+            signer, signer.total
+        )));
+    }
 
-    if account_guardian_ids.len() != required_quantity as usize {
-        return Err(anyhow::anyhow!(format!(
+    // every supplied guardian becomes active, so the total has to match them
+    if account_guardian_ids.len() != signer.total as usize {
+        return Err(ApiError::Validation(format!(
             "Invalid number of guardians supplied. Expected {}, got {}, for signing strategy {}",
-            required_quantity,
+            signer.total,
             account_guardian_ids.len(),
             signer
         )));
@@ -142,47 +202,36 @@ async fn validate_guardian_quantity(
 
 async fn get_guardian_settings(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
-) -> Result<Json<ApiResponse<AccountGuardianSettingsResponse, ApiErrorResponse>>, StatusCode> {
-    match try_get_guardian_settings(app_state, token).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+) -> Result<Json<ApiResponse<AccountGuardianSettingsResponse, ApiErrorResponse>>, ApiError> {
+    try_get_guardian_settings(app_state, account)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_get_guardian_settings(
     app_state: State<AppState>,
-    token: String,
-) -> anyhow::Result<AccountGuardianSettingsResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => {
-            let settings =
-                guardian_settings_repo::find_for_account_id(&app_state.database, acc.id.clone())
-                    .await?;
-            match settings {
-                Some(s) => {
-                    let account_guardians =
-                        guardian_account_repo::find_all_active_guardians_by_account_id(
-                            &app_state.database,
-                            acc.id.clone(),
-                        )
-                        .await?;
-
-                    let active_guardians =
-                        to_account_guardians(&app_state.database, account_guardians).await?;
-
-                    Ok(AccountGuardianSettingsResponse {
-                        signers: s.signers,
-                        active_guardians,
-                        signing_strategies: SigningStrategy::all(),
-                    })
-                }
-                None => Err(anyhow::anyhow!("Settings not found")),
-            }
+    acc: account_repo::Model,
+) -> Result<AccountGuardianSettingsResponse, ApiError> {
+    let settings =
+        guardian_settings_repo::find_for_account_id(&app_state.database, acc.id.clone()).await?;
+    match settings {
+        Some(s) => {
+            let account_guardians = guardian_account_repo::find_all_active_guardians_by_account_id(
+                &app_state.database,
+                acc.id.clone(),
+            )
+            .await?;
+
+            let active_guardians =
+                to_account_guardians(&app_state.database, account_guardians).await?;
+
+            Ok(AccountGuardianSettingsResponse {
+                signers: s.signers(),
+                active_guardians,
+                signing_strategies: signing_strategies(&app_state.database, acc.id.clone()).await?,
+            })
         }
-        None => Err(anyhow::anyhow!("Account not found")),
+        None => Err(ApiError::NotFound("Settings not found".to_string())),
     }
 }
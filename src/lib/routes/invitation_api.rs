@@ -0,0 +1,113 @@
+use crate::{
+    models::{
+        api::{
+            api_success, ApiErrorResponse, ApiResponse, GuardianChallengeResponse,
+            InvitationChallengeRequest, InvitationUpdateRequest, NominationUpdateResponse,
+        },
+        error::ApiError,
+    },
+    operations::invitation::decode_invitation_token,
+    repos::{
+        db::AppState, guardian_repo, nomination_invitation_repo, nomination_repo,
+        unit_of_work::UnitOfWork,
+    },
+};
+use axum::{extract::State, routing::post, Json, Router};
+
+use super::guardian_api::{issue_challenge, update_nomination, verify_nomination_update};
+
+// public, the invitation token stands in for a Clutch account
+pub fn routes<S>(app_state: &AppState) -> Router<S> {
+    Router::new()
+        .route("/", post(update_invitation))
+        .route("/challenge", post(create_challenge))
+        .with_state(app_state.to_owned())
+}
+
+async fn update_invitation(
+    app_state: State<AppState>,
+    Json(req): Json<InvitationUpdateRequest>,
+) -> Result<Json<ApiResponse<NominationUpdateResponse, ApiErrorResponse>>, ApiError> {
+    try_update_invitation(&app_state, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+// the token is only used up once the response to the nomination is valid, and in the same
+// transaction as the nomination is updated
+async fn try_update_invitation(
+    app_state: &State<AppState>,
+    req: &InvitationUpdateRequest,
+) -> Result<NominationUpdateResponse, ApiError> {
+    let (invitation, nomination, guardian) = find_invitation(app_state, &req.token).await?;
+    let uow = UnitOfWork::begin(&app_state.database).await?;
+    let verified_address =
+        verify_nomination_update(app_state, uow.db(), &guardian, &nomination, &req.update).await?;
+    let consumed =
+        nomination_invitation_repo::consume(uow.db(), invitation.id, app_state.clock.now_ms())
+            .await?;
+    if !consumed {
+        return Err(ApiError::Unauthorized(
+            "Invitation has expired or was already used".to_string(),
+        ));
+    }
+    update_nomination(
+        app_state,
+        uow,
+        &guardian,
+        &nomination,
+        &req.update.status,
+        verified_address,
+    )
+    .await
+}
+
+async fn create_challenge(
+    app_state: State<AppState>,
+    Json(req): Json<InvitationChallengeRequest>,
+) -> Result<Json<ApiResponse<GuardianChallengeResponse, ApiErrorResponse>>, ApiError> {
+    try_create_challenge(&app_state, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_create_challenge(
+    app_state: &State<AppState>,
+    req: &InvitationChallengeRequest,
+) -> Result<GuardianChallengeResponse, ApiError> {
+    let (_, nomination, guardian) = find_invitation(app_state, &req.token).await?;
+    issue_challenge(app_state, &guardian, &nomination).await
+}
+
+async fn find_invitation(
+    app_state: &State<AppState>,
+    token: &str,
+) -> Result<
+    (
+        nomination_invitation_repo::Model,
+        nomination_repo::Model,
+        guardian_repo::Model,
+    ),
+    ApiError,
+> {
+    let db = &app_state.database;
+    let claims = decode_invitation_token(token)
+        .map_err(|_| ApiError::Unauthorized("Invalid invitation token".to_string()))?;
+    let invitation = nomination_invitation_repo::find_by_id(db, claims.sub)
+        .await?
+        .filter(|invitation| invitation.nomination_id == claims.nid)
+        .ok_or_else(|| ApiError::Unauthorized("Invalid invitation token".to_string()))?;
+    if invitation.consumed_at.is_some() || invitation.expires_at <= app_state.clock.now_ms() {
+        return Err(ApiError::Unauthorized(
+            "Invitation has expired or was already used".to_string(),
+        ));
+    }
+
+    let nomination = nomination_repo::find_by_id(db, invitation.nomination_id.clone())
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Nomination not found".to_string()))?;
+    let guardian = guardian_repo::find_by_id(db, nomination.guardian_id.clone())
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Guardian not found".to_string()))?;
+    Ok((invitation, nomination, guardian))
+}
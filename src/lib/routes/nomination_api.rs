@@ -1,112 +1,107 @@
 use crate::{
-    models::api::{
-        api_error, api_success, ApiErrorResponse, ApiResponse, ListNominationsResponse, Nomination,
-        NominationCreateRequest, NominationCreateResponse, NominationDeleteResponse,
-        NominationParams,
+    models::{
+        api::{
+            api_success, ApiErrorResponse, ApiResponse, ListNominationsResponse, Nomination,
+            NominationCreateRequest, NominationCreateResponse, NominationDeleteResponse,
+            NominationParams, NominationResendResponse,
+        },
+        error::ApiError,
+    },
+    operations::{
+        invitation::{send_invitation, INVITATION, RESEND},
+        nomination::{current_status, nominate, transition, ACCEPTED, CANCELLED, EXPIRED, PENDING},
+    },
+    repos::{
+        account_repo, db::AppState, guardian_repo, nomination_invitation_repo, nomination_repo,
+        unit_of_work::UnitOfWork,
     },
-    operations::jwt::{decode_jwt, validate_jwt_claims},
-    repos::{account_repo, db::AppState, guardian_repo, nomination_repo},
 };
 use axum::{
     extract::{Path, Query, State},
-    routing::{delete, get},
+    routing::{delete, get, post},
     Json, Router,
 };
-use axum_auth::AuthBearer;
 use email_address::EmailAddress;
-use hyper::StatusCode;
+use sea_orm::ConnectionTrait;
 use uuid::Uuid;
 
+use super::{
+    extractors::AuthenticatedAccount,
+    verification_api::{check_send_limit, SEND_LIMIT_WINDOW_MS},
+};
+
 pub fn routes<S>(app_state: &AppState) -> Router<S> {
     Router::new()
         .route("/", get(get_nominations).post(create_nomination))
         .route("/:nomination_id", delete(delete_nomination))
+        .route("/:nomination_id/resend", post(resend_nomination))
         .with_state(app_state.to_owned())
 }
 
 async fn delete_nomination(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Path(nomination_id): Path<String>,
-) -> Result<Json<ApiResponse<NominationDeleteResponse, ApiErrorResponse>>, StatusCode> {
-    match try_delete_nomination(app_state, token, nomination_id).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<NominationDeleteResponse, ApiErrorResponse>>, ApiError> {
+    try_delete_nomination(app_state, account, nomination_id)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_delete_nomination(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     nomination_id: String,
-) -> anyhow::Result<NominationDeleteResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => {
-            let nomination = nomination_repo::find_by_account_and_id(
-                &app_state.database,
-                acc.id.clone(),
-                nomination_id.clone(),
-            )
-            .await?;
-            match nomination {
-                Some(nom) => {
-                    if nom.status == *"PENDING" {
-                        nomination_repo::delete_by_account_and_id(
-                            &app_state.database,
-                            acc.id.clone(),
-                            nomination_id.clone(),
-                        )
-                        .await
-                        .map(|_| NominationDeleteResponse { nomination_id })
-                    } else {
-                        Err(anyhow::anyhow!(
-                            "Nomination can't be deleted with state: {}, must be in state PENDING",
-                            nom.status
-                        ))
-                    }
-                }
-                None => Err(anyhow::anyhow!("Nomination not found")),
+) -> Result<NominationDeleteResponse, ApiError> {
+    let nomination = nomination_repo::find_by_account_and_id(
+        &app_state.database,
+        acc.id.clone(),
+        nomination_id.clone(),
+    )
+    .await?;
+    match nomination {
+        Some(nom) => {
+            let now = app_state.clock.now_ms();
+            let status = current_status(&nom, now);
+            if status == PENDING && transition(&app_state.database, &nom, CANCELLED, now).await? {
+                Ok(NominationDeleteResponse { nomination_id })
+            } else {
+                Err(ApiError::Conflict(format!(
+                    "Nomination can't be deleted with state: {}, must be in state PENDING",
+                    status
+                )))
             }
         }
-        None => Err(anyhow::anyhow!("Account not found")),
+        None => Err(ApiError::NotFound("Nomination not found".to_string())),
     }
 }
 
 async fn get_nominations(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Query(params): Query<NominationParams>,
-) -> Result<Json<ApiResponse<ListNominationsResponse, ApiErrorResponse>>, StatusCode> {
-    match try_get_nominations(app_state, token, &params).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<ListNominationsResponse, ApiErrorResponse>>, ApiError> {
+    try_get_nominations(app_state, account, &params)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_get_nominations(
     app_state: State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     params: &NominationParams,
-) -> anyhow::Result<ListNominationsResponse> {
-    let claims = decode_jwt(token).await?;
-    validate_jwt_claims(claims.clone()).await?;
-    let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-    match account {
-        Some(acc) => match &params.nomination_id {
-            Some(nomination_id) => find_all_by_id(&app_state, acc.id, nomination_id.clone()).await,
-            None => match &params.status {
-                Some(status) => find_all_by_status(&app_state, acc.id, status.clone()).await,
-                None => match &params.email {
-                    Some(email) => find_all_by_email(&app_state, acc.id, email.clone()).await,
-                    None => find_all_nominations(&app_state, acc.id).await,
-                },
+) -> Result<ListNominationsResponse, ApiError> {
+    let nominations = match &params.nomination_id {
+        Some(nomination_id) => find_all_by_id(&app_state, acc.id, nomination_id.clone()).await,
+        None => match &params.status {
+            Some(status) => find_all_by_status(&app_state, acc.id, status.clone()).await,
+            None => match &params.email {
+                Some(email) => find_all_by_email(&app_state, acc.id, email.clone()).await,
+                None => find_all_nominations(&app_state, acc.id).await,
             },
         },
-        None => Err(anyhow::anyhow!("Account not found")),
-    }
+    };
+    Ok(nominations?)
 }
 
 async fn find_all_by_id(
@@ -202,107 +197,166 @@ async fn find_all_nominations(
 
 async fn create_nomination(
     app_state: State<AppState>,
-    AuthBearer(token): AuthBearer,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
     Json(req): Json<NominationCreateRequest>,
-) -> Result<Json<ApiResponse<NominationCreateResponse, ApiErrorResponse>>, StatusCode> {
-    match try_create_nomination(&app_state, token, &req).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<NominationCreateResponse, ApiErrorResponse>>, ApiError> {
+    try_create_nomination(&app_state, account, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_create_nomination(
     app_state: &State<AppState>,
-    token: String,
+    acc: account_repo::Model,
     req: &NominationCreateRequest,
-) -> anyhow::Result<NominationCreateResponse> {
-    if EmailAddress::is_valid(&req.email) {
-        let claims = decode_jwt(token).await?;
-        validate_jwt_claims(claims.clone()).await?;
-        let account = account_repo::find_by_id(&app_state.database, claims.sub.clone()).await?;
-        let nomination_id = Uuid::new_v4();
-        match account {
-            Some(acc) => {
-                let maybe_guardian =
-                    guardian_repo::find_by_email(&app_state.database, req.email.clone()).await?;
-                match maybe_guardian {
-                    Some(guardian) => {
-                        nomination_repo::create(
-                            &app_state.database,
-                            nomination_id,
-                            req.email.clone(),
-                            acc.id,
-                            guardian.id,
-                            "PENDING".to_string(),
-                        )
-                        .await?;
-                        Ok(NominationCreateResponse {
-                            nomination_id: nomination_id.to_string(),
-                        })
-                    }
-                    None => {
-                        let maybe_account = account_repo::find_by_email(
-                            &app_state.database,
-                            req.email.clone().as_str(),
-                        )
-                        .await?;
-                        match maybe_account {
-                            Some(user_account) => {
-                                let guardian_id = Uuid::new_v4();
-                                guardian_repo::create(
-                                    &app_state.database,
-                                    guardian_id,
-                                    req.email.clone(),
-                                    Some(user_account.id),
-                                    None,
-                                )
-                                .await?;
-                                nomination_repo::create(
-                                    &app_state.database,
-                                    nomination_id,
-                                    req.email.clone(),
-                                    acc.id.clone(),
-                                    guardian_id.to_string(),
-                                    "PENDING".to_string(),
-                                )
-                                .await?;
-                                Ok(NominationCreateResponse {
-                                    nomination_id: nomination_id.to_string(),
-                                })
-                            }
-                            None => {
-                                let guardian_id = Uuid::new_v4();
-                                guardian_repo::create(
-                                    &app_state.database,
-                                    guardian_id,
-                                    req.email.clone(),
-                                    None,
-                                    None,
-                                )
-                                .await?;
-                                nomination_repo::create(
-                                    &app_state.database,
-                                    nomination_id,
-                                    req.email.clone(),
-                                    acc.id,
-                                    guardian_id.to_string(),
-                                    "PENDING".to_string(),
-                                )
-                                .await?;
-                                Ok(NominationCreateResponse {
-                                    nomination_id: nomination_id.to_string(),
-                                })
-                            }
-                        }
-                    }
-                }
+) -> Result<NominationCreateResponse, ApiError> {
+    if !EmailAddress::is_valid(&req.email) {
+        return Err(ApiError::Validation(format!(
+            "Invalid email format {}",
+            req.email
+        )));
+    }
+    if req.email.eq_ignore_ascii_case(&acc.email) {
+        return Err(ApiError::Validation(
+            "You can't nominate yourself as a guardian".to_string(),
+        ));
+    }
+    verify_invitation_send_limit(app_state, &req.email).await?;
+
+    // the guardian and their nomination are created together
+    let now = app_state.clock.now_ms();
+    let uow = UnitOfWork::begin(&app_state.database).await?;
+    let maybe_guardian = guardian_repo::find_by_email(uow.db(), req.email.clone()).await?;
+    let guardian_id = match maybe_guardian {
+        Some(guardian) => {
+            if guardian.account_id.as_ref() == Some(&acc.id) {
+                return Err(ApiError::Validation(
+                    "You can't nominate yourself as a guardian".to_string(),
+                ));
             }
-            None => Err(anyhow::anyhow!(
-                "Error account not found for account: {}",
-                claims.sub
-            )),
+            verify_not_nominated(uow.db(), &acc, &guardian, now).await?;
+            guardian.id
         }
-    } else {
-        Err(anyhow::anyhow!("Invalid email format {}", req.email))
+        None => {
+            let maybe_account = account_repo::find_by_email(uow.db(), req.email.as_str()).await?;
+            let guardian_id = Uuid::new_v4();
+            guardian_repo::create(
+                uow.db(),
+                guardian_id,
+                req.email.clone(),
+                maybe_account.map(|user_account| user_account.id),
+                None,
+            )
+            .await?;
+            guardian_id.to_string()
+        }
+    };
+
+    let nomination_id = Uuid::new_v4();
+    nominate(
+        uow.db(),
+        nomination_id,
+        req.email.clone(),
+        acc.id.clone(),
+        guardian_id,
+        now + app_state.settings.nomination.ttl_secs as i64 * 1000,
+        now,
+    )
+    .await?;
+    uow.commit().await?;
+
+    let nomination = nomination_repo::find_by_id(&app_state.database, nomination_id.to_string())
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Nomination not found".to_string()))?;
+    send_invitation(app_state, &nomination, &acc.email, INVITATION, now).await?;
+
+    Ok(NominationCreateResponse {
+        nomination_id: nomination_id.to_string(),
+    })
+}
+
+async fn resend_nomination(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    Path(nomination_id): Path<String>,
+) -> Result<Json<ApiResponse<NominationResendResponse, ApiErrorResponse>>, ApiError> {
+    try_resend_nomination(&app_state, account, nomination_id)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_resend_nomination(
+    app_state: &State<AppState>,
+    acc: account_repo::Model,
+    nomination_id: String,
+) -> Result<NominationResendResponse, ApiError> {
+    let nomination =
+        nomination_repo::find_by_account_and_id(&app_state.database, acc.id, nomination_id)
+            .await?
+            .ok_or_else(|| ApiError::NotFound("Nomination not found".to_string()))?;
+    let status = current_status(&nomination, app_state.clock.now_ms());
+    if status != PENDING {
+        return Err(ApiError::Conflict(format!(
+            "Nomination can't be resent with state: {}, must be in state PENDING",
+            status
+        )));
     }
+    verify_invitation_send_limit(app_state, &nomination.email).await?;
+
+    send_invitation(
+        app_state,
+        &nomination,
+        &acc.email,
+        RESEND,
+        app_state.clock.now_ms(),
+    )
+    .await?;
+    Ok(NominationResendResponse {
+        nomination_id: nomination.id,
+    })
+}
+
+// a guardian can be nominated again once the previous nomination was rejected, expired or
+// cancelled, a pending nomination past its ttl is expired here if it wasn't swept yet
+async fn verify_not_nominated(
+    db: &impl ConnectionTrait,
+    acc: &account_repo::Model,
+    guardian: &guardian_repo::Model,
+    now: i64,
+) -> Result<(), ApiError> {
+    let nominations = nomination_repo::find_all_by_account_and_guardian_and_statuses(
+        db,
+        acc.id.clone(),
+        guardian.id.clone(),
+        vec![PENDING, ACCEPTED],
+    )
+    .await?;
+    for nomination in nominations {
+        if current_status(&nomination, now) != EXPIRED {
+            return Err(ApiError::Conflict(format!(
+                "Guardian {} is already nominated",
+                guardian.email
+            )));
+        }
+        transition(db, &nomination, EXPIRED, now).await?;
+    }
+    Ok(())
+}
+
+async fn verify_invitation_send_limit(
+    app_state: &State<AppState>,
+    email: &str,
+) -> Result<(), ApiError> {
+    let email_count = nomination_invitation_repo::count_by_email_created_after(
+        &app_state.database,
+        email,
+        app_state.clock.now_ms() - SEND_LIMIT_WINDOW_MS,
+    )
+    .await?;
+    check_send_limit(
+        &app_state.settings,
+        "Nomination invitation",
+        email,
+        email_count,
+    )
 }
@@ -0,0 +1,217 @@
+use super::{
+    account_api::validate_code,
+    extractors::{AuthenticatedAccount, ValidatedJson},
+};
+use crate::{
+    jobs::recovery::{CANCELLED, PENDING, SUBMITTED},
+    models::{
+        api::{
+            api_success, ApiErrorResponse, ApiResponse, ListRecoveriesResponse, Recovery,
+            RecoveryCreateRequest,
+        },
+        error::ApiError,
+        validation::parse_address,
+    },
+    operations::recovery::{approval_status, recovery_message},
+    repos::{
+        account_repo, db::AppState, guardian_account_repo, guardian_settings_repo, recovery_repo,
+        unit_of_work::UnitOfWork,
+    },
+    utils::convert_to_hex,
+};
+use axum::{
+    extract::{Path, State},
+    routing::get,
+    Json, Router,
+};
+use uuid::Uuid;
+
+pub fn routes<S>(app_state: &AppState) -> Router<S> {
+    Router::new()
+        .route("/", get(get_recoveries).post(start_recovery))
+        .route("/:recovery_id", get(get_recovery).delete(cancel_recovery))
+        .with_state(app_state.to_owned())
+}
+
+async fn start_recovery(
+    app_state: State<AppState>,
+    ValidatedJson(req): ValidatedJson<RecoveryCreateRequest>,
+) -> Result<Json<ApiResponse<Recovery, ApiErrorResponse>>, ApiError> {
+    try_start_recovery(&app_state, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+// an owner who lost their key may have lost their session too, a code sent to the account's
+// email starts the recovery instead. The code is only used up once the recovery is stored
+async fn try_start_recovery(
+    app_state: &State<AppState>,
+    req: &RecoveryCreateRequest,
+) -> Result<Recovery, ApiError> {
+    let now = app_state.clock.now_ms();
+    let uow = UnitOfWork::begin(&app_state.database).await?;
+    let db = uow.db();
+    validate_code(db, req.email.clone(), req.code.clone(), now).await?;
+    let account = account_repo::find_by_email(db, &req.email)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Account not found".to_string()))?;
+    let new_owner = convert_to_hex(parse_address("new_owner", &req.new_owner)?);
+    if new_owner == account.eoa_address {
+        return Err(ApiError::Validation(format!(
+            "{} already owns the wallet",
+            new_owner
+        )));
+    }
+    if recovery_repo::find_by_account_id_and_statuses(
+        db,
+        account.id.clone(),
+        vec![PENDING, SUBMITTED],
+    )
+    .await?
+    .is_some()
+    {
+        return Err(ApiError::Conflict(
+            "A recovery is already pending for this account".to_string(),
+        ));
+    }
+    let settings = guardian_settings_repo::find_for_account_id(db, account.id.clone())
+        .await?
+        .ok_or_else(|| {
+            ApiError::Validation("Account has no guardian settings to recover with".to_string())
+        })?;
+    let required = settings.signers().threshold as i64;
+    let active =
+        guardian_account_repo::find_all_active_guardians_by_account_id(db, account.id.clone())
+            .await?
+            .len() as i64;
+    if active < required {
+        return Err(ApiError::Validation(format!(
+            "Account needs {} active guardians to recover, it has {}",
+            required, active
+        )));
+    }
+
+    let recovery = recovery_repo::Model {
+        id: Uuid::new_v4().to_string(),
+        account_id: account.id.clone(),
+        new_owner,
+        status: PENDING.to_string(),
+        executable_at: now + app_state.settings.recovery.timelock_secs as i64 * 1000,
+        user_op_hash: None,
+        last_error: None,
+        created_at: now,
+        updated_at: now,
+    };
+    recovery_repo::create(db, recovery.clone()).await?;
+    uow.commit().await?;
+    to_recovery(app_state, &account, &recovery).await
+}
+
+async fn get_recoveries(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+) -> Result<Json<ApiResponse<ListRecoveriesResponse, ApiErrorResponse>>, ApiError> {
+    try_get_recoveries(&app_state, account)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_get_recoveries(
+    app_state: &State<AppState>,
+    account: account_repo::Model,
+) -> Result<ListRecoveriesResponse, ApiError> {
+    let mut recoveries = vec![];
+    for recovery in
+        recovery_repo::find_all_by_account_id(&app_state.database, account.id.clone()).await?
+    {
+        recoveries.push(to_recovery(app_state, &account, &recovery).await?);
+    }
+    Ok(ListRecoveriesResponse { recoveries })
+}
+
+async fn get_recovery(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    Path(recovery_id): Path<String>,
+) -> Result<Json<ApiResponse<Recovery, ApiErrorResponse>>, ApiError> {
+    try_get_recovery(&app_state, account, recovery_id)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_get_recovery(
+    app_state: &State<AppState>,
+    account: account_repo::Model,
+    recovery_id: String,
+) -> Result<Recovery, ApiError> {
+    let recovery = recovery_repo::find_by_account_id_and_id(
+        &app_state.database,
+        account.id.clone(),
+        recovery_id,
+    )
+    .await?
+    .ok_or_else(|| ApiError::NotFound("Recovery not found".to_string()))?;
+    to_recovery(app_state, &account, &recovery).await
+}
+
+async fn cancel_recovery(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    Path(recovery_id): Path<String>,
+) -> Result<Json<ApiResponse<Recovery, ApiErrorResponse>>, ApiError> {
+    try_cancel_recovery(&app_state, account, recovery_id)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_cancel_recovery(
+    app_state: &State<AppState>,
+    account: account_repo::Model,
+    recovery_id: String,
+) -> Result<Recovery, ApiError> {
+    let db = &app_state.database;
+    let recovery = recovery_repo::find_by_account_id_and_id(db, account.id.clone(), recovery_id)
+        .await?
+        .ok_or_else(|| ApiError::NotFound("Recovery not found".to_string()))?;
+    if recovery.status != PENDING {
+        return Err(ApiError::Conflict(format!(
+            "Recovery can't be cancelled with state: {}, must be in state PENDING",
+            recovery.status
+        )));
+    }
+
+    let now = app_state.clock.now_ms();
+    recovery_repo::update_status(db, recovery.id.clone(), CANCELLED, None, None, now).await?;
+    let recovery = recovery_repo::Model {
+        status: CANCELLED.to_string(),
+        updated_at: now,
+        ..recovery
+    };
+    to_recovery(app_state, &account, &recovery).await
+}
+
+pub(crate) async fn to_recovery(
+    app_state: &AppState,
+    account: &account_repo::Model,
+    recovery: &recovery_repo::Model,
+) -> Result<Recovery, ApiError> {
+    let (approvals, approvals_required) = approval_status(&app_state.database, recovery).await?;
+    Ok(Recovery {
+        id: recovery.id.clone(),
+        account_id: recovery.account_id.clone(),
+        wallet_address: account.wallet_address.clone(),
+        new_owner: recovery.new_owner.clone(),
+        status: recovery.status.clone(),
+        message: recovery_message(
+            &recovery.id,
+            &account.wallet_address,
+            &recovery.new_owner,
+            app_state.settings.chain_id(),
+        ),
+        approvals,
+        approvals_required,
+        executable_at: recovery.executable_at,
+        user_op_hash: recovery.user_op_hash.clone(),
+        created_at: recovery.created_at,
+    })
+}
@@ -1,16 +1,26 @@
-use super::{account_guardians_api, nomination_api};
+use super::extractors::{AuthenticatedAccount, ValidatedJson};
 use crate::{
-    models::api::*,
-    repos::{account_repo, db::AppState},
+    jobs::receipt_tracker::{DROPPED, FAILED, INCLUDED, PENDING, REVERTED, SUCCESS},
+    models::{api::*, error::ApiError, validation::parse_address},
+    operations::{
+        encryption::decrypt_private_key,
+        paymaster::{call_targets, check_policy, max_gas_cost, sponsor},
+        token::{parse_amount, NATIVE_DECIMALS},
+        user_operation::{paymaster, store_user_operation, user_op_hash},
+    },
+    repos::{
+        account_repo, db::AppState, paymaster_policy_repo, paymaster_sponsorship_repo,
+        user_operation_repo,
+    },
     routes::sign_message,
+    utils::convert_to_hex,
 };
 use axum::{
     extract::{Path, Query, State},
-    routing::post,
+    routing::{get, post},
     Json, Router,
 };
 
-use chrono::Utc;
 use clutch_wallet_lib::utils::wallet_lib::{self, abi_entry_point, Transaction};
 use clutch_wallet_lib::utils::{bundler::UserOperationTransport, wallet_lib::WalletLib};
 
@@ -21,14 +31,19 @@ use ethers::{
     types::{Bytes, U256},
     utils,
 };
-use hyper::StatusCode;
 use std::{str::FromStr, ops::Add};
+use uuid::Uuid;
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
 
 pub fn routes<S>(app_state: &AppState) -> Router<S> {
     Router::new()
         .route("/", post(send_transaction))
         .route("/prefund", post(prefund))
         .route("/format-user-op", post(format_user_op))
+        .route("/history", get(get_history))
+        .route("/:user_op_hash", get(get_user_operation))
         .with_state(app_state.to_owned())
 }
 
@@ -41,127 +56,305 @@ pub fn routes<S>(app_state: &AppState) -> Router<S> {
 )]
 async fn send_transaction(
     app_state: State<AppState>,
-    Json(req): Json<SendTransactionRequest>,
-) -> Result<Json<ApiResponse<SendTransactionResponse, ApiErrorResponse>>, StatusCode> {
-    match try_send_transaction(&app_state, &req).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    ValidatedJson(req): ValidatedJson<SendTransactionRequest>,
+) -> Result<Json<ApiResponse<SendTransactionResponse, ApiErrorResponse>>, ApiError> {
+    try_send_transaction(&app_state, account, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_send_transaction(
     app_state: &State<AppState>,
+    account: account_repo::Model,
     req: &SendTransactionRequest,
-) -> anyhow::Result<SendTransactionResponse> {
+) -> Result<SendTransactionResponse, ApiError> {
+    verify_wallet_owner(&account, &req.from)?;
+    verify_wallet_owner(&account, &convert_to_hex(req.user_op.sender))?;
+    if !account.is_custodial() {
+        return Err(ApiError::Conflict(
+            "Wallet owner key is not held since the wallet was recovered".to_string(),
+        ));
+    }
     let app_state = app_state.0.clone();
-    let account = account_repo::find_by_wallet_address(&app_state.database, req.from.clone())
-        .await?
-        .unwrap();
-    let private_key = account.eoa_private_address;
+    let private_key = decrypt_private_key(
+        &app_state.settings.wallet_master_key()?,
+        &account.eoa_private_data_key,
+        &account.eoa_private_address,
+    )?;
     let wallet_signer = private_key
         .as_str()
         .parse::<LocalWallet>()
-        .unwrap()
+        .map_err(|e| ApiError::Internal(format!("Invalid account key: {}", e)))?
         .with_chain_id(app_state.settings.chain_id());
 
-    let mut wallet_lib = app_state.wallet_lib;
+    let wallet = app_state.wallet.clone();
 
-    let dt = Utc::now();
-    let valid_after = dt.timestamp() as u64;
-    let valid_until = dt.timestamp() as u64 + 3600;
+    let now = app_state.clock.now_ms();
+    let valid_after = (now / 1000) as u64;
+    let valid_until = valid_after + 3600;
 
     let mut user_op_tx = req.user_op.clone();
-    let _ = wallet_lib
-        .estimate_user_operation_gas(&mut user_op_tx, None)
+    let _ = wallet
+        .estimate_user_operation_gas(&mut user_op_tx)
         .await
-        .map_err(|e| anyhow::anyhow!("Err{}", e))?;
+        .map_err(|e| ApiError::Upstream(format!("Err{}", e)))?;
 
     user_op_tx.verification_gas_limit = user_op_tx.verification_gas_limit.add(U256::from(40000));
     user_op_tx.pre_verification_gas = user_op_tx.pre_verification_gas.add(U256::from(1872));
-    
-    let (packed_user_op_hash, validation_data) = wallet_lib
+
+    // the gas changed since the user op was formatted, so its sponsorship is checked and signed again
+    let sponsoring_policy = if is_sponsored(&app_state, &user_op_tx)? {
+        let policy = sponsoring_policy(&app_state, &account, &user_op_tx, now).await?;
+        sign_sponsorship(&app_state, &mut user_op_tx).await?;
+        Some(policy)
+    } else {
+        None
+    };
+
+    let (packed_user_op_hash, validation_data) = wallet
         .pack_user_op_hash(user_op_tx.clone(), Some(valid_after), Some(valid_until))
         .await
-        .map_err(|e| anyhow::anyhow!("Err{}", e))?;
+        .map_err(|e| ApiError::Upstream(format!("Err{}", e)))?;
 
     let signature = sign_message(packed_user_op_hash, wallet_signer).await?;
-    let packed_signature_ret = wallet_lib
-        .pack_user_op_signature(signature, validation_data, None)
+    let packed_signature_ret = wallet
+        .pack_user_op_signature(signature, validation_data)
         .await
-        .map_err(|e| anyhow::anyhow!("Err{}", e))?;
+        .map_err(|e| ApiError::Upstream(format!("Err{}", e)))?;
     user_op_tx.signature = ethers::types::Bytes::from(packed_signature_ret);
-    let _ = wallet_lib
-        .send_user_operation(user_op_tx.clone())
-        .await
-        .map_err(|e| anyhow::anyhow!("Err{}", e))?;
+
+    let entry_point = Address::from_str(&app_state.settings.contracts.entry_point())
+        .map_err(|e| ApiError::Internal(format!("Invalid entry point address: {}", e)))?;
+    let user_op_hash = format!(
+        "{:?}",
+        user_op_hash(&user_op_tx, entry_point, app_state.settings.chain_id())
+    );
+    let sent = wallet.send_user_operation(user_op_tx.clone()).await;
+    let (status, bundler_response) = match &sent {
+        Ok(response) => (PENDING, format!("{:?}", response)),
+        Err(e) => (FAILED, format!("{}", e)),
+    };
+    store_user_operation(
+        &app_state.database,
+        account.id.clone(),
+        &user_op_tx,
+        user_op_hash.clone(),
+        status,
+        bundler_response,
+        now,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(format!("Error storing user operation: {}", e)))?;
+
+    sent.map_err(|e| ApiError::Upstream(format!("Err{}", e)))?;
+    if let Some(policy) = sponsoring_policy {
+        paymaster_sponsorship_repo::create(
+            &app_state.database,
+            paymaster_sponsorship_repo::Model {
+                id: Uuid::new_v4().to_string(),
+                policy_id: policy.id,
+                account_id: account.id.clone(),
+                user_op_hash: user_op_hash.clone(),
+                max_gas_cost: max_gas_cost(&user_op_tx).to_string(),
+                created_at: now,
+            },
+        )
+        .await?;
+    }
     Ok(SendTransactionResponse {
         status: "Success".to_string(),
+        user_op_hash,
     })
 }
 
-async fn prefund(
+async fn get_history(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    Query(params): Query<TransactionHistoryParams>,
+) -> Result<Json<ApiResponse<ListUserOperationsResponse, ApiErrorResponse>>, ApiError> {
+    try_get_history(&app_state, account, params)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_get_history(
+    app_state: &State<AppState>,
+    account: account_repo::Model,
+    params: TransactionHistoryParams,
+) -> Result<ListUserOperationsResponse, ApiError> {
+    if let Some(status) = &params.status {
+        validate_user_operation_status(status)?;
+    }
+    let page = params.page.unwrap_or(0);
+    let page_size = params.page_size.unwrap_or(DEFAULT_PAGE_SIZE);
+    if page_size == 0 || page_size > MAX_PAGE_SIZE {
+        return Err(ApiError::Validation(format!(
+            "Invalid page size {}, must be between 1 and {}",
+            page_size, MAX_PAGE_SIZE
+        )));
+    }
+
+    let (user_operations, total) = user_operation_repo::find_page_by_account(
+        &app_state.database,
+        account.id,
+        params.status,
+        params.created_after,
+        params.created_before,
+        page,
+        page_size,
+    )
+    .await?;
+
+    Ok(ListUserOperationsResponse {
+        user_operations: user_operations.iter().map(to_user_operation).collect(),
+        page,
+        page_size,
+        total,
+    })
+}
+
+async fn get_user_operation(
     app_state: State<AppState>,
-    Json(req): Json<PrefundRequest>,
-) -> Result<Json<ApiResponse<PrefundResponse, ApiErrorResponse>>, StatusCode> {
-    match try_prefud(&app_state, &req).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    Path(user_op_hash): Path<String>,
+) -> Result<Json<ApiResponse<UserOperation, ApiErrorResponse>>, ApiError> {
+    try_get_user_operation(&app_state, account, user_op_hash)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
+async fn try_get_user_operation(
+    app_state: &State<AppState>,
+    account: account_repo::Model,
+    user_op_hash: String,
+) -> Result<UserOperation, ApiError> {
+    user_operation_repo::find_by_account_and_user_op_hash(
+        &app_state.database,
+        account.id,
+        user_op_hash.to_lowercase(),
+    )
+    .await?
+    .map(|user_operation| to_user_operation(&user_operation))
+    .ok_or_else(|| ApiError::NotFound(format!("User operation not found {}", user_op_hash)))
+}
+
+fn validate_user_operation_status(status: &str) -> Result<(), ApiError> {
+    match status {
+        PENDING | INCLUDED | SUCCESS | REVERTED | DROPPED | FAILED => Ok(()),
+        _ => Err(ApiError::Validation(format!(
+            "Invalid status {} must be one of {}, {}, {}, {}, {} or {}",
+            status, PENDING, INCLUDED, SUCCESS, REVERTED, DROPPED, FAILED
+        ))),
     }
 }
 
+fn to_user_operation(user_operation: &user_operation_repo::Model) -> UserOperation {
+    UserOperation {
+        user_op_hash: user_operation.user_op_hash.clone(),
+        sender: user_operation.sender.clone(),
+        nonce: user_operation.nonce.clone(),
+        call_data_summary: user_operation.call_data_summary.clone(),
+        call_gas_limit: user_operation.call_gas_limit.clone(),
+        verification_gas_limit: user_operation.verification_gas_limit.clone(),
+        pre_verification_gas: user_operation.pre_verification_gas.clone(),
+        max_fee_per_gas: user_operation.max_fee_per_gas.clone(),
+        max_priority_fee_per_gas: user_operation.max_priority_fee_per_gas.clone(),
+        paymaster: user_operation.paymaster.clone(),
+        bundler_response: user_operation.bundler_response.clone(),
+        status: user_operation.status.clone(),
+        transaction_hash: user_operation.transaction_hash.clone(),
+        block_number: user_operation.block_number,
+        actual_gas_cost: user_operation.actual_gas_cost.clone(),
+        created_at: user_operation.created_at,
+        updated_at: user_operation.updated_at,
+    }
+}
+
+async fn prefund(
+    app_state: State<AppState>,
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    ValidatedJson(req): ValidatedJson<PrefundRequest>,
+) -> Result<Json<ApiResponse<PrefundResponse, ApiErrorResponse>>, ApiError> {
+    try_prefud(&app_state, account, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
+}
+
 async fn try_prefud(
     app_state: &State<AppState>,
+    account: account_repo::Model,
     req: &PrefundRequest,
-) -> anyhow::Result<PrefundResponse> {
-    let mut tx: Transaction = Default::default();
+) -> Result<PrefundResponse, ApiError> {
+    verify_wallet_owner(&account, &req.from)?;
     let app_state = app_state.0.clone();
-    let mut wallet_lib = app_state.wallet_lib;
-    if req.send_type == "send_eth" {
-        tx = Transaction {
-            to: Address::from_str(&req.to).unwrap(),
+    let wallet = app_state.wallet.clone();
+    let from = parse_address("from", &req.from)?;
+    let to = parse_address("to", &req.to)?;
+    let value = req
+        .value
+        .as_deref()
+        .ok_or_else(|| ApiError::Validation("Missing value".to_string()))?;
+    let tx = match req.send_type {
+        SendType::SendEth => Transaction {
+            to,
             data: Some(Bytes::from(b"")),
-            value: Some(ethers::utils::parse_ether(&req.value.clone().unwrap()).unwrap()),
-            gas_limit: None,
-        };
-    } else if req.send_type == "send_erc20" {
-        let call_data = WalletLib::transfer_erc20_calldata(
-            Address::from_str(&req.to).unwrap(),
-            ethers::utils::parse_ether(&req.value.clone().unwrap()).unwrap(),
-        )
-        .unwrap();
-        tx = Transaction {
-            to: Address::from_str(&req.pay_token.clone().unwrap()).unwrap(),
-            data: Some(call_data),
-            value: None,
+            value: Some(
+                parse_amount(value, NATIVE_DECIMALS)
+                    .map_err(|e| ApiError::Validation(e.to_string()))?,
+            ),
             gas_limit: None,
-        };
+        },
+        SendType::SendErc20 => {
+            let token = parse_address("pay_token", req.pay_token.as_deref().unwrap_or_default())?;
+            let decimals = app_state
+                .tokens
+                .decimals(&app_state.database, token, app_state.clock.now_ms())
+                .await
+                .map_err(|e| {
+                    ApiError::Upstream(format!(
+                        "Error resolving decimals of token {}: {}",
+                        convert_to_hex(token),
+                        e
+                    ))
+                })?;
+            let amount =
+                parse_amount(value, decimals).map_err(|e| ApiError::Validation(e.to_string()))?;
+            let call_data = WalletLib::transfer_erc20_calldata(to, amount)
+                .map_err(|e| ApiError::Internal(format!("Error encoding token transfer: {}", e)))?;
+            Transaction {
+                to: token,
+                data: Some(call_data),
+                value: None,
+                gas_limit: None,
+            }
+        }
     };
 
-    let max_fee_per_gas = U256::from_str(&app_state.settings.default_max_fee()).unwrap();
-    let max_priority_fee_per_gas =
-        U256::from_str(&app_state.settings.default_max_priority_fee()).unwrap();
-    let mut user_op = wallet_lib
+    let fees = app_state
+        .gas_oracle
+        .fees()
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Error fetching gas fees: {}", e)))?;
+    let mut user_op = wallet
         .from_transaction(
-            max_fee_per_gas,
-            max_priority_fee_per_gas,
-            Address::from_str(&req.from).unwrap(),
+            fees.max_fee_per_gas,
+            fees.max_priority_fee_per_gas,
+            from,
             vec![tx],
-            None,
         )
         .await
-        .map_err(|e| anyhow::anyhow!("Err {}", e))
-        .unwrap();
+        .map_err(|e| ApiError::Upstream(format!("Err {}", e)))?;
 
-    let _ = wallet_lib
-        .estimate_user_operation_gas(&mut user_op, None)
+    let _ = wallet
+        .estimate_user_operation_gas(&mut user_op)
         .await
-        .map_err(|e| anyhow::anyhow!("Err{}", e))?;
+        .map_err(|e| ApiError::Upstream(format!("Err{}", e)))?;
 
-    let prefund = wallet_lib
+    let prefund = wallet
         .pre_fund(user_op)
         .await
-        .map_err(|e| anyhow::anyhow!("Err {}", e))
-        .unwrap();
+        .map_err(|e| ApiError::Upstream(format!("Err {}", e)))?;
     Ok(PrefundResponse {
         deposit: prefund.deposit.to_string(),
         prefund: prefund.prefund.to_string(),
@@ -171,55 +364,190 @@ async fn try_prefud(
 
 async fn format_user_op(
     app_state: State<AppState>,
-    Json(req): Json<FormatUserOpRequest>,
-) -> Result<Json<ApiResponse<FormatUserOpResponse, ApiErrorResponse>>, StatusCode> {
-    match try_format_user_op(&app_state, &req).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+    AuthenticatedAccount { account, .. }: AuthenticatedAccount,
+    ValidatedJson(req): ValidatedJson<FormatUserOpRequest>,
+) -> Result<Json<ApiResponse<FormatUserOpResponse, ApiErrorResponse>>, ApiError> {
+    try_format_user_op(&app_state, account, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_format_user_op(
     app_state: &State<AppState>,
+    account: account_repo::Model,
     req: &FormatUserOpRequest,
-) -> anyhow::Result<FormatUserOpResponse> {
+) -> Result<FormatUserOpResponse, ApiError> {
+    verify_wallet_owner(&account, &convert_to_hex(req.selected_address))?;
     let app_state = app_state.0.clone();
-    let mut wallet_lib = app_state.wallet_lib;
+    let wallet = app_state.wallet.clone();
     let raw_txs = req
         .raw_txs
         .iter()
         .map(|trx| trx.clone())
         .collect::<Vec<Transaction>>();
-    let max_fee_per_gas = U256::from_str(&app_state.settings.default_max_fee()).unwrap();
-    let max_priority_fee_per_gas =
-        U256::from_str(&app_state.settings.default_max_priority_fee()).unwrap();
+    let fees = app_state
+        .gas_oracle
+        .fees()
+        .await
+        .map_err(|e| ApiError::Upstream(format!("Error fetching gas fees: {}", e)))?;
 
-    let mut user_op = wallet_lib
+    let mut user_op = wallet
         .from_transaction(
-            max_fee_per_gas,
-            max_priority_fee_per_gas,
+            fees.max_fee_per_gas,
+            fees.max_priority_fee_per_gas,
             req.selected_address,
             raw_txs,
-            None,
         )
         .await
-        .map_err(|err| anyhow::anyhow!("Err, {}", err))?;
-    if req.pay_token.is_zero() == false {
-        let paymaster = Address::from_str(&app_state.settings.contracts.paymaster()).unwrap();
+        .map_err(|err| ApiError::Upstream(format!("Err, {}", err)))?;
+    if req.sponsored {
+        if !req.pay_token.is_zero() {
+            return Err(ApiError::Validation(
+                "A sponsored user operation cannot pay with a token".to_string(),
+            ));
+        }
+        // signed up front so the estimation runs through the verifying paymaster
+        sign_sponsorship(&app_state, &mut user_op).await?;
+    } else if req.pay_token.is_zero() == false {
+        let paymaster_supported = app_state
+            .tokens
+            .find(&app_state.database, req.pay_token)
+            .await?
+            .map_or(false, |token| token.paymaster_supported);
+        if !paymaster_supported {
+            return Err(ApiError::Validation(format!(
+                "Token {} is not supported by the paymaster",
+                convert_to_hex(req.pay_token)
+            )));
+        }
+        let paymaster = Address::from_str(&app_state.settings.contracts.paymaster())
+            .map_err(|e| ApiError::Internal(format!("Invalid paymaster address: {}", e)))?;
         user_op.paymaster_and_data = WalletLib::add_paymaster_and_data(req.pay_token, paymaster)
             .await
-            .map_err(|err| anyhow::anyhow!("Err : {}", err))?;
+            .map_err(|err| ApiError::Upstream(format!("Err : {}", err)))?;
     }
 
-    let _ret = wallet_lib
-        .estimate_user_operation_gas(&mut user_op, None)
+    let _ret = wallet
+        .estimate_user_operation_gas(&mut user_op)
         .await
-        .map_err(|err| anyhow::anyhow!("Err: {}", err))?;
-    let prefund = wallet_lib
+        .map_err(|err| ApiError::Upstream(format!("Err: {}", err)))?;
+    if req.sponsored {
+        sponsoring_policy(&app_state, &account, &user_op, app_state.clock.now_ms()).await?;
+        sign_sponsorship(&app_state, &mut user_op).await?;
+    }
+    let prefund = wallet
         .pre_fund(user_op.clone())
         .await
-        .map_err(|e| anyhow::anyhow!("Err {}", e))
-        .unwrap();
+        .map_err(|e| ApiError::Upstream(format!("Err {}", e)))?;
 
     Ok(FormatUserOpResponse { user_op, prefund })
 }
+
+fn verifying_paymaster(app_state: &AppState) -> Result<Option<Address>, ApiError> {
+    app_state
+        .settings
+        .paymaster
+        .verifying_paymaster
+        .as_ref()
+        .map(|paymaster| {
+            Address::from_str(paymaster).map_err(|e| {
+                ApiError::Internal(format!("Invalid verifying paymaster address: {}", e))
+            })
+        })
+        .transpose()
+}
+
+fn is_sponsored(app_state: &AppState, user_op: &UserOperationTransport) -> Result<bool, ApiError> {
+    let sponsored = verifying_paymaster(app_state)?.map_or(false, |verifying_paymaster| {
+        paymaster(&user_op.paymaster_and_data) == Some(verifying_paymaster)
+    });
+    Ok(sponsored)
+}
+
+// the first active policy, account specific ones first, that covers the user op as it will be sent
+async fn sponsoring_policy(
+    app_state: &AppState,
+    account: &account_repo::Model,
+    user_op: &UserOperationTransport,
+    now: i64,
+) -> Result<paymaster_policy_repo::Model, ApiError> {
+    let policies =
+        paymaster_policy_repo::find_all_active_for_account(&app_state.database, account.id.clone())
+            .await?;
+    let targets = call_targets(&user_op.call_data);
+    let cost = max_gas_cost(user_op);
+
+    let mut rejections = vec![];
+    for policy in policies {
+        let sponsored = paymaster_sponsorship_repo::find_all_by_policy_id_and_account_id(
+            &app_state.database,
+            policy.id.clone(),
+            account.id.clone(),
+        )
+        .await?
+        .iter()
+        .try_fold(U256::zero(), |sum, sponsorship| {
+            U256::from_dec_str(&sponsorship.max_gas_cost)
+                .map(|cost| sum.saturating_add(cost))
+                .map_err(|e| ApiError::Internal(format!("Invalid sponsored gas cost: {}", e)))
+        })?;
+        match check_policy(&policy, targets.as_deref(), cost, sponsored, now) {
+            Ok(()) => return Ok(policy),
+            Err(e) => rejections.push(e.to_string()),
+        }
+    }
+
+    if rejections.is_empty() {
+        Err(ApiError::Forbidden(
+            "No paymaster policy sponsors this account".to_string(),
+        ))
+    } else {
+        Err(ApiError::Forbidden(format!(
+            "User operation is not sponsored: {}",
+            rejections.join("; ")
+        )))
+    }
+}
+
+async fn sign_sponsorship(
+    app_state: &AppState,
+    user_op: &mut UserOperationTransport,
+) -> Result<(), ApiError> {
+    let paymaster = verifying_paymaster(app_state)?.ok_or_else(|| {
+        ApiError::Validation("Sponsored user operations are not enabled".to_string())
+    })?;
+    let signer = app_state
+        .settings
+        .paymaster
+        .signer()
+        .map_err(|e| ApiError::Internal(format!("{}", e)))?
+        .ok_or_else(|| ApiError::Internal("No paymaster signer configured".to_string()))?
+        .parse::<LocalWallet>()
+        .map_err(|e| ApiError::Internal(format!("Invalid paymaster signer: {}", e)))?;
+    let valid_after = (app_state.clock.now_ms() / 1000) as u64;
+    let valid_until = valid_after + app_state.settings.paymaster.validity_secs;
+
+    sponsor(
+        user_op,
+        &signer,
+        paymaster,
+        app_state.settings.chain_id(),
+        valid_after,
+        valid_until,
+    )
+    .await
+    .map_err(|e| ApiError::Internal(format!("Error signing sponsorship: {}", e)))
+}
+
+// the custodial key signs for the account's own wallet only
+fn verify_wallet_owner(account: &account_repo::Model, address: &str) -> Result<(), ApiError> {
+    let address = parse_address("wallet", address)?;
+    if account.wallet_address == convert_to_hex(address) {
+        Ok(())
+    } else {
+        Err(ApiError::Forbidden(format!(
+            "Wallet {} does not belong to account",
+            convert_to_hex(address)
+        )))
+    }
+}
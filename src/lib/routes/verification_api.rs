@@ -1,14 +1,12 @@
-use crate::models::api::{
-    api_error, api_success, ApiErrorResponse, ApiResponse, VerificationRequest,
-    VerificationResponse,
+use crate::config::settings::Settings;
+use crate::models::{
+    api::{api_success, ApiErrorResponse, ApiResponse, VerificationRequest, VerificationResponse},
+    error::ApiError,
 };
 use crate::operations::code::generate_code;
-use crate::operations::email::send_verification_code_email;
-use crate::operations::time::get_unix_timestamp_ms;
 use crate::repos::db::AppState;
 use crate::repos::verification_repo;
 use axum::extract::State;
-use axum::http::StatusCode;
 use axum::Json;
 use axum::{routing::post, Router};
 use email_address::EmailAddress;
@@ -27,70 +25,87 @@ responses(
 async fn create_verification(
     app_state: State<AppState>,
     Json(req): Json<VerificationRequest>,
-) -> Result<Json<ApiResponse<VerificationResponse, ApiErrorResponse>>, StatusCode> {
-    match try_create_verification(&app_state, &req).await {
-        Ok(payload) => Ok(Json(api_success(payload))),
-        Err(error_payload) => Ok(Json(api_error(format!("{}", error_payload)))),
-    }
+) -> Result<Json<ApiResponse<VerificationResponse, ApiErrorResponse>>, ApiError> {
+    try_create_verification(&app_state, &req)
+        .await
+        .map(|payload| Json(api_success(payload)))
 }
 
 async fn try_create_verification(
     app_state: &State<AppState>,
     req: &VerificationRequest,
-) -> anyhow::Result<VerificationResponse> {
+) -> Result<VerificationResponse, ApiError> {
     if EmailAddress::is_valid(&req.email) {
         let code = generate_code();
         verify_email_send_limit(app_state, req).await?;
-        store_verification(app_state.database.clone(), req.email.clone(), code.clone()).await?;
-        send_email_verification_code(app_state, req.email.clone(), code).await?;
+        store_verification(
+            app_state.database.clone(),
+            req.email.clone(),
+            code.clone(),
+            app_state.clock.now_ms(),
+        )
+        .await?;
+        app_state
+            .mailer
+            .send_verification_code(req.email.clone(), code)
+            .await?;
         Ok(VerificationResponse { success: true })
     } else {
-        Err(anyhow::anyhow!("Invalid email format {}", req.email))
+        Err(ApiError::Validation(format!(
+            "Invalid email format {}",
+            req.email
+        )))
     }
 }
 
 async fn verify_email_send_limit(
     app_state: &State<AppState>,
     req: &VerificationRequest,
-) -> Result<(), anyhow::Error> {
-    let email_count = verification_repo::count_by_email(&app_state.database, &req.email).await?;
-    let email_send_limit = app_state.settings.email.send_limit.try_into().unwrap_or(0);
+) -> Result<(), ApiError> {
+    let email_count = verification_repo::count_by_email_created_after(
+        &app_state.database,
+        &req.email,
+        app_state.clock.now_ms() - SEND_LIMIT_WINDOW_MS,
+    )
+    .await?;
+    check_send_limit(
+        &app_state.settings,
+        "Email verification",
+        &req.email,
+        email_count,
+    )
+}
+
+// codes and invitation emails sent to an address are limited to the send limit within this window
+pub(crate) const SEND_LIMIT_WINDOW_MS: i64 = 60 * 60 * 1000;
+
+pub(crate) fn check_send_limit(
+    settings: &Settings,
+    what: &str,
+    email: &str,
+    email_count: u64,
+) -> Result<(), ApiError> {
+    let email_send_limit = settings.email.send_limit.try_into().unwrap_or(0);
     if email_count > email_send_limit {
-        return Err(anyhow::anyhow!(
-            "Email verification limit exceeded (attempts: {}, limit: {}) for email: {}",
-            email_count,
-            email_send_limit,
-            req.email
-        ));
+        return Err(ApiError::RateLimited(format!(
+            "{} limit exceeded (attempts: {}, limit: {}) for email: {}",
+            what, email_count, email_send_limit, email
+        )));
     };
     Ok(())
 }
 
-async fn send_email_verification_code(
-    app_state: &State<AppState>,
-    email: String,
-    code: String,
-) -> anyhow::Result<()> {
-    send_verification_code_email(
-        app_state.settings.email.key().clone(),
-        app_state.settings.email.template_id,
-        app_state.settings.email.base_url.clone(),
-        email,
-        code,
-    )
-    .await
-}
-
 pub async fn store_verification(
     db: DatabaseConnection,
     email: String,
     code: String,
-) -> anyhow::Result<()> {
+    now: i64,
+) -> Result<(), ApiError> {
     let id = Uuid::new_v4();
     let one_minute = 60 * 1000;
-    let expires_at = get_unix_timestamp_ms() + one_minute;
+    let expires_at = now + one_minute;
 
-    verification_repo::create(&db, id, &email, &code, expires_at)
+    verification_repo::create(&db, id, &email, &code, expires_at, now)
         .await
-        .map_err(|e| anyhow::anyhow!("Error storing verification: {}", e))
+        .map_err(|e| ApiError::Internal(format!("Error storing verification: {}", e)))
 }
@@ -0,0 +1,32 @@
+use crate::operations::time::{get_unix_timestamp_ms, Clock};
+use std::sync::atomic::{AtomicI64, Ordering};
+
+/// Starts at the wall clock time and only moves when a test tells it to.
+#[derive(Debug)]
+pub struct FakeClock {
+    now: AtomicI64,
+}
+
+impl FakeClock {
+    pub fn set(&self, now: i64) {
+        self.now.store(now, Ordering::SeqCst);
+    }
+
+    pub fn advance(&self, ms: i64) {
+        self.now.fetch_add(ms, Ordering::SeqCst);
+    }
+}
+
+impl Default for FakeClock {
+    fn default() -> Self {
+        FakeClock {
+            now: AtomicI64::new(get_unix_timestamp_ms()),
+        }
+    }
+}
+
+impl Clock for FakeClock {
+    fn now_ms(&self) -> i64 {
+        self.now.load(Ordering::SeqCst)
+    }
+}
@@ -0,0 +1,70 @@
+use crate::operations::email::Mailer;
+use axum::async_trait;
+use std::sync::Mutex;
+
+/// Records verification codes and invitation links instead of sending them, so tests can read
+/// them back.
+#[derive(Debug, Default)]
+pub struct FakeMailer {
+    sent: Mutex<Vec<(String, String)>>,
+    invitations: Mutex<Vec<(String, String, bool)>>,
+}
+
+impl FakeMailer {
+    pub fn last_code_for(&self, email: &str) -> Option<String> {
+        self.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(to, _)| to == email)
+            .map(|(_, code)| code.clone())
+    }
+
+    pub fn sent_count(&self) -> usize {
+        self.sent.lock().unwrap().len()
+    }
+
+    /// The token of the last invitation link sent to `email`.
+    pub fn last_invitation_token_for(&self, email: &str) -> Option<String> {
+        self.invitations
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(to, _, _)| to == email)
+            .and_then(|(_, link, _)| link.split("token=").nth(1).map(|t| t.to_string()))
+    }
+
+    pub fn invitation_count(&self) -> usize {
+        self.invitations.lock().unwrap().len()
+    }
+
+    pub fn reminder_count(&self) -> usize {
+        self.invitations
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, _, reminder)| *reminder)
+            .count()
+    }
+}
+
+#[async_trait]
+impl Mailer for FakeMailer {
+    async fn send_verification_code(&self, to: String, code: String) -> anyhow::Result<()> {
+        self.sent.lock().unwrap().push((to, code));
+        Ok(())
+    }
+
+    async fn send_nomination_invitation(
+        &self,
+        to: String,
+        _nominator: String,
+        link: String,
+        reminder: bool,
+    ) -> anyhow::Result<()> {
+        self.invitations.lock().unwrap().push((to, link, reminder));
+        Ok(())
+    }
+}
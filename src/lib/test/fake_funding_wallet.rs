@@ -0,0 +1,54 @@
+use crate::operations::treasury::FundingWallet;
+use axum::async_trait;
+use ethers::{
+    types::{Address, H256, U256},
+    utils::{keccak256, parse_ether},
+};
+use std::sync::Mutex;
+
+/// Funding wallet with an in-memory balance, starts with 10 ether and records transfers.
+#[derive(Debug)]
+pub struct FakeFundingWallet {
+    balance: Mutex<U256>,
+    transfers: Mutex<Vec<(Address, U256)>>,
+}
+
+impl FakeFundingWallet {
+    pub fn set_balance(&self, balance: U256) {
+        *self.balance.lock().unwrap() = balance;
+    }
+
+    pub fn transfers(&self) -> Vec<(Address, U256)> {
+        self.transfers.lock().unwrap().clone()
+    }
+}
+
+impl Default for FakeFundingWallet {
+    fn default() -> Self {
+        FakeFundingWallet {
+            balance: Mutex::new(parse_ether(10).unwrap()),
+            transfers: Mutex::new(vec![]),
+        }
+    }
+}
+
+#[async_trait]
+impl FundingWallet for FakeFundingWallet {
+    fn address(&self) -> Address {
+        Address::repeat_byte(0x77)
+    }
+
+    async fn balance(&self) -> anyhow::Result<U256> {
+        Ok(*self.balance.lock().unwrap())
+    }
+
+    async fn transfer(&self, to: Address, amount: U256) -> anyhow::Result<H256> {
+        let mut balance = self.balance.lock().unwrap();
+        *balance = balance
+            .checked_sub(amount)
+            .ok_or_else(|| anyhow::anyhow!("insufficient funds"))?;
+        let mut transfers = self.transfers.lock().unwrap();
+        transfers.push((to, amount));
+        Ok(H256::from(keccak256(transfers.len().to_be_bytes())))
+    }
+}
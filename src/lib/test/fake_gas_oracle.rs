@@ -0,0 +1,34 @@
+use crate::operations::gas_oracle::{Fees, GasOracle};
+use axum::async_trait;
+use ethers::types::U256;
+use std::sync::Mutex;
+
+/// Answers with fixed fees, tests can change them with `set_fees`.
+#[derive(Debug)]
+pub struct FakeGasOracle {
+    fees: Mutex<Fees>,
+}
+
+impl FakeGasOracle {
+    pub fn set_fees(&self, fees: Fees) {
+        *self.fees.lock().unwrap() = fees;
+    }
+}
+
+impl Default for FakeGasOracle {
+    fn default() -> Self {
+        FakeGasOracle {
+            fees: Mutex::new(Fees {
+                max_fee_per_gas: U256::from(2_000_000_000_u64),
+                max_priority_fee_per_gas: U256::from(1_000_000_000_u64),
+            }),
+        }
+    }
+}
+
+#[async_trait]
+impl GasOracle for FakeGasOracle {
+    async fn fees(&self) -> anyhow::Result<Fees> {
+        Ok(*self.fees.lock().unwrap())
+    }
+}
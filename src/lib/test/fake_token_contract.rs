@@ -0,0 +1,29 @@
+use crate::operations::token::TokenContract;
+use axum::async_trait;
+use ethers::types::Address;
+use std::{collections::HashMap, sync::Mutex};
+
+/// Token contracts with 18 decimals unless set otherwise, counts the on chain lookups.
+#[derive(Debug, Default)]
+pub struct FakeTokenContract {
+    decimals: Mutex<HashMap<Address, u8>>,
+    calls: Mutex<usize>,
+}
+
+impl FakeTokenContract {
+    pub fn set_decimals(&self, token: Address, decimals: u8) {
+        self.decimals.lock().unwrap().insert(token, decimals);
+    }
+
+    pub fn calls(&self) -> usize {
+        *self.calls.lock().unwrap()
+    }
+}
+
+#[async_trait]
+impl TokenContract for FakeTokenContract {
+    async fn decimals(&self, token: Address) -> anyhow::Result<u8> {
+        *self.calls.lock().unwrap() += 1;
+        Ok(*self.decimals.lock().unwrap().get(&token).unwrap_or(&18))
+    }
+}
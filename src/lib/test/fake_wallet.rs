@@ -0,0 +1,147 @@
+use crate::operations::wallet_backend::WalletBackend;
+use axum::async_trait;
+use clutch_wallet_lib::utils::{
+    bundler::UserOperationTransport,
+    wallet_lib::{PreFund, Transaction},
+};
+use ethers::{
+    abi::{encode, Token},
+    types::{Address, Bytes, H256, U256},
+    utils::{id, keccak256},
+};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+
+/// In-memory `WalletBackend` for tests, nothing leaves the process. Wallet addresses are
+/// derived from the owner, so they are stable across runs, and sent user ops are recorded.
+/// Transactions are encoded as `execute` or `executeBatch` calldata like the wallet does.
+#[derive(Debug, Default)]
+pub struct FakeWalletBackend {
+    sent: Mutex<Vec<UserOperationTransport>>,
+    unavailable: AtomicBool,
+    missfund: Mutex<U256>,
+}
+
+impl FakeWalletBackend {
+    /// What the wallet still lacks to pay for its user ops, zero by default.
+    pub fn set_missfund(&self, missfund: U256) {
+        *self.missfund.lock().unwrap() = missfund;
+    }
+
+    /// Makes the bundler reject every user op until switched back.
+    pub fn set_unavailable(&self, unavailable: bool) {
+        self.unavailable.store(unavailable, Ordering::SeqCst);
+    }
+
+    pub fn sent_user_operations(&self) -> Vec<UserOperationTransport> {
+        self.sent.lock().unwrap().clone()
+    }
+}
+
+#[async_trait]
+impl WalletBackend for FakeWalletBackend {
+    async fn create_unsigned_deploy_wallet_user_op(
+        &self,
+        index: u64,
+        owner: Address,
+        guardian_hash: H256,
+        _call_data: &str,
+    ) -> anyhow::Result<UserOperationTransport> {
+        let salt = [
+            owner.as_bytes(),
+            guardian_hash.as_bytes(),
+            &index.to_be_bytes(),
+        ]
+        .concat();
+        Ok(UserOperationTransport {
+            sender: Address::from_slice(&keccak256(salt)[12..]),
+            init_code: Bytes::from(owner.as_bytes().to_vec()),
+            ..Default::default()
+        })
+    }
+
+    async fn estimate_user_operation_gas(
+        &self,
+        user_op: &mut UserOperationTransport,
+    ) -> anyhow::Result<()> {
+        user_op.call_gas_limit = U256::from(50000);
+        user_op.verification_gas_limit = U256::from(100000);
+        user_op.pre_verification_gas = U256::from(45000);
+        Ok(())
+    }
+
+    async fn pre_fund(&self, _user_op: UserOperationTransport) -> anyhow::Result<PreFund> {
+        Ok(PreFund {
+            missfund: *self.missfund.lock().unwrap(),
+            ..Default::default()
+        })
+    }
+
+    async fn pack_user_op_hash(
+        &self,
+        user_op: UserOperationTransport,
+        _valid_after: Option<u64>,
+        _valid_until: Option<u64>,
+    ) -> anyhow::Result<(Vec<u8>, U256)> {
+        let packed = [user_op.sender.as_bytes(), &user_op.call_data].concat();
+        Ok((keccak256(packed).to_vec(), U256::zero()))
+    }
+
+    async fn pack_user_op_signature(
+        &self,
+        signature: Vec<u8>,
+        _validation_data: U256,
+    ) -> anyhow::Result<Vec<u8>> {
+        Ok(signature)
+    }
+
+    async fn send_user_operation(&self, user_op: UserOperationTransport) -> anyhow::Result<bool> {
+        if self.unavailable.load(Ordering::SeqCst) {
+            return Err(anyhow::anyhow!("bundler unavailable"));
+        }
+        self.sent.lock().unwrap().push(user_op);
+        Ok(true)
+    }
+
+    async fn from_transaction(
+        &self,
+        max_fee_per_gas: U256,
+        max_priority_fee_per_gas: U256,
+        from: Address,
+        txs: Vec<Transaction>,
+    ) -> anyhow::Result<UserOperationTransport> {
+        let call_data = match txs.as_slice() {
+            [] => vec![],
+            [tx] => [
+                id("execute(address,uint256,bytes)").to_vec(),
+                encode(&[
+                    Token::Address(tx.to),
+                    Token::Uint(tx.value.unwrap_or_default()),
+                    Token::Bytes(tx.data.clone().unwrap_or_default().to_vec()),
+                ]),
+            ]
+            .concat(),
+            txs => [
+                id("executeBatch(address[],bytes[])").to_vec(),
+                encode(&[
+                    Token::Array(txs.iter().map(|tx| Token::Address(tx.to)).collect()),
+                    Token::Array(
+                        txs.iter()
+                            .map(|tx| Token::Bytes(tx.data.clone().unwrap_or_default().to_vec()))
+                            .collect(),
+                    ),
+                ]),
+            ]
+            .concat(),
+        };
+        Ok(UserOperationTransport {
+            sender: from,
+            call_data: Bytes::from(call_data),
+            max_fee_per_gas,
+            max_priority_fee_per_gas,
+            ..Default::default()
+        })
+    }
+}
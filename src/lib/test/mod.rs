@@ -1 +1,7 @@
+pub mod fake_clock;
+pub mod fake_email;
+pub mod fake_funding_wallet;
+pub mod fake_gas_oracle;
+pub mod fake_token_contract;
+pub mod fake_wallet;
 pub mod utils;
@@ -1,38 +1,83 @@
 use crate::{
     config::settings::{Env, Settings},
     models::api::{AccountCreateResponse, ApiErrorResponse, ApiPayload, ApiResponse},
-    operations::time::get_unix_timestamp_ms,
+    operations::{time::get_unix_timestamp_ms, token::TokenRegistry, treasury::Treasury},
     repos::{
         db::{db_connect, AppState},
         migration::migrate,
         verification_repo,
     },
     routes::api::router,
+    test::{
+        fake_clock::FakeClock, fake_email::FakeMailer, fake_funding_wallet::FakeFundingWallet,
+        fake_gas_oracle::FakeGasOracle, fake_token_contract::FakeTokenContract,
+        fake_wallet::FakeWalletBackend,
+    },
 };
+use axum::{routing::post, Json, Router};
 use axum_test_helper::{TestClient, TestResponse};
 use hyper::StatusCode;
 use sea_orm::DatabaseConnection;
-use std::fs;
+use serde_json::{json, Value};
+use std::{collections::HashMap, fs, net::TcpListener, sync::Arc};
 use uuid::Uuid;
 
-pub async fn setup() -> (TestClient, AppState, String) {
-    // let settings = &Settings::new(Env::Test).unwrap();
-
-    // let random_db = format!("tests/db/db_{}", Uuid::new_v4());
-    // let random_db_connection_url = format!("sqlite://{}", random_db);
+pub struct Fakes {
+    pub wallet: Arc<FakeWalletBackend>,
+    pub gas_oracle: Arc<FakeGasOracle>,
+    pub funding_wallet: Arc<FakeFundingWallet>,
+    pub token_contract: Arc<FakeTokenContract>,
+    pub mailer: Arc<FakeMailer>,
+    pub clock: Arc<FakeClock>,
+}
 
-    // migrate(&random_db);
+pub async fn setup() -> (TestClient, AppState, String) {
+    let (client, app_state, random_db, _) = setup_with_fakes().await;
+    (client, app_state, random_db)
+}
 
-    // let app_state = AppState {
-    //     settings: settings.to_owned(),
-    //     database: db_connect(random_db_connection_url).await,
-    // };
+// Every test gets its own migrated sqlite database, remove it with tear_down
+pub async fn setup_with_fakes() -> (TestClient, AppState, String, Fakes) {
+    let settings = &Settings::new(Env::Test).unwrap();
+
+    let random_db = format!("tests/db/db_{}", Uuid::new_v4());
+    let random_db_connection_url = format!("sqlite://{}", random_db);
+
+    migrate(&random_db);
+
+    let fakes = Fakes {
+        wallet: Arc::new(FakeWalletBackend::default()),
+        gas_oracle: Arc::new(FakeGasOracle::default()),
+        funding_wallet: Arc::new(FakeFundingWallet::default()),
+        token_contract: Arc::new(FakeTokenContract::default()),
+        mailer: Arc::new(FakeMailer::default()),
+        clock: Arc::new(FakeClock::default()),
+    };
+    let app_state = AppState {
+        settings: settings.to_owned(),
+        database: db_connect(random_db_connection_url).await,
+        wallet: fakes.wallet.clone(),
+        gas_oracle: fakes.gas_oracle.clone(),
+        treasury: Arc::new(
+            Treasury::new(fakes.funding_wallet.clone(), &settings.treasury).unwrap(),
+        ),
+        tokens: Arc::new(TokenRegistry::new(
+            fakes.token_contract.clone(),
+            settings.chain_id(),
+        )),
+        mailer: fakes.mailer.clone(),
+        clock: fakes.clock.clone(),
+    };
+    app_state
+        .tokens
+        .seed(&app_state.database, &settings.tokens, fakes.clock.now_ms())
+        .await
+        .expect("error seeding tokens");
 
-    // let router = router(app_state.clone());
-    // let client = TestClient::new(router);
+    let router = router(app_state.clone());
+    let client = TestClient::new(router);
 
-    // (client, app_state, random_db)
-    unimplemented!()
+    (client, app_state, random_db, fakes)
 }
 
 pub async fn tear_down(db: String) {
@@ -64,9 +109,16 @@ pub async fn create_verified_account_jwt(
     let verify_expires_at = get_unix_timestamp_ms() + one_minute;
     let code = "123456";
 
-    verification_repo::create(db, verify_id, &email, code, verify_expires_at)
-        .await
-        .expect("error creating verification");
+    verification_repo::create(
+        db,
+        verify_id,
+        &email,
+        code,
+        verify_expires_at,
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating verification");
 
     let res = create_account(client, email, code.to_string()).await;
     assert_eq!(res.status(), StatusCode::OK);
@@ -76,11 +128,8 @@ pub async fn create_verified_account_jwt(
         .await;
 
     match json_response.payload {
-        ApiPayload::Success(AccountCreateResponse {
-            jwt,
-            contract_wallet_addr,
-        }) => jwt,
-        ApiPayload::Error(ApiErrorResponse { error_message }) => {
+        ApiPayload::Success(AccountCreateResponse { jwt, .. }) => jwt,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
             panic!("error: {}", error_message)
         }
     }
@@ -94,3 +143,36 @@ pub async fn create_verify(client: &TestClient, email: String) -> TestResponse {
         .send()
         .await
 }
+
+// Bundler Utils
+
+// Serves eth_getUserOperationReceipt for the given receipts and eth_blockNumber, returns its url
+pub async fn mock_bundler(receipts: HashMap<String, Value>, block_number: u64) -> String {
+    let receipts = Arc::new(receipts);
+    let app = Router::new().route(
+        "/",
+        post(move |Json(req): Json<Value>| {
+            let receipts = receipts.clone();
+            async move {
+                let result = match req["method"].as_str() {
+                    Some("eth_getUserOperationReceipt") => req["params"][0]
+                        .as_str()
+                        .and_then(|hash| receipts.get(hash).cloned())
+                        .unwrap_or(Value::Null),
+                    Some("eth_blockNumber") => json!(format!("0x{:x}", block_number)),
+                    _ => Value::Null,
+                };
+                Json(json!({ "jsonrpc": "2.0", "id": req["id"], "result": result }))
+            }
+        }),
+    );
+
+    let listener = TcpListener::bind("127.0.0.1:0").expect("Unable to bind mock bundler");
+    let address = listener.local_addr().unwrap();
+    tokio::spawn(
+        axum::Server::from_tcp(listener)
+            .unwrap()
+            .serve(app.into_make_service()),
+    );
+    format!("http://{}", address)
+}
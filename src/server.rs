@@ -1,10 +1,21 @@
 use lib::config::settings::Env;
 use lib::config::settings::Settings;
+use lib::jobs::nomination_expiry;
+use lib::jobs::nomination_reminders;
+use lib::jobs::receipt_tracker;
+use lib::jobs::recovery;
+use lib::jobs::wallet_deployment;
+use lib::operations::email::SendinblueMailer;
+use lib::operations::gas_oracle::FeeHistoryGasOracle;
+use lib::operations::time::SystemClock;
+use lib::operations::token::{RpcTokenContract, TokenRegistry};
+use lib::operations::treasury::{RpcFundingWallet, Treasury};
 use lib::repos::db::db_connect;
 use lib::repos::db::AppState;
+use lib::repos::migration::encrypt_private_keys;
 use lib::repos::migration::migrate;
 use lib::routes::api::router;
-use std::net::SocketAddr;
+use std::{net::SocketAddr, sync::Arc};
 
 use clutch_wallet_lib::utils::wallet_lib::*;
 
@@ -34,6 +45,16 @@ fn clutch_wallet(setting: &Settings) -> WalletLib {
     wallet_lib
 }
 
+fn treasury(settings: &Settings) -> Treasury {
+    let funding_wallet = RpcFundingWallet::new(
+        settings.rpc(),
+        &settings.wallet_private_key(),
+        settings.chain_id(),
+    )
+    .expect("Invalid treasury private key");
+    Treasury::new(Arc::new(funding_wallet), &settings.treasury).expect("Invalid treasury limits")
+}
+
 #[tokio::main]
 async fn main() {
     env_logger::init_from_env(env_logger::Env::default().default_filter_or("info"));
@@ -41,12 +62,52 @@ async fn main() {
 
     migrate(&settings.database.url);
 
+    let database = db_connect(settings.db_connection_url()).await;
+    let master_key = settings
+        .wallet_master_key()
+        .expect("Missing wallet master key");
+    if let Err(e) = encrypt_private_keys(&database, &master_key).await {
+        log::error!("Error encrypting private keys: {}", e);
+    }
+
     let app_state = AppState {
         settings: settings.to_owned(),
-        database: db_connect(settings.db_connection_url()).await,
-        wallet_lib: clutch_wallet(settings),
+        database,
+        wallet: Arc::new(clutch_wallet(settings)),
+        gas_oracle: Arc::new(FeeHistoryGasOracle::new(
+            settings.rpc(),
+            settings.gas.clone(),
+        )),
+        treasury: Arc::new(treasury(settings)),
+        tokens: Arc::new(TokenRegistry::new(
+            Arc::new(RpcTokenContract::new(settings.rpc())),
+            settings.chain_id(),
+        )),
+        mailer: Arc::new(SendinblueMailer {
+            api_key: settings.email.key(),
+            template_id: settings.email.template_id,
+            invitation_template_id: settings.invitation.template_id,
+            base_path: settings.email.base_url.clone(),
+        }),
+        clock: Arc::new(SystemClock),
     };
 
+    app_state
+        .tokens
+        .seed(
+            &app_state.database,
+            &settings.tokens,
+            app_state.clock.now_ms(),
+        )
+        .await
+        .expect("Unable to seed the token registry");
+
+    tokio::spawn(receipt_tracker::run(app_state.clone()));
+    tokio::spawn(wallet_deployment::run(app_state.clone()));
+    tokio::spawn(recovery::run(app_state.clone()));
+    tokio::spawn(nomination_reminders::run(app_state.clone()));
+    tokio::spawn(nomination_expiry::run(app_state.clone()));
+
     let router = router(app_state);
 
     let address: SocketAddr = settings
@@ -4,16 +4,18 @@ use hyper::header::AUTHORIZATION;
 use lib::models::api::AccountCreateResponse;
 use lib::models::api::ApiErrorResponse;
 use lib::models::api::ApiResponse;
-use lib::models::api::ListAccountsResponse;
 use lib::operations::time::get_unix_timestamp_ms;
 use lib::repos::account_repo;
 use lib::repos::verification_repo;
+use lib::routes::auth_api::create_session;
 use lib::test::utils::create_account;
 use lib::test::utils::create_verified_account_jwt;
 use lib::test::utils::create_verify;
 use lib::test::utils::setup;
+use lib::test::utils::setup_with_fakes;
 use lib::test::utils::tear_down;
-use sea_orm::DatabaseConnection;
+use sea_orm::{ConnectionTrait, DatabaseConnection, Statement};
+use serde_json::Value;
 use uuid::Uuid;
 
 #[tokio::test]
@@ -26,9 +28,16 @@ async fn test_successfully_create_account_with_jwt() {
     let expires_at = get_unix_timestamp_ms() + one_minute;
     let code = "123456";
 
-    verification_repo::create(&app_state.database, id, &email, code, expires_at)
-        .await
-        .expect("error creating verification");
+    verification_repo::create(
+        &app_state.database,
+        id,
+        &email,
+        code,
+        expires_at,
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating verification");
 
     let res = create_account(&client, email.clone(), code.to_string()).await;
     assert_eq!(res.status(), StatusCode::OK);
@@ -38,7 +47,9 @@ async fn test_successfully_create_account_with_jwt() {
         .await;
 
     insta::assert_yaml_snapshot!(json_response, {
-        ".**.jwt" => "[jwt]"
+        ".**.jwt" => "[jwt]",
+        ".**.refresh_token" => "[refresh_token]",
+        ".**.contract_wallet_addr" => "[address]"
     });
 
     tear_down(db_url).await;
@@ -54,7 +65,7 @@ async fn test_invalid_email_format() {
         .header("Content-Type", "application/json")
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
@@ -79,7 +90,7 @@ async fn test_error_when_account_already_exists() {
     assert_eq!(created.status(), StatusCode::OK);
 
     let res = create_account(&client, email.clone(), code.clone()).await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::CONFLICT);
 
     insta::assert_yaml_snapshot!(res.text().await);
 
@@ -93,13 +104,76 @@ async fn test_error_when_verification_not_found_for_account() {
     let email = "someone@example.com".to_string();
 
     let res = create_account(&client, email.clone(), "123456".to_string()).await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
 
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
 }
 
+#[tokio::test]
+async fn test_error_when_verification_not_found_with_unsupported_paymaster_token() {
+    let (client, _app_state, db_url) = setup().await;
+
+    // the code is checked before the paymaster tokens
+    let res = client
+        .post("/accounts")
+        .body(
+            "{\"email\":\"someone@example.com\",\"code\":\"123456\",\
+             \"paymaster_tokens\":[\"0x0000000000000000000000000000000000000001\"]}",
+        )
+        .header("Content-Type", "application/json")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_code_is_not_used_when_deployment_is_not_stored() {
+    let (client, app_state, db_url) = setup().await;
+    let db = &app_state.database;
+
+    let email = "someone@example.com".to_string();
+    let code = "123456";
+    verification_repo::create(
+        db,
+        Uuid::new_v4(),
+        &email,
+        code,
+        get_unix_timestamp_ms() + 60 * 1000,
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating verification");
+    execute(
+        db,
+        "CREATE TRIGGER fail_deployment_jobs BEFORE INSERT ON deployment_jobs \
+         BEGIN SELECT RAISE(ABORT, 'deployment jobs are failing'); END",
+    )
+    .await;
+
+    let res = create_account(&client, email.clone(), code.to_string()).await;
+    assert_eq!(res.status(), StatusCode::INTERNAL_SERVER_ERROR);
+    assert!(account_repo::find_by_email(db, &email)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(verification_repo::find_by_email_and_code(db, &email, code)
+        .await
+        .unwrap()
+        .is_some());
+
+    execute(db, "DROP TRIGGER fail_deployment_jobs").await;
+    let res = create_account(&client, email.clone(), code.to_string()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
+
 #[tokio::test]
 async fn test_error_when_verification_code_expired() {
     let (client, app_state, db_url) = setup().await;
@@ -110,24 +184,67 @@ async fn test_error_when_verification_code_expired() {
     let expires_at = get_unix_timestamp_ms() - one_minute;
     let code = "123456";
 
-    verification_repo::create(&app_state.database, id, &email, code, expires_at)
-        .await
-        .expect("error creating verification");
+    verification_repo::create(
+        &app_state.database,
+        id,
+        &email,
+        code,
+        expires_at,
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating verification");
 
     let res = create_account(&client, email.clone(), code.to_string()).await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
 
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
 }
 
+#[tokio::test]
+async fn test_successfully_create_account_with_emailed_code() {
+    let (client, _app_state, db_url, fakes) = setup_with_fakes().await;
+
+    let email = "someone@example.com".to_string();
+    let res = create_verify(&client, email.clone()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let code = fakes.mailer.last_code_for(&email).expect("no code sent");
+
+    let res = create_account(&client, email, code).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    // the wallet is deployed by the deployment job, not while signing up
+    assert!(fakes.wallet.sent_user_operations().is_empty());
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_emailed_code_expired() {
+    let (client, _app_state, db_url, fakes) = setup_with_fakes().await;
+
+    let email = "someone@example.com".to_string();
+    let res = create_verify(&client, email.clone()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let code = fakes.mailer.last_code_for(&email).expect("no code sent");
+
+    let two_minutes = 2 * 60 * 1000;
+    fakes.clock.advance(two_minutes);
+
+    let res = create_account(&client, email, code).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert!(fakes.wallet.sent_user_operations().is_empty());
+
+    tear_down(db_url).await;
+}
+
 #[tokio::test]
 async fn test_retrieve_all_accounts() {
     let (client, app_state, db_url) = setup().await;
 
     let email = "first@example.com".to_string();
-    create_verified_account(
+    let jwt = create_verified_account(
         &app_state.database,
         &client,
         email,
@@ -135,12 +252,22 @@ async fn test_retrieve_all_accounts() {
         "".to_string(),
     )
     .await;
-    let res = client.get("/accounts").send().await;
+    create_verified_account(
+        &app_state.database,
+        &client,
+        "another@me.com".to_string(),
+        "".to_string(),
+        "".to_string(),
+    )
+    .await;
+    let res = client
+        .get("/accounts")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
     assert_eq!(res.status(), StatusCode::OK);
 
-    let json_response = res
-        .json::<ApiResponse<ListAccountsResponse, ApiErrorResponse>>()
-        .await;
+    let json_response = res.json::<Value>().await;
 
     insta::assert_yaml_snapshot!(json_response, {
         ".**.accounts[0].id" => "[uuid]",
@@ -150,13 +277,33 @@ async fn test_retrieve_all_accounts() {
     tear_down(db_url).await;
 }
 
+#[tokio::test]
+async fn test_error_when_no_bearer_token_on_retrieve_accounts() {
+    let (client, app_state, db_url) = setup().await;
+
+    create_verified_account(
+        &app_state.database,
+        &client,
+        "first@example.com".to_string(),
+        "".to_string(),
+        "".to_string(),
+    )
+    .await;
+    let res = client.get("/accounts").send().await;
+
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
 #[tokio::test]
 async fn test_find_all_by_wallet_address() {
     let (client, app_state, db_url) = setup().await;
 
     let email = "first@example.com".to_string();
     let wallet_address = "123".to_string();
-    create_verified_account(
+    let jwt = create_verified_account(
         &app_state.database,
         &client,
         email,
@@ -174,13 +321,12 @@ async fn test_find_all_by_wallet_address() {
     .await;
     let res = client
         .get(format!("/accounts?wallet_address={}", wallet_address).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
     assert_eq!(res.status(), StatusCode::OK);
 
-    let json_response = res
-        .json::<ApiResponse<ListAccountsResponse, ApiErrorResponse>>()
-        .await;
+    let json_response = res.json::<Value>().await;
 
     insta::assert_yaml_snapshot!(json_response, {
         ".**.accounts[0].id" => "[uuid]",
@@ -196,7 +342,7 @@ async fn test_find_all_by_eoa_address() {
 
     let email = "first@example.com".to_string();
     let eao_address = "123".to_string();
-    create_verified_account(
+    let jwt = create_verified_account(
         &app_state.database,
         &client,
         email,
@@ -214,13 +360,12 @@ async fn test_find_all_by_eoa_address() {
     .await;
     let res = client
         .get(format!("/accounts?eoa_address={}", eao_address).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
     assert_eq!(res.status(), StatusCode::OK);
 
-    let json_response = res
-        .json::<ApiResponse<ListAccountsResponse, ApiErrorResponse>>()
-        .await;
+    let json_response = res.json::<Value>().await;
 
     insta::assert_yaml_snapshot!(json_response, {
         ".**.accounts[0].id" => "[uuid]",
@@ -236,7 +381,7 @@ async fn test_find_all_by_email_address() {
 
     let email = "first@example.com".to_string();
 
-    create_verified_account(
+    let jwt = create_verified_account(
         &app_state.database,
         &client,
         email.clone(),
@@ -254,13 +399,12 @@ async fn test_find_all_by_email_address() {
     .await;
     let res = client
         .get(format!("/accounts?email={}", email.clone()).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
     assert_eq!(res.status(), StatusCode::OK);
 
-    let json_response = res
-        .json::<ApiResponse<ListAccountsResponse, ApiErrorResponse>>()
-        .await;
+    let json_response = res.json::<Value>().await;
 
     insta::assert_yaml_snapshot!(json_response, {
         ".**.accounts[0].id" => "[uuid]",
@@ -270,6 +414,101 @@ async fn test_find_all_by_email_address() {
     tear_down(db_url).await;
 }
 
+#[tokio::test]
+async fn test_cannot_find_another_users_account_by_email_address() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_verified_account(
+        &app_state.database,
+        &client,
+        "first@example.com".to_string(),
+        "".to_string(),
+        "".to_string(),
+    )
+    .await;
+    create_verified_account(
+        &app_state.database,
+        &client,
+        "another@me.com".to_string(),
+        "".to_string(),
+        "".to_string(),
+    )
+    .await;
+    let res = client
+        .get("/accounts?email=another@me.com")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let json_response = res.json::<Value>().await;
+
+    insta::assert_yaml_snapshot!(json_response);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_retrieve_account_by_email() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "first@example.com".to_string();
+    let jwt = create_verified_account(
+        &app_state.database,
+        &client,
+        email.clone(),
+        "123".to_string(),
+        "".to_string(),
+    )
+    .await;
+    let res = client
+        .get(format!("/accounts/{}", email).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let json_response = res.json::<Value>().await;
+
+    insta::assert_yaml_snapshot!(json_response, {
+        ".**.id" => "[uuid]",
+        ".**.updated_at" => "[timestamp]"
+    });
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_retrieve_another_users_account_by_email() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_verified_account(
+        &app_state.database,
+        &client,
+        "first@example.com".to_string(),
+        "".to_string(),
+        "".to_string(),
+    )
+    .await;
+    create_verified_account(
+        &app_state.database,
+        &client,
+        "another@me.com".to_string(),
+        "".to_string(),
+        "".to_string(),
+    )
+    .await;
+    let res = client
+        .get("/accounts/another@me.com")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
 #[tokio::test]
 async fn test_successfully_update_account() {
     let (client, app_state, db_url) = setup().await;
@@ -322,7 +561,7 @@ async fn test_error_when_no_bearer_token_on_update_account() {
         .send()
         .await;
 
-    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
@@ -355,7 +594,7 @@ async fn test_error_when_cannot_decode_bearer_token_on_update_account() {
         .send()
         .await;
 
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
@@ -369,7 +608,7 @@ async fn create_verified_account(
     email: String,
     wallet_address: String,
     eoa_addres: String,
-) -> (Uuid, String, i64) {
+) -> String {
     let res = create_verify(client, email.clone()).await;
     assert_eq!(res.status(), StatusCode::OK);
 
@@ -382,9 +621,16 @@ async fn create_verified_account(
     let account_id = Uuid::new_v4();
     let account_created_at = get_unix_timestamp_ms();
 
-    verification_repo::create(&db, verify_id, &email, code, verify_expires_at)
-        .await
-        .expect("error creating verification");
+    verification_repo::create(
+        &db,
+        verify_id,
+        &email,
+        code,
+        verify_expires_at,
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating verification");
 
     account_repo::create(
         &db,
@@ -392,11 +638,24 @@ async fn create_verified_account(
         email.clone(),
         wallet_address,
         eoa_addres,
-        "".to_string(),
+        "sealed-private-key".to_string(),
+        "sealed-data-key".to_string(),
         account_created_at,
     )
     .await
     .expect("error creating account");
 
-    (verify_id, code.to_string(), verify_expires_at)
+    create_session(&db, account_id.to_string(), None, None, account_created_at)
+        .await
+        .expect("error creating session")
+        .0
+}
+
+async fn execute(db: &DatabaseConnection, sql: &str) {
+    db.execute(Statement::from_string(
+        db.get_database_backend(),
+        sql.to_string(),
+    ))
+    .await
+    .expect("error executing statement");
 }
@@ -37,6 +37,7 @@ async fn test_can_retrieve_all_guardians_for_an_account() {
         account_id.clone().to_string(),
         guardian_id.to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -349,7 +350,7 @@ async fn test_error_when_remove_an_active_guardian_from_an_account() {
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(delete_res.status(), StatusCode::OK);
+    assert_eq!(delete_res.status(), StatusCode::CONFLICT);
     insta::assert_yaml_snapshot!(delete_res.text().await);
 
     let res = client
@@ -370,3 +371,68 @@ async fn test_error_when_remove_an_active_guardian_from_an_account() {
 
     tear_down(db_url).await;
 }
+
+#[tokio::test]
+async fn test_removing_a_guardian_cancels_their_accepted_nomination() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt =
+        create_verified_account_jwt(&app_state.database, &client, "user@example.com".to_string())
+            .await;
+    let account_id = decode_jwt(jwt.clone()).await.unwrap().sub;
+
+    let guardian_id = Uuid::new_v4();
+    guardian_repo::create(
+        &app_state.database,
+        guardian_id,
+        "guardian@example.com".to_string(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let nomination_id = Uuid::new_v4();
+    nomination_repo::create(
+        &app_state.database,
+        nomination_id,
+        "guardian@example.com".to_string(),
+        account_id.clone(),
+        guardian_id.to_string(),
+        "ACCEPTED".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+
+    guardian_account_repo::create(
+        &app_state.database,
+        Uuid::new_v4(),
+        guardian_id.to_string(),
+        account_id.clone(),
+        "AVAILABLE".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let res = client
+        .delete(format!("/accounts/guardians/{}", guardian_id).as_str())
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let guardian_accounts =
+        guardian_account_repo::find_all_guardians_by_account_id(&app_state.database, account_id)
+            .await
+            .unwrap();
+    assert!(guardian_accounts.is_empty());
+    let nomination = nomination_repo::find_by_id(&app_state.database, nomination_id.to_string())
+        .await
+        .unwrap()
+        .expect("nomination not found");
+    assert_eq!(nomination.status, "CANCELLED");
+
+    tear_down(db_url).await;
+}
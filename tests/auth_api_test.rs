@@ -0,0 +1,543 @@
+use axum::http::StatusCode;
+use axum_test_helper::{TestClient, TestResponse};
+use ethers::signers::{LocalWallet, Signer};
+use hyper::header::AUTHORIZATION;
+use lib::models::api::{
+    ApiErrorResponse, ApiPayload, ApiResponse, ListSessionsResponse, LoginResponse,
+    RefreshResponse, SiweNonceResponse,
+};
+use lib::operations::jwt::{decode_jwt, ACCESS_TOKEN_TTL_MS, REFRESH_TOKEN_TTL_MS};
+use lib::operations::time::get_unix_timestamp_ms;
+use lib::repos::{account_repo, login_attempt_repo, verification_repo};
+use lib::test::utils::{setup, setup_with_fakes, tear_down};
+use lib::utils::convert_to_hex;
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+#[tokio::test]
+async fn test_successfully_login_with_email_code() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    create_code(&app_state.database, email.clone(), "123456", 60 * 1000).await;
+
+    let res = login(&client, email.clone(), "123456".to_string()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let json_response = res
+        .json::<ApiResponse<LoginResponse, ApiErrorResponse>>()
+        .await;
+
+    insta::assert_yaml_snapshot!(json_response, {
+        ".**.jwt" => "[jwt]",
+        ".**.refresh_token" => "[refresh_token]"
+    });
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_login_code_is_reused() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    create_code(&app_state.database, email.clone(), "123456", 60 * 1000).await;
+
+    let res = login(&client, email.clone(), "123456".to_string()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = login(&client, email.clone(), "123456".to_string()).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_login_code_expired() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    create_code(&app_state.database, email.clone(), "123456", -60 * 1000).await;
+
+    let res = login(&client, email.clone(), "123456".to_string()).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_login_without_account() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_code(&app_state.database, email.clone(), "123456", 60 * 1000).await;
+
+    let res = login(&client, email.clone(), "123456".to_string()).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_login_attempts_are_recorded() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    create_code(&app_state.database, email.clone(), "123456", 60 * 1000).await;
+
+    login(&client, email.clone(), "654321".to_string()).await;
+    login(&client, email.clone(), "123456".to_string()).await;
+
+    let attempts = login_attempt_repo::find_all_by_email(&app_state.database, email.clone())
+        .await
+        .unwrap()
+        .iter()
+        .map(|a| a.success)
+        .collect::<Vec<bool>>();
+    assert_eq!(attempts.len(), 2);
+    assert_eq!(attempts.iter().filter(|success| **success).count(), 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_refresh_rotates_refresh_token() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    let tokens = login_tokens(&client, &app_state.database, email.clone(), "123456").await;
+
+    let res = refresh(&client, tokens.refresh_token.clone()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let json_response = res
+        .json::<ApiResponse<RefreshResponse, ApiErrorResponse>>()
+        .await;
+    insta::assert_yaml_snapshot!(json_response, {
+        ".**.jwt" => "[jwt]",
+        ".**.refresh_token" => "[refresh_token]"
+    });
+
+    let res = refresh(&client, tokens.refresh_token.clone()).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_list_sessions() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    let tokens = login_tokens(&client, &app_state.database, email.clone(), "123456").await;
+    login_tokens(&client, &app_state.database, email.clone(), "654321").await;
+
+    let res = client
+        .get("/auth/sessions")
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let sessions = match res
+        .json::<ApiResponse<ListSessionsResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(payload) => payload.sessions,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    };
+    assert_eq!(sessions.len(), 2);
+    assert_eq!(sessions.iter().filter(|s| s.current).count(), 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_using_a_revoked_session() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    let tokens = login_tokens(&client, &app_state.database, email.clone(), "123456").await;
+    let other_tokens = login_tokens(&client, &app_state.database, email.clone(), "654321").await;
+    let session_id = decode_jwt(other_tokens.jwt.clone()).await.unwrap().sid;
+
+    let res = client
+        .delete(format!("/auth/sessions/{}", session_id).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = client
+        .get("/auth/sessions")
+        .header(AUTHORIZATION, format!("Bearer {}", other_tokens.jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    let res = refresh(&client, other_tokens.refresh_token.clone()).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_revoke_all_sessions() {
+    let (client, app_state, db_url) = setup().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    let tokens = login_tokens(&client, &app_state.database, email.clone(), "123456").await;
+    login_tokens(&client, &app_state.database, email.clone(), "654321").await;
+
+    let res = client
+        .delete("/auth/sessions")
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    let res = client
+        .get("/auth/sessions")
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_access_token_expired() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    let tokens = login_tokens(&client, &app_state.database, email.clone(), "123456").await;
+
+    fakes.clock.advance(ACCESS_TOKEN_TTL_MS);
+    let res = client
+        .get("/auth/sessions")
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    fakes.clock.advance(1);
+    let res = client
+        .get("/auth/sessions")
+        .header(AUTHORIZATION, format!("Bearer {}", tokens.jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    assert!(res.text().await.contains("Token expired"));
+
+    let res = refresh(&client, tokens.refresh_token.clone()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_refresh_token_expired() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+
+    let email = "someone@example.com".to_string();
+    create_account(&app_state.database, email.clone()).await;
+    let tokens = login_tokens(&client, &app_state.database, email.clone(), "123456").await;
+
+    fakes.clock.advance(REFRESH_TOKEN_TTL_MS);
+    let res = refresh(&client, tokens.refresh_token.clone()).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_successfully_login_with_siwe() {
+    let (client, app_state, db_url) = setup().await;
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    create_account_with_eoa(
+        &app_state.database,
+        "someone@example.com".to_string(),
+        &wallet,
+    )
+    .await;
+    let nonce = siwe_nonce(&client).await;
+    let message = siwe_message(&wallet, "localhost:5000", &nonce);
+
+    let res = siwe_login(&client, &wallet, message).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let json_response = res
+        .json::<ApiResponse<LoginResponse, ApiErrorResponse>>()
+        .await;
+
+    insta::assert_yaml_snapshot!(json_response, {
+        ".**.jwt" => "[jwt]",
+        ".**.refresh_token" => "[refresh_token]"
+    });
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_siwe_nonce_is_reused() {
+    let (client, app_state, db_url) = setup().await;
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    create_account_with_eoa(
+        &app_state.database,
+        "someone@example.com".to_string(),
+        &wallet,
+    )
+    .await;
+    let nonce = siwe_nonce(&client).await;
+    let message = siwe_message(&wallet, "localhost:5000", &nonce);
+
+    let res = siwe_login(&client, &wallet, message.clone()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = siwe_login(&client, &wallet, message).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    match res
+        .json::<ApiResponse<LoginResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(_) => panic!("nonce was accepted twice"),
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            assert_eq!(error_message, "Invalid signature or address")
+        }
+    }
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_siwe_domain_is_invalid() {
+    let (client, app_state, db_url) = setup().await;
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    create_account_with_eoa(
+        &app_state.database,
+        "someone@example.com".to_string(),
+        &wallet,
+    )
+    .await;
+    let nonce = siwe_nonce(&client).await;
+    let message = siwe_message(&wallet, "evil.example.com", &nonce);
+
+    let res = siwe_login(&client, &wallet, message).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_siwe_address_has_no_account() {
+    let (client, app_state, db_url) = setup().await;
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let nonce = siwe_nonce(&client).await;
+    let message = siwe_message(&wallet, "localhost:5000", &nonce);
+
+    let res = siwe_login(&client, &wallet, message.clone()).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    // the nonce is used up and the attempt recorded against the address
+    create_account_with_eoa(
+        &app_state.database,
+        "someone@example.com".to_string(),
+        &wallet,
+    )
+    .await;
+    let res = siwe_login(&client, &wallet, message).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    let attempts = login_attempt_repo::find_all_by_email(
+        &app_state.database,
+        convert_to_hex(wallet.address()),
+    )
+    .await
+    .unwrap();
+    assert_eq!(attempts.len(), 1);
+    assert!(!attempts[0].success);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_siwe_signed_by_another_wallet() {
+    let (client, app_state, db_url) = setup().await;
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let other_wallet = LocalWallet::new(&mut rand::thread_rng());
+    create_account_with_eoa(
+        &app_state.database,
+        "someone@example.com".to_string(),
+        &wallet,
+    )
+    .await;
+    let nonce = siwe_nonce(&client).await;
+    let message = siwe_message(&wallet, "localhost:5000", &nonce);
+
+    let res = siwe_login(&client, &other_wallet, message).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn login(client: &TestClient, email: String, code: String) -> TestResponse {
+    client
+        .post("/auth/login")
+        .body(format!("{{\"email\":\"{}\",\"code\":\"{}\"}}", email, code))
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+}
+
+async fn create_account(db: &DatabaseConnection, email: String) {
+    account_repo::create(
+        db,
+        Uuid::new_v4(),
+        email,
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating account");
+}
+
+async fn create_code(db: &DatabaseConnection, email: String, code: &str, expires_in: i64) {
+    verification_repo::create(
+        db,
+        Uuid::new_v4(),
+        &email,
+        code,
+        get_unix_timestamp_ms() + expires_in,
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating verification");
+}
+
+async fn refresh(client: &TestClient, refresh_token: String) -> TestResponse {
+    client
+        .post("/auth/refresh")
+        .body(format!("{{\"refresh_token\":\"{}\"}}", refresh_token))
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+}
+
+async fn login_tokens(
+    client: &TestClient,
+    db: &DatabaseConnection,
+    email: String,
+    code: &str,
+) -> LoginResponse {
+    create_code(db, email.clone(), code, 60 * 1000).await;
+    let res = login(client, email, code.to_string()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    match res
+        .json::<ApiResponse<LoginResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(tokens) => tokens,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+async fn create_account_with_eoa(db: &DatabaseConnection, email: String, wallet: &LocalWallet) {
+    account_repo::create(
+        db,
+        Uuid::new_v4(),
+        email,
+        "".to_string(),
+        convert_to_hex(wallet.address()),
+        "".to_string(),
+        "".to_string(),
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating account");
+}
+
+async fn siwe_nonce(client: &TestClient) -> String {
+    let res = client.get("/auth/siwe/nonce").send().await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    match res
+        .json::<ApiResponse<SiweNonceResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(SiweNonceResponse { nonce }) => nonce,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+fn siwe_message(wallet: &LocalWallet, domain: &str, nonce: &str) -> String {
+    format!(
+        "{} wants you to sign in with your Ethereum account:\n\
+         {}\n\n\
+         Sign in to Clutch\n\n\
+         URI: http://{}\n\
+         Version: 1\n\
+         Chain ID: 80001\n\
+         Nonce: {}\n\
+         Issued At: {}",
+        domain,
+        ethers::utils::to_checksum(&wallet.address(), None),
+        domain,
+        nonce,
+        chrono::Utc::now().to_rfc3339(),
+    )
+}
+
+async fn siwe_login(client: &TestClient, wallet: &LocalWallet, message: String) -> TestResponse {
+    let signature = wallet.sign_message(&message).await.unwrap();
+    let body = serde_json::json!({
+        "message": message,
+        "signature": format!("0x{}", signature),
+    });
+
+    client
+        .post("/auth/siwe")
+        .body(body.to_string())
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+}
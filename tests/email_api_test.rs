@@ -1,6 +1,7 @@
 use axum::http::StatusCode;
 use axum_test_helper::{TestClient, TestResponse};
 use lib::test::utils::setup;
+use lib::test::utils::setup_with_fakes;
 use lib::test::utils::tear_down;
 
 #[tokio::test]
@@ -19,6 +20,28 @@ async fn test_verify_email() {
     tear_down(db_url).await;
 }
 
+#[tokio::test]
+async fn test_verify_email_sends_code() {
+    let (client, _app_state, db_url, fakes) = setup_with_fakes().await;
+
+    let res = client
+        .post("/email/verify")
+        .body("{\"email\":\"a@me.com\"}")
+        .header("Content-Type", "application/json")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let code = fakes
+        .mailer
+        .last_code_for("a@me.com")
+        .expect("no code sent");
+    assert_eq!(code.len(), 6);
+    assert_eq!(fakes.mailer.sent_count(), 1);
+
+    tear_down(db_url).await;
+}
+
 #[tokio::test]
 async fn test_invalid_email_format() {
     let (client, _app_state, db_url) = setup().await;
@@ -29,7 +52,7 @@ async fn test_invalid_email_format() {
         .header("Content-Type", "application/json")
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
@@ -53,8 +76,36 @@ async fn test_error_when_exceeds_email_send_limit() {
     }
 
     let res = verify_email(&client).await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
 }
+
+#[tokio::test]
+async fn test_email_send_limit_resets_after_an_hour() {
+    let (client, _app_state, db_url, fakes) = setup_with_fakes().await;
+
+    async fn verify_email(client: &TestClient) -> TestResponse {
+        client
+            .post("/email/verify")
+            .body("{\"email\":\"exceeds@example.com\"}")
+            .header("Content-Type", "application/json")
+            .send()
+            .await
+    }
+
+    for _ in 0..5 {
+        assert_eq!(verify_email(&client).await.status(), StatusCode::OK);
+    }
+    assert_eq!(
+        verify_email(&client).await.status(),
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    // only the codes sent within the last hour count towards the limit
+    fakes.clock.advance(60 * 60 * 1000);
+    assert_eq!(verify_email(&client).await.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
@@ -1,12 +1,15 @@
+use axum_test_helper::{TestClient, TestResponse};
+use ethers::signers::{LocalWallet, Signer};
 use hyper::{header::AUTHORIZATION, StatusCode};
 use lib::{
     models::api::{
-        ApiErrorResponse, ApiResponse, ListGuardianAccountsResponse, ListNominationsResponse,
-        NominationUpdateResponse,
+        ApiErrorResponse, ApiPayload, ApiResponse, GuardianChallengeResponse,
+        ListGuardianAccountsResponse, ListNominationsResponse, NominationUpdateResponse,
     },
     operations::{jwt::decode_jwt, time::get_unix_timestamp_ms},
-    repos::{account_repo, guardian_account_repo, guardian_repo, nomination_repo},
+    repos::{account_repo, db::AppState, guardian_account_repo, guardian_repo, nomination_repo},
     test::utils::{create_verified_account_jwt, setup, tear_down},
+    utils::convert_to_hex,
 };
 use uuid::Uuid;
 
@@ -38,6 +41,7 @@ async fn test_list_all_nominations_for_user_when_user_is_also_a_guardian() {
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -91,6 +95,7 @@ async fn test_list_filter_nominations_by_status_for_user_when_user_is_also_a_gua
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -102,6 +107,7 @@ async fn test_list_filter_nominations_by_status_for_user_when_user_is_also_a_gua
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -157,6 +163,7 @@ async fn test_list_filter_nominations_by_id_for_user_when_user_is_also_a_guardia
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -168,6 +175,7 @@ async fn test_list_filter_nominations_by_id_for_user_when_user_is_also_a_guardia
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -224,17 +232,21 @@ async fn test_successfully_accept_nomination_as_a_guardian() {
         nominators_account_id.to_string(),
         guardian_id.to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
 
-    let res = client
-        .put(format!("/guardian/nomination/{}", nomination_id).as_str())
-        .body("{\"status\":\"accepted\"}")
-        .header("Content-Type", "application/json")
-        .header(AUTHORIZATION, format!("Bearer {}", jwt))
-        .send()
-        .await;
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let challenge = create_challenge(&client, &jwt, &nomination_id.to_string()).await;
+    let res = accept_nomination(
+        &client,
+        &jwt,
+        &nomination_id.to_string(),
+        &wallet,
+        &challenge,
+    )
+    .await;
     assert_eq!(res.status(), StatusCode::OK);
 
     let json_response = res
@@ -245,6 +257,15 @@ async fn test_successfully_accept_nomination_as_a_guardian() {
         ".**.nomination_id" => "[uuid]",
     });
 
+    let guardian = guardian_repo::find_by_id(&app_state.database, guardian_id.to_string())
+        .await
+        .unwrap()
+        .expect("guardian not found");
+    assert_eq!(
+        guardian.verified_address,
+        Some(convert_to_hex(wallet.address()))
+    );
+
     let guardian_accounts = guardian_account_repo::find_all_guardians_by_account_id(
         &app_state.database,
         nominators_account_id.to_string(),
@@ -288,6 +309,7 @@ async fn test_successfully_reject_nomination_as_a_guardian() {
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -342,6 +364,7 @@ async fn test_error_when_try_to_accept_a_rejected_nomination_as_a_guardian() {
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "REJECTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -353,7 +376,7 @@ async fn test_error_when_try_to_accept_a_rejected_nomination_as_a_guardian() {
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::CONFLICT);
 
     let json_response = res
         .json::<ApiResponse<NominationUpdateResponse, ApiErrorResponse>>()
@@ -396,6 +419,7 @@ async fn test_error_when_try_to_reject_an_accepted_nomination_as_a_guardian() {
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -407,7 +431,7 @@ async fn test_error_when_try_to_reject_an_accepted_nomination_as_a_guardian() {
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::CONFLICT);
 
     let json_response = res
         .json::<ApiResponse<NominationUpdateResponse, ApiErrorResponse>>()
@@ -450,6 +474,7 @@ async fn test_error_when_invalid_status_on_update_nomination_as_a_guardian() {
         Uuid::new_v4().to_string(),
         guardian_id.to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -461,7 +486,7 @@ async fn test_error_when_invalid_status_on_update_nomination_as_a_guardian() {
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 
     let json_response = res
         .json::<ApiResponse<NominationUpdateResponse, ApiErrorResponse>>()
@@ -504,6 +529,8 @@ async fn test_as_a_guardian_list_accounts_im_guardian_for() {
         some_user_email.clone(),
         some_user_wallet_address.clone(),
         some_user_eoa_address.clone(),
+        "".to_string(),
+        "".to_string(),
         get_unix_timestamp_ms(),
     )
     .await
@@ -518,6 +545,7 @@ async fn test_as_a_guardian_list_accounts_im_guardian_for() {
         some_user_account_id.clone().to_string(),
         guardian_id.to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -581,6 +609,8 @@ async fn test_as_a_guardian_list_accounts_im_guardian_for_filtered_by_account_id
         some_user_email.clone(),
         some_user_wallet_address.clone(),
         some_user_eoa_address.clone(),
+        "".to_string(),
+        "".to_string(),
         get_unix_timestamp_ms(),
     )
     .await
@@ -595,6 +625,7 @@ async fn test_as_a_guardian_list_accounts_im_guardian_for_filtered_by_account_id
         some_user_account_id.clone().to_string(),
         guardian_id.to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -627,3 +658,290 @@ async fn test_as_a_guardian_list_accounts_im_guardian_for_filtered_by_account_id
 
     tear_down(db_url).await;
 }
+
+#[tokio::test]
+async fn test_error_when_accepting_nomination_without_proof_of_ownership() {
+    let (client, app_state, db_url) = setup().await;
+
+    let (jwt, nomination_id) = create_pending_nomination(&app_state, &client).await;
+
+    let res = client
+        .put(format!("/guardian/nomination/{}", nomination_id).as_str())
+        .body("{\"status\":\"accepted\"}")
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_challenge_is_signed_by_another_wallet() {
+    let (client, app_state, db_url) = setup().await;
+
+    let (jwt, nomination_id) = create_pending_nomination(&app_state, &client).await;
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let someone_else = LocalWallet::new(&mut rand::thread_rng());
+    let challenge = create_challenge(&client, &jwt, &nomination_id).await;
+    let signature = someone_else.sign_message(&challenge.message).await.unwrap();
+    let res = client
+        .put(format!("/guardian/nomination/{}", nomination_id).as_str())
+        .body(format!(
+            "{{\"status\":\"accepted\",\"address\":\"{}\",\"nonce\":\"{}\",\"signature\":\"0x{}\"}}",
+            convert_to_hex(wallet.address()),
+            challenge.nonce,
+            signature
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    // the challenge isn't used up by the failed attempt
+    let res = accept_nomination(&client, &jwt, &nomination_id, &wallet, &challenge).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_challenge_is_used_twice() {
+    let (client, app_state, db_url) = setup().await;
+
+    let (jwt, nomination_id) = create_pending_nomination(&app_state, &client).await;
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let challenge = create_challenge(&client, &jwt, &nomination_id).await;
+    let res = accept_nomination(&client, &jwt, &nomination_id, &wallet, &challenge).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let res = accept_nomination(&client, &jwt, &nomination_id, &wallet, &challenge).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert!(res
+        .text()
+        .await
+        .contains(&format!("Invalid or expired challenge {}", challenge.nonce)));
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_accepting_with_another_wallet_than_the_verified_one() {
+    let (client, app_state, db_url) = setup().await;
+
+    let (jwt, nomination_id) = create_pending_nomination(&app_state, &client).await;
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let challenge = create_challenge(&client, &jwt, &nomination_id).await;
+    let res = accept_nomination(&client, &jwt, &nomination_id, &wallet, &challenge).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    // the same guardian nominated by another account
+    let nomination = find_nomination(&app_state, &nomination_id).await;
+    let other_nomination_id = Uuid::new_v4();
+    nomination_repo::create(
+        &app_state.database,
+        other_nomination_id,
+        nomination.email.clone(),
+        Uuid::new_v4().to_string(),
+        nomination.guardian_id.clone(),
+        "PENDING".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+    let other_nomination_id = other_nomination_id.to_string();
+
+    let other_wallet = LocalWallet::new(&mut rand::thread_rng());
+    let challenge = create_challenge(&client, &jwt, &other_nomination_id).await;
+    let res = accept_nomination(
+        &client,
+        &jwt,
+        &other_nomination_id,
+        &other_wallet,
+        &challenge,
+    )
+    .await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    insta::assert_yaml_snapshot!(res.text().await);
+    assert_eq!(
+        find_nomination(&app_state, &other_nomination_id)
+            .await
+            .status,
+        "PENDING"
+    );
+
+    let challenge = create_challenge(&client, &jwt, &other_nomination_id).await;
+    let res = accept_nomination(&client, &jwt, &other_nomination_id, &wallet, &challenge).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let guardian = guardian_repo::find_by_id(&app_state.database, nomination.guardian_id)
+        .await
+        .unwrap()
+        .expect("guardian not found");
+    assert_eq!(
+        guardian.verified_address,
+        Some(convert_to_hex(wallet.address()))
+    );
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_reject_nomination_removes_guardian_from_account() {
+    let (client, app_state, db_url) = setup().await;
+
+    let (jwt, nomination_id) = create_pending_nomination(&app_state, &client).await;
+    let nomination = find_nomination(&app_state, &nomination_id).await;
+    guardian_account_repo::create(
+        &app_state.database,
+        Uuid::new_v4(),
+        nomination.guardian_id.clone(),
+        nomination.account_id.clone(),
+        "AVAILABLE".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let res = client
+        .put(format!("/guardian/nomination/{}", nomination_id).as_str())
+        .body("{\"status\":\"rejected\"}")
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let guardian_accounts = guardian_account_repo::find_all_guardians_by_account_id(
+        &app_state.database,
+        nomination.account_id,
+    )
+    .await
+    .unwrap();
+    assert!(guardian_accounts.is_empty());
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_accept_nomination_keeps_an_existing_guardian_link() {
+    let (client, app_state, db_url) = setup().await;
+
+    let (jwt, nomination_id) = create_pending_nomination(&app_state, &client).await;
+    let nomination = find_nomination(&app_state, &nomination_id).await;
+    guardian_account_repo::create(
+        &app_state.database,
+        Uuid::new_v4(),
+        nomination.guardian_id.clone(),
+        nomination.account_id.clone(),
+        "ACTIVE".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let challenge = create_challenge(&client, &jwt, &nomination_id).await;
+    let res = accept_nomination(&client, &jwt, &nomination_id, &wallet, &challenge).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let guardian_accounts = guardian_account_repo::find_all_guardians_by_account_id(
+        &app_state.database,
+        nomination.account_id,
+    )
+    .await
+    .unwrap();
+    assert_eq!(guardian_accounts.len(), 1);
+    assert_eq!(guardian_accounts[0].status, "ACTIVE");
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+// a guardian with their own account, nominated by some other account
+async fn create_pending_nomination(app_state: &AppState, client: &TestClient) -> (String, String) {
+    let guardian_email = "guardian@example.com".to_string();
+    let jwt =
+        create_verified_account_jwt(&app_state.database, client, guardian_email.clone()).await;
+    let account_id = decode_jwt(jwt.clone()).await.unwrap().sub;
+
+    let guardian_id = Uuid::new_v4();
+    guardian_repo::create(
+        &app_state.database,
+        guardian_id,
+        guardian_email.clone(),
+        Some(account_id),
+        None,
+    )
+    .await
+    .unwrap();
+
+    let nomination_id = Uuid::new_v4();
+    nomination_repo::create(
+        &app_state.database,
+        nomination_id,
+        guardian_email,
+        Uuid::new_v4().to_string(),
+        guardian_id.to_string(),
+        "PENDING".to_string(),
+        None,
+    )
+    .await
+    .unwrap();
+    (jwt, nomination_id.to_string())
+}
+
+async fn create_challenge(
+    client: &TestClient,
+    jwt: &str,
+    nomination_id: &str,
+) -> GuardianChallengeResponse {
+    let res = client
+        .post(format!("/guardian/nomination/{}/challenge", nomination_id).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    match res
+        .json::<ApiResponse<GuardianChallengeResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(challenge) => challenge,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+async fn accept_nomination(
+    client: &TestClient,
+    jwt: &str,
+    nomination_id: &str,
+    wallet: &LocalWallet,
+    challenge: &GuardianChallengeResponse,
+) -> TestResponse {
+    let signature = wallet.sign_message(&challenge.message).await.unwrap();
+    client
+        .put(format!("/guardian/nomination/{}", nomination_id).as_str())
+        .body(format!(
+            "{{\"status\":\"accepted\",\"address\":\"{}\",\"nonce\":\"{}\",\"signature\":\"0x{}\"}}",
+            convert_to_hex(wallet.address()),
+            challenge.nonce,
+            signature
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+async fn find_nomination(app_state: &AppState, nomination_id: &str) -> nomination_repo::Model {
+    nomination_repo::find_by_id(&app_state.database, nomination_id.to_string())
+        .await
+        .unwrap()
+        .expect("nomination not found")
+}
@@ -1,7 +1,7 @@
 use hyper::{header::AUTHORIZATION, StatusCode};
 use lib::{
     models::api::{
-        AccountGuardianSettingsResponse, ApiErrorResponse, ApiResponse, SigningStrategy,
+        AccountGuardianSettingsResponse, ApiErrorResponse, ApiPayload, ApiResponse, SigningStrategy,
     },
     operations::jwt::decode_jwt,
     repos::{guardian_account_repo, guardian_repo, guardian_settings_repo},
@@ -9,6 +9,8 @@ use lib::{
 };
 use uuid::Uuid;
 
+const GUARDIAN_WALLET: &str = "0x4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0c1d2e3f";
+
 #[tokio::test]
 async fn test_can_retrieve_guardian_settings_for_an_account() {
     let (client, app_state, db_url) = setup().await;
@@ -43,7 +45,7 @@ async fn test_can_retrieve_guardian_settings_for_an_account() {
     guardian_settings_repo::create(
         &app_state.database,
         Uuid::new_v4(),
-        SigningStrategy::OneOfOne,
+        SigningStrategy::new(1, 1),
         account_id.to_string(),
     )
     .await
@@ -90,6 +92,15 @@ async fn test_can_successfully_update_guardian_settings() {
     .await
     .unwrap();
 
+    guardian_repo::update_verified_address(
+        &app_state.database,
+        guardian_id.to_string(),
+        GUARDIAN_WALLET.to_string(),
+        0,
+    )
+    .await
+    .unwrap();
+
     let guardian_account_id = Uuid::new_v4();
     guardian_account_repo::create(
         &app_state.database,
@@ -104,7 +115,7 @@ async fn test_can_successfully_update_guardian_settings() {
     guardian_settings_repo::create(
         &app_state.database,
         Uuid::new_v4(),
-        SigningStrategy::OneOfOne,
+        SigningStrategy::new(1, 1),
         account_id.to_string(),
     )
     .await
@@ -113,8 +124,7 @@ async fn test_can_successfully_update_guardian_settings() {
     let res = client
         .put("/accounts/guardians/settings")
         .body(format!(
-            "{{\"signers\":\"{}\",\"guardians\":[\"{}\"]}}",
-            "OneOfOne".to_string(),
+            "{{\"signers\":{{\"threshold\":1,\"total\":1}},\"guardians\":[\"{}\"]}}",
             guardian_account_id.to_string()
         ))
         .header("Content-Type", "application/json")
@@ -171,7 +181,7 @@ async fn test_error_when_invalid_amount_of_guardians_for_proposed_signing_strate
     guardian_settings_repo::create(
         &app_state.database,
         Uuid::new_v4(),
-        SigningStrategy::OneOfOne,
+        SigningStrategy::new(1, 1),
         account_id.to_string(),
     )
     .await
@@ -180,15 +190,14 @@ async fn test_error_when_invalid_amount_of_guardians_for_proposed_signing_strate
     let res = client
         .put("/accounts/guardians/settings")
         .body(format!(
-            "{{\"signers\":\"{}\",\"guardians\":[\"{}\"]}}",
-            "OneOfTwo".to_string(),
+            "{{\"signers\":{{\"threshold\":1,\"total\":2}},\"guardians\":[\"{}\"]}}",
             guardian_account_id.to_string()
         ))
         .header("Content-Type", "application/json")
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 
     let json_response = res
         .json::<ApiResponse<AccountGuardianSettingsResponse, ApiErrorResponse>>()
@@ -237,7 +246,7 @@ async fn test_error_when_invalid_guardians_for_on_update_guardian_settings() {
     guardian_settings_repo::create(
         &app_state.database,
         Uuid::new_v4(),
-        SigningStrategy::OneOfOne,
+        SigningStrategy::new(1, 1),
         account_id.to_string(),
     )
     .await
@@ -246,15 +255,14 @@ async fn test_error_when_invalid_guardians_for_on_update_guardian_settings() {
     let res = client
         .put("/accounts/guardians/settings")
         .body(format!(
-            "{{\"signers\":\"{}\",\"guardians\":[\"{}\"]}}",
-            "OneOfOne".to_string(),
+            "{{\"signers\":{{\"threshold\":1,\"total\":1}},\"guardians\":[\"{}\"]}}",
             "6ac5790f-148d-46be-a657-0b06ad41d135".to_string()
         ))
         .header("Content-Type", "application/json")
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
 
     let json_response = res
         .json::<ApiResponse<AccountGuardianSettingsResponse, ApiErrorResponse>>()
@@ -266,3 +274,191 @@ async fn test_error_when_invalid_guardians_for_on_update_guardian_settings() {
 
     tear_down(db_url).await;
 }
+
+#[tokio::test]
+async fn test_can_update_guardian_settings_to_three_of_five() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt =
+        create_verified_account_jwt(&app_state.database, &client, "user@example.com".to_string())
+            .await;
+    let account_id = decode_jwt(jwt.clone()).await.unwrap().sub;
+
+    let mut guardian_account_ids = vec![];
+    for i in 0..5 {
+        let guardian_id = Uuid::new_v4();
+        guardian_repo::create(
+            &app_state.database,
+            guardian_id,
+            format!("guardian{}@example.com", i),
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+
+        guardian_repo::update_verified_address(
+            &app_state.database,
+            guardian_id.to_string(),
+            format!("0x{:040x}", i + 1),
+            0,
+        )
+        .await
+        .unwrap();
+
+        let guardian_account_id = Uuid::new_v4();
+        guardian_account_repo::create(
+            &app_state.database,
+            guardian_account_id,
+            guardian_id.to_string(),
+            account_id.to_string(),
+            "AVAILABLE".to_string(),
+        )
+        .await
+        .unwrap();
+        guardian_account_ids.push(guardian_account_id.to_string());
+    }
+
+    guardian_settings_repo::create(
+        &app_state.database,
+        Uuid::new_v4(),
+        SigningStrategy::new(1, 1),
+        account_id.to_string(),
+    )
+    .await
+    .unwrap();
+
+    let res = client
+        .put("/accounts/guardians/settings")
+        .body(format!(
+            "{{\"signers\":{{\"threshold\":3,\"total\":5}},\"guardians\":{}}}",
+            serde_json::to_string(&guardian_account_ids).unwrap()
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let json_response = res
+        .json::<ApiResponse<AccountGuardianSettingsResponse, ApiErrorResponse>>()
+        .await;
+    match json_response.payload {
+        ApiPayload::Success(settings) => {
+            assert_eq!(settings.signers, SigningStrategy::new(3, 5));
+            assert_eq!(settings.active_guardians.len(), 5);
+            assert_eq!(settings.signing_strategies.len(), 15);
+        }
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+
+    let settings = guardian_settings_repo::find_for_account_id(&app_state.database, account_id)
+        .await
+        .unwrap()
+        .expect("settings not found");
+    assert_eq!(settings.signers(), SigningStrategy::new(3, 5));
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_threshold_exceeds_total_on_update_guardian_settings() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt =
+        create_verified_account_jwt(&app_state.database, &client, "user@example.com".to_string())
+            .await;
+    let account_id = decode_jwt(jwt.clone()).await.unwrap().sub;
+
+    let guardian_id = Uuid::new_v4();
+    guardian_repo::create(
+        &app_state.database,
+        guardian_id,
+        "guardian@example.com".to_string(),
+        None,
+        None,
+    )
+    .await
+    .unwrap();
+
+    let guardian_account_id = Uuid::new_v4();
+    guardian_account_repo::create(
+        &app_state.database,
+        guardian_account_id,
+        guardian_id.to_string(),
+        account_id.to_string(),
+        "ACTIVE".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let res = client
+        .put("/accounts/guardians/settings")
+        .body(format!(
+            "{{\"signers\":{{\"threshold\":2,\"total\":1}},\"guardians\":[\"{}\"]}}",
+            guardian_account_id
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert!(res
+        .text()
+        .await
+        .contains("Invalid signing strategy 2-of-1, the threshold must be between 1 and 1"));
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_guardian_is_not_verified_on_update_guardian_settings() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt =
+        create_verified_account_jwt(&app_state.database, &client, "user@example.com".to_string())
+            .await;
+    let account_id = decode_jwt(jwt.clone()).await.unwrap().sub;
+
+    let guardian_id = Uuid::new_v4();
+    guardian_repo::create(
+        &app_state.database,
+        guardian_id,
+        "guardian@example.com".to_string(),
+        None,
+        Some(GUARDIAN_WALLET.to_string()),
+    )
+    .await
+    .unwrap();
+
+    let guardian_account_id = Uuid::new_v4();
+    guardian_account_repo::create(
+        &app_state.database,
+        guardian_account_id,
+        guardian_id.to_string(),
+        account_id.to_string(),
+        "AVAILABLE".to_string(),
+    )
+    .await
+    .unwrap();
+
+    let res = client
+        .put("/accounts/guardians/settings")
+        .body(format!(
+            "{{\"signers\":{{\"threshold\":1,\"total\":1}},\"guardians\":[\"{}\"]}}",
+            guardian_account_id
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert!(res.text().await.contains(&format!(
+        "Guardians haven't proven ownership of their wallet: [\\\"{}\\\"]",
+        guardian_account_id
+    )));
+
+    tear_down(db_url).await;
+}
@@ -0,0 +1,248 @@
+use axum_test_helper::{TestClient, TestResponse};
+use ethers::signers::{LocalWallet, Signer};
+use hyper::{header::AUTHORIZATION, StatusCode};
+use lib::{
+    jobs::nomination_reminders::send_reminders,
+    models::api::{
+        ApiErrorResponse, ApiPayload, ApiResponse, GuardianChallengeResponse,
+        NominationCreateResponse, NominationUpdateResponse,
+    },
+    operations::time::Clock,
+    repos::{guardian_account_repo, guardian_repo, nomination_repo},
+    test::utils::{create_verified_account_jwt, setup_with_fakes, tear_down, Fakes},
+    utils::convert_to_hex,
+};
+
+const EMAIL: &str = "someone@example.com";
+const GUARDIAN_EMAIL: &str = "guardian@example.com";
+const TTL_MS: i64 = 604800 * 1000;
+const REMINDER_MS: i64 = 259200 * 1000;
+
+#[tokio::test]
+async fn test_accept_nomination_with_invitation_link() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let nomination_id = nominate(&client, &jwt, GUARDIAN_EMAIL).await;
+    assert_eq!(fakes.mailer.invitation_count(), 1);
+    let token = invitation_token(&fakes);
+
+    let res = client
+        .post("/invitations/challenge")
+        .body(format!("{{\"token\":\"{}\"}}", token))
+        .header("Content-Type", "application/json")
+        .send()
+        .await;
+    let challenge = match res
+        .json::<ApiResponse<GuardianChallengeResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(challenge) => challenge,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    };
+
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let signature = wallet.sign_message(&challenge.message).await.unwrap();
+    let body = format!(
+        "{{\"token\":\"{}\",\"status\":\"accepted\",\"address\":\"{}\",\"nonce\":\"{}\",\"signature\":\"0x{}\"}}",
+        token,
+        convert_to_hex(wallet.address()),
+        challenge.nonce,
+        signature
+    );
+    let res = update_invitation(&client, &body).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let json_response = res
+        .json::<ApiResponse<NominationUpdateResponse, ApiErrorResponse>>()
+        .await;
+    match json_response.payload {
+        ApiPayload::Success(update) => {
+            assert_eq!(update.nomination_id, nomination_id);
+            assert_eq!(update.status, "ACCEPTED");
+        }
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+
+    let nomination = nomination_repo::find_by_id(db, nomination_id.clone())
+        .await
+        .unwrap()
+        .expect("nomination not found");
+    let guardian = guardian_repo::find_by_id(db, nomination.guardian_id.clone())
+        .await
+        .unwrap()
+        .expect("guardian not found");
+    assert_eq!(guardian.account_id, None);
+    assert_eq!(
+        guardian.verified_address,
+        Some(convert_to_hex(wallet.address()))
+    );
+    let guardian_accounts =
+        guardian_account_repo::find_all_guardians_by_account_id(db, nomination.account_id)
+            .await
+            .unwrap();
+    assert_eq!(guardian_accounts.len(), 1);
+    assert_eq!(guardian_accounts[0].status, "AVAILABLE");
+
+    // the link works once
+    let res = update_invitation(&client, &body).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_reject_nomination_with_invitation_link() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let nomination_id = nominate(&client, &jwt, GUARDIAN_EMAIL).await;
+
+    let body = format!(
+        "{{\"token\":\"{}\",\"status\":\"rejected\"}}",
+        invitation_token(&fakes)
+    );
+    let res = update_invitation(&client, &body).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let nomination = nomination_repo::find_by_id(db, nomination_id)
+        .await
+        .unwrap()
+        .expect("nomination not found");
+    assert_eq!(nomination.status, "REJECTED");
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_invitation_expired() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    nominate(&client, &jwt, GUARDIAN_EMAIL).await;
+
+    fakes.clock.advance(TTL_MS);
+    let body = format!(
+        "{{\"token\":\"{}\",\"status\":\"rejected\"}}",
+        invitation_token(&fakes)
+    );
+    let res = update_invitation(&client, &body).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_invitation_token_is_invalid() {
+    let (client, _app_state, db_url, _fakes) = setup_with_fakes().await;
+
+    let res = update_invitation(&client, "{\"token\":\"invalid\",\"status\":\"rejected\"}").await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_resend_invitation_within_send_limit() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let nomination_id = nominate(&client, &jwt, GUARDIAN_EMAIL).await;
+
+    // the send limit of 4 allows a fifth email to the address within the hour
+    for _ in 0..4 {
+        let res = resend(&client, &jwt, &nomination_id).await;
+        assert_eq!(res.status(), StatusCode::OK);
+    }
+    let res = resend(&client, &jwt, &nomination_id).await;
+    assert_eq!(res.status(), StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(fakes.mailer.invitation_count(), 5);
+
+    fakes.clock.advance(60 * 60 * 1000);
+    let res = resend(&client, &jwt, &nomination_id).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_remind_guardian_of_pending_nomination() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    nominate(&client, &jwt, GUARDIAN_EMAIL).await;
+
+    send_reminders(&app_state, fakes.clock.now_ms())
+        .await
+        .unwrap();
+    assert_eq!(fakes.mailer.reminder_count(), 0);
+
+    fakes.clock.advance(REMINDER_MS);
+    send_reminders(&app_state, fakes.clock.now_ms())
+        .await
+        .unwrap();
+    assert_eq!(fakes.mailer.reminder_count(), 1);
+
+    send_reminders(&app_state, fakes.clock.now_ms())
+        .await
+        .unwrap();
+    assert_eq!(fakes.mailer.reminder_count(), 1);
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn nominate(client: &TestClient, jwt: &str, email: &str) -> String {
+    let res = client
+        .post("/accounts/nominations")
+        .body(format!("{{\"email\":\"{}\"}}", email))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    match res
+        .json::<ApiResponse<NominationCreateResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(NominationCreateResponse { nomination_id }) => nomination_id,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+fn invitation_token(fakes: &Fakes) -> String {
+    fakes
+        .mailer
+        .last_invitation_token_for(GUARDIAN_EMAIL)
+        .expect("no invitation sent")
+}
+
+async fn update_invitation(client: &TestClient, body: &str) -> TestResponse {
+    client
+        .post("/invitations")
+        .body(body.to_string())
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+}
+
+async fn resend(client: &TestClient, jwt: &str, nomination_id: &str) -> TestResponse {
+    client
+        .post(format!("/accounts/nominations/{}/resend", nomination_id).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
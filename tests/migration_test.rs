@@ -0,0 +1,62 @@
+use lib::{
+    operations::encryption::decrypt_private_key,
+    repos::{account_repo, migration::encrypt_private_keys},
+    test::utils::{setup, tear_down},
+};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+const PRIVATE_KEY: &str = "4c0883a69102937d6231471b5dbb6204fe5129617082792ae468d01a3f362318";
+
+#[tokio::test]
+async fn test_encrypt_plaintext_private_keys_and_skip_the_others() {
+    let (_client, app_state, db_url) = setup().await;
+    let db = &app_state.database;
+    let master_key = app_state.settings.wallet_master_key().unwrap();
+
+    create_account(db, "plain@example.com", PRIVATE_KEY).await;
+    create_account(db, "garbled@example.com", "not a key").await;
+
+    encrypt_private_keys(db, &master_key).await.unwrap();
+
+    let plain = find_account(db, "plain@example.com").await;
+    assert!(!plain.eoa_private_data_key.is_empty());
+    assert_eq!(
+        decrypt_private_key(
+            &master_key,
+            &plain.eoa_private_data_key,
+            &plain.eoa_private_address
+        )
+        .unwrap(),
+        PRIVATE_KEY
+    );
+    let garbled = find_account(db, "garbled@example.com").await;
+    assert_eq!(garbled.eoa_private_address, "not a key");
+    assert!(garbled.eoa_private_data_key.is_empty());
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn create_account(db: &DatabaseConnection, email: &str, private_key: &str) {
+    account_repo::create(
+        db,
+        Uuid::new_v4(),
+        email.to_string(),
+        "0x1".to_string(),
+        "0x2".to_string(),
+        private_key.to_string(),
+        "".to_string(),
+        0,
+    )
+    .await
+    .expect("error creating account");
+}
+
+async fn find_account(db: &DatabaseConnection, email: &str) -> account_repo::Model {
+    account_repo::find_by_email(db, email)
+        .await
+        .unwrap()
+        .expect("account not found")
+}
@@ -4,8 +4,9 @@ use lib::{
         ApiErrorResponse, ApiPayload, ApiResponse, ListNominationsResponse,
         NominationCreateResponse, NominationDeleteResponse,
     },
-    operations::jwt::{decode_jwt, generate_jwt},
+    operations::jwt::decode_jwt,
     repos::{guardian_repo, nomination_repo},
+    routes::auth_api::create_session,
     test::utils::{create_verified_account_jwt, setup, tear_down},
 };
 use uuid::Uuid;
@@ -28,7 +29,7 @@ async fn test_invalid_email_format() {
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
@@ -45,7 +46,15 @@ async fn test_account_not_found() {
     )
     .await;
 
-    let jwt = generate_jwt("does_not_exist".to_string()).await.unwrap();
+    let (jwt, _) = create_session(
+        &app_state.database,
+        "does_not_exist".to_string(),
+        None,
+        None,
+        app_state.clock.now_ms(),
+    )
+    .await
+    .unwrap();
 
     let res = client
         .post("/accounts/nominations")
@@ -54,7 +63,7 @@ async fn test_account_not_found() {
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
     insta::assert_yaml_snapshot!(res.text().await);
 
     tear_down(db_url).await;
@@ -247,6 +256,7 @@ async fn test_find_all_nominations() {
         account_id.clone(),
         "guardian_id".to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -288,6 +298,7 @@ async fn test_find_all_by_id() {
         account_id.clone(),
         "guardian_id1".to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -298,6 +309,7 @@ async fn test_find_all_by_id() {
         account_id.clone(),
         "guardian_id2".to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -308,6 +320,7 @@ async fn test_find_all_by_id() {
         account_id.clone(),
         "guardian_id3".to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -349,6 +362,7 @@ async fn test_find_all_by_status() {
         account_id.clone(),
         "guardian_id1".to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -359,6 +373,7 @@ async fn test_find_all_by_status() {
         account_id.clone(),
         "guardian_id2".to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -369,6 +384,7 @@ async fn test_find_all_by_status() {
         account_id.clone(),
         "guardian_id3".to_string(),
         "REJECTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -410,6 +426,7 @@ async fn test_find_all_by_email() {
         account_id.clone(),
         "guardian_id1".to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -420,6 +437,7 @@ async fn test_find_all_by_email() {
         account_id.clone(),
         "guardian_id2".to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -430,6 +448,7 @@ async fn test_find_all_by_email() {
         account_id.clone(),
         "guardian_id3".to_string(),
         "REJECTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -471,6 +490,7 @@ async fn test_successfully_delete_nomination() {
         account_id.clone(),
         "guardian_id1".to_string(),
         "PENDING".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -512,6 +532,7 @@ async fn test_error_on_delete_nomination_if_status_is_accepted_or_rejected() {
         account_id.clone(),
         "guardian_id1".to_string(),
         "ACCEPTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -523,6 +544,7 @@ async fn test_error_on_delete_nomination_if_status_is_accepted_or_rejected() {
         account_id.clone(),
         "guardian_id2".to_string(),
         "REJECTED".to_string(),
+        None,
     )
     .await
     .unwrap();
@@ -533,7 +555,7 @@ async fn test_error_on_delete_nomination_if_status_is_accepted_or_rejected() {
         .header(AUTHORIZATION, format!("Bearer {}", jwt))
         .send()
         .await;
-    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(res.status(), StatusCode::CONFLICT);
 
     let json_response = res
         .json::<ApiResponse<NominationDeleteResponse, ApiErrorResponse>>()
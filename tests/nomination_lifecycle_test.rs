@@ -0,0 +1,226 @@
+use axum_test_helper::{TestClient, TestResponse};
+use hyper::{header::AUTHORIZATION, StatusCode};
+use lib::{
+    jobs::nomination_expiry::expire_nominations,
+    models::api::{ApiErrorResponse, ApiPayload, ApiResponse, NominationCreateResponse},
+    operations::time::Clock,
+    repos::{nomination_history_repo, nomination_repo},
+    test::utils::{create_verified_account_jwt, setup_with_fakes, tear_down},
+};
+use sea_orm::DatabaseConnection;
+
+const EMAIL: &str = "someone@example.com";
+const GUARDIAN_EMAIL: &str = "guardian@example.com";
+const NOMINATION_TTL_MS: i64 = 1209600 * 1000;
+
+#[tokio::test]
+async fn test_error_when_nominating_yourself() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let res = nominate(&client, &jwt, "Someone@Example.com").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_guardian_already_nominated() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    nomination_id_from(nominate(&client, &jwt, GUARDIAN_EMAIL).await).await;
+
+    let res = nominate(&client, &jwt, GUARDIAN_EMAIL).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    insta::assert_yaml_snapshot!(res.text().await);
+    assert_eq!(fakes.mailer.invitation_count(), 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_nominate_again_after_rejection() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let first = nomination_id_from(nominate(&client, &jwt, GUARDIAN_EMAIL).await).await;
+    let token = fakes
+        .mailer
+        .last_invitation_token_for(GUARDIAN_EMAIL)
+        .expect("no invitation sent");
+    let res = client
+        .post("/invitations")
+        .body(format!(
+            "{{\"token\":\"{}\",\"status\":\"rejected\"}}",
+            token
+        ))
+        .header("Content-Type", "application/json")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let second = nomination_id_from(nominate(&client, &jwt, GUARDIAN_EMAIL).await).await;
+    assert_ne!(first, second);
+    assert_eq!(find_nomination(db, &first).await.status, "REJECTED");
+    assert_eq!(find_nomination(db, &second).await.status, "PENDING");
+    assert_eq!(
+        transitions(db, &first).await,
+        vec![
+            (None, "PENDING".to_string()),
+            (pending(), "REJECTED".to_string())
+        ]
+    );
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_expire_pending_nominations() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let nomination_id = nomination_id_from(nominate(&client, &jwt, GUARDIAN_EMAIL).await).await;
+    let nomination = find_nomination(db, &nomination_id).await;
+    assert_eq!(
+        nomination.expires_at,
+        Some(fakes.clock.now_ms() + NOMINATION_TTL_MS)
+    );
+
+    fakes.clock.advance(NOMINATION_TTL_MS - 1);
+    expire_nominations(&app_state, fakes.clock.now_ms())
+        .await
+        .unwrap();
+    assert_eq!(find_nomination(db, &nomination_id).await.status, "PENDING");
+
+    fakes.clock.advance(1);
+    expire_nominations(&app_state, fakes.clock.now_ms())
+        .await
+        .unwrap();
+    assert_eq!(find_nomination(db, &nomination_id).await.status, "EXPIRED");
+    assert_eq!(
+        transitions(db, &nomination_id).await,
+        vec![
+            (None, "PENDING".to_string()),
+            (pending(), "EXPIRED".to_string())
+        ]
+    );
+
+    let res = resend(&client, &jwt, &nomination_id).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_nominate_again_after_expiry_before_it_is_swept() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let first = nomination_id_from(nominate(&client, &jwt, GUARDIAN_EMAIL).await).await;
+
+    fakes.clock.advance(NOMINATION_TTL_MS);
+    let second = nomination_id_from(nominate(&client, &jwt, GUARDIAN_EMAIL).await).await;
+    assert_eq!(find_nomination(db, &first).await.status, "EXPIRED");
+    assert_eq!(find_nomination(db, &second).await.status, "PENDING");
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_cancel_pending_nomination() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let nomination_id = nomination_id_from(nominate(&client, &jwt, GUARDIAN_EMAIL).await).await;
+
+    let res = client
+        .delete(format!("/accounts/nominations/{}", nomination_id).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(
+        find_nomination(db, &nomination_id).await.status,
+        "CANCELLED"
+    );
+    assert_eq!(
+        transitions(db, &nomination_id).await,
+        vec![
+            (None, "PENDING".to_string()),
+            (pending(), "CANCELLED".to_string())
+        ]
+    );
+
+    let res = nominate(&client, &jwt, GUARDIAN_EMAIL).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn nominate(client: &TestClient, jwt: &str, email: &str) -> TestResponse {
+    client
+        .post("/accounts/nominations")
+        .body(format!("{{\"email\":\"{}\"}}", email))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+async fn nomination_id_from(res: TestResponse) -> String {
+    match res
+        .json::<ApiResponse<NominationCreateResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(NominationCreateResponse { nomination_id }) => nomination_id,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+async fn resend(client: &TestClient, jwt: &str, nomination_id: &str) -> TestResponse {
+    client
+        .post(format!("/accounts/nominations/{}/resend", nomination_id).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+async fn find_nomination(db: &DatabaseConnection, nomination_id: &str) -> nomination_repo::Model {
+    nomination_repo::find_by_id(db, nomination_id.to_string())
+        .await
+        .unwrap()
+        .expect("nomination not found")
+}
+
+// sorted, the transitions of a test happen at the same time on the fake clock
+async fn transitions(
+    db: &DatabaseConnection,
+    nomination_id: &str,
+) -> Vec<(Option<String>, String)> {
+    let mut transitions =
+        nomination_history_repo::find_all_by_nomination_id(db, nomination_id.to_string())
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|history| (history.from_status, history.to_status))
+            .collect::<Vec<_>>();
+    transitions.sort();
+    transitions
+}
+
+fn pending() -> Option<String> {
+    Some("PENDING".to_string())
+}
@@ -0,0 +1,254 @@
+use axum_test_helper::{TestClient, TestResponse};
+use clutch_wallet_lib::utils::{bundler::UserOperationTransport, wallet_lib::Transaction};
+use ethers::types::{Address, Bytes, U256};
+use hyper::{header::AUTHORIZATION, StatusCode};
+use lib::{
+    models::api::{
+        ApiErrorResponse, ApiPayload, ApiResponse, FormatUserOpRequest, FormatUserOpResponse,
+        SendTransactionRequest,
+    },
+    operations::{time::Clock, user_operation::paymaster},
+    repos::{account_repo, paymaster_policy_repo, paymaster_sponsorship_repo},
+    test::utils::{create_verified_account_jwt, setup_with_fakes, tear_down, Fakes},
+};
+use sea_orm::DatabaseConnection;
+use std::str::FromStr;
+use uuid::Uuid;
+
+const EMAIL: &str = "someone@example.com";
+const VERIFYING_PAYMASTER: &str = "0x9d1a5c6b1e0f4a3b8c7d2e9f0a1b2c3d4e5f6a7b";
+const TARGET: &str = "0x35e218ac80e08990cf0b868deb512f6ababf1dde";
+const ANOTHER_TARGET: &str = "0x370f9c8e06c2a6ae986fc050d36d0c6a0475bb99";
+
+#[tokio::test]
+async fn test_format_sponsored_user_op() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    create_policy(db, None, None, Some(vec![TARGET]), &fakes).await;
+
+    let res = format_user_op(&client, db, &jwt, TARGET, true).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    let user_op = formatted_user_op(res).await;
+    assert_eq!(
+        paymaster(&user_op.paymaster_and_data),
+        Some(Address::from_str(VERIFYING_PAYMASTER).unwrap())
+    );
+    assert_eq!(user_op.paymaster_and_data.len(), 20 + 64 + 65);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_no_policy_sponsors_account() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+
+    let res = format_user_op(&client, db, &jwt, TARGET, true).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_target_is_not_sponsored() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let policy_id = create_policy(db, None, None, Some(vec![TARGET]), &fakes).await;
+
+    let res = format_user_op(&client, db, &jwt, ANOTHER_TARGET, true).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert!(res.text().await.contains(&format!(
+        "Policy {} does not sponsor calls to {}",
+        policy_id, ANOTHER_TARGET
+    )));
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_policy_is_not_yet_valid() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let valid_from = fakes.clock.now_ms() + 60 * 1000;
+    create_policy(db, None, Some(valid_from), None, &fakes).await;
+
+    let res = format_user_op(&client, db, &jwt, TARGET, true).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+
+    fakes.clock.advance(60 * 1000);
+    let res = format_user_op(&client, db, &jwt, TARGET, true).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_send_sponsored_user_ops_within_gas_budget() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db).await;
+    // (50000 + 3 * 140000 + 46872) gas at 2 gwei is 1033744 gwei per sent user op
+    let gas_budget = U256::from(2_000_000_000_000_000_u64);
+    let policy_id = create_policy(db, Some(gas_budget), None, None, &fakes).await;
+
+    let res = format_user_op(&client, db, &jwt, TARGET, true).await;
+    let user_op = formatted_user_op(res).await;
+
+    let res = send_transaction(&client, &jwt, &account, user_op.clone()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let sent = fakes.wallet.sent_user_operations();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(
+        paymaster(&sent[0].paymaster_and_data),
+        Some(Address::from_str(VERIFYING_PAYMASTER).unwrap())
+    );
+    let sponsorships = paymaster_sponsorship_repo::find_all_by_policy_id_and_account_id(
+        db,
+        policy_id.clone(),
+        account.id.clone(),
+    )
+    .await
+    .unwrap();
+    assert_eq!(sponsorships.len(), 1);
+    assert_eq!(sponsorships[0].max_gas_cost, "1033744000000000");
+
+    let res = send_transaction(&client, &jwt, &account, user_op).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    assert!(res.text().await.contains("would exceed the budget"));
+    assert_eq!(fakes.wallet.sent_user_operations().len(), 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_send_unsponsored_user_op_without_policy() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db).await;
+
+    let res = format_user_op(&client, db, &jwt, TARGET, false).await;
+    let user_op = formatted_user_op(res).await;
+    assert!(user_op.paymaster_and_data.is_empty());
+
+    let res = send_transaction(&client, &jwt, &account, user_op).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(fakes.wallet.sent_user_operations().len(), 1);
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn create_policy(
+    db: &DatabaseConnection,
+    gas_budget: Option<U256>,
+    valid_from: Option<i64>,
+    allowed_targets: Option<Vec<&str>>,
+    fakes: &Fakes,
+) -> String {
+    let id = Uuid::new_v4().to_string();
+    let now = fakes.clock.now_ms();
+    paymaster_policy_repo::create(
+        db,
+        paymaster_policy_repo::Model {
+            id: id.clone(),
+            account_id: None,
+            gas_budget: gas_budget.map(|gas_budget| gas_budget.to_string()),
+            allowed_targets: allowed_targets
+                .map(|targets| serde_json::to_string(&targets).unwrap()),
+            valid_from,
+            valid_until: None,
+            active: true,
+            created_at: now,
+            updated_at: now,
+        },
+    )
+    .await
+    .expect("error creating paymaster policy");
+    id
+}
+
+async fn find_account(db: &DatabaseConnection) -> account_repo::Model {
+    account_repo::find_by_email(db, EMAIL)
+        .await
+        .unwrap()
+        .expect("account not found")
+}
+
+async fn format_user_op(
+    client: &TestClient,
+    db: &DatabaseConnection,
+    jwt: &str,
+    to: &str,
+    sponsored: bool,
+) -> TestResponse {
+    let account = find_account(db).await;
+    let req = FormatUserOpRequest {
+        max_fee_per_gas: U256::zero(),
+        max_priority_fee_per_gas: U256::zero(),
+        selected_address: Address::from_str(&account.wallet_address).unwrap(),
+        raw_txs: vec![Transaction {
+            to: Address::from_str(to).unwrap(),
+            data: Some(Bytes::default()),
+            value: Some(U256::zero()),
+            gas_limit: None,
+        }],
+        pay_token: Address::zero(),
+        sponsored,
+    };
+
+    client
+        .post("/transaction/format-user-op")
+        .body(serde_json::to_string(&req).unwrap())
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+async fn formatted_user_op(res: TestResponse) -> UserOperationTransport {
+    match res
+        .json::<ApiResponse<FormatUserOpResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(FormatUserOpResponse { user_op, .. }) => user_op,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+async fn send_transaction(
+    client: &TestClient,
+    jwt: &str,
+    account: &account_repo::Model,
+    user_op: UserOperationTransport,
+) -> TestResponse {
+    let req = SendTransactionRequest {
+        user_op,
+        from: account.wallet_address.clone(),
+    };
+
+    client
+        .post("/transaction")
+        .body(serde_json::to_string(&req).unwrap())
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
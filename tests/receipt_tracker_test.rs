@@ -0,0 +1,135 @@
+use lib::{
+    jobs::receipt_tracker::track_receipts,
+    operations::time::get_unix_timestamp_ms,
+    repos::user_operation_repo,
+    test::utils::{mock_bundler, setup, tear_down},
+};
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+const ACCOUNT_ID: &str = "d69e35f5-3ce3-402b-b70b-11745651d88f";
+const SUCCEEDED: &str = "0x1111111111111111111111111111111111111111111111111111111111111111";
+const REVERTED: &str = "0x2222222222222222222222222222222222222222222222222222222222222222";
+const UNCONFIRMED: &str = "0x3333333333333333333333333333333333333333333333333333333333333333";
+const STALE: &str = "0x4444444444444444444444444444444444444444444444444444444444444444";
+const WAITING: &str = "0x5555555555555555555555555555555555555555555555555555555555555555";
+const TRANSACTION_HASH: &str = "0x9999999999999999999999999999999999999999999999999999999999999999";
+
+#[tokio::test]
+async fn test_track_receipts_moves_user_operations_to_their_final_status() {
+    let (_client, app_state, db_url) = setup().await;
+    let db = &app_state.database;
+
+    let now = get_unix_timestamp_ms();
+    let an_hour_ago = now - 60 * 60 * 1000;
+    create_user_operation(db, SUCCEEDED, "PENDING", now).await;
+    create_user_operation(db, REVERTED, "PENDING", now).await;
+    create_user_operation(db, UNCONFIRMED, "PENDING", now).await;
+    create_user_operation(db, STALE, "PENDING", an_hour_ago).await;
+    create_user_operation(db, WAITING, "PENDING", now).await;
+
+    let bundler = mock_bundler(
+        HashMap::from([
+            (SUCCEEDED.to_string(), receipt(SUCCEEDED, true, 100)),
+            (REVERTED.to_string(), receipt(REVERTED, false, 100)),
+            (UNCONFIRMED.to_string(), receipt(UNCONFIRMED, true, 101)),
+        ]),
+        102,
+    )
+    .await;
+
+    track_receipts(db, &bundler, &bundler, now).await.unwrap();
+
+    let succeeded = find(db, SUCCEEDED).await;
+    assert_eq!(succeeded.status, "SUCCESS");
+    assert_eq!(
+        succeeded.transaction_hash,
+        Some(TRANSACTION_HASH.to_string())
+    );
+    assert_eq!(succeeded.block_number, Some(100));
+    assert_eq!(succeeded.actual_gas_cost, Some("21000".to_string()));
+    assert_eq!(find(db, REVERTED).await.status, "REVERTED");
+    assert_eq!(find(db, UNCONFIRMED).await.status, "INCLUDED");
+    assert_eq!(find(db, STALE).await.status, "DROPPED");
+    assert_eq!(find(db, WAITING).await.status, "PENDING");
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_track_receipts_returns_included_user_operation_to_pending_on_reorg() {
+    let (_client, app_state, db_url) = setup().await;
+    let db = &app_state.database;
+
+    let now = get_unix_timestamp_ms();
+    create_user_operation(db, UNCONFIRMED, "INCLUDED", now).await;
+
+    let bundler = mock_bundler(HashMap::new(), 102).await;
+
+    track_receipts(db, &bundler, &bundler, now).await.unwrap();
+
+    assert_eq!(find(db, UNCONFIRMED).await.status, "PENDING");
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+fn receipt(user_op_hash: &str, success: bool, block_number: u64) -> Value {
+    json!({
+        "userOpHash": user_op_hash,
+        "success": success,
+        "actualGasCost": "0x5208",
+        "receipt": {
+            "transactionHash": TRANSACTION_HASH,
+            "blockNumber": format!("0x{:x}", block_number),
+        },
+    })
+}
+
+async fn create_user_operation(
+    db: &DatabaseConnection,
+    user_op_hash: &str,
+    status: &str,
+    created_at: i64,
+) {
+    user_operation_repo::create(
+        db,
+        user_operation_repo::Model {
+            id: Uuid::new_v4().to_string(),
+            account_id: ACCOUNT_ID.to_string(),
+            user_op_hash: user_op_hash.to_string(),
+            sender: "0x1cf1d4ac0f6ac1b2e3c2a5e8b6d8e1f2a3b4c5d6".to_string(),
+            nonce: "0".to_string(),
+            call_data_summary: "0xb61d27f6 (196 bytes)".to_string(),
+            call_gas_limit: "50000".to_string(),
+            verification_gas_limit: "100000".to_string(),
+            pre_verification_gas: "45000".to_string(),
+            max_fee_per_gas: "100000000000".to_string(),
+            max_priority_fee_per_gas: "100000000000".to_string(),
+            paymaster: None,
+            bundler_response: Some("true".to_string()),
+            status: status.to_string(),
+            transaction_hash: None,
+            block_number: None,
+            actual_gas_cost: None,
+            created_at,
+            updated_at: created_at,
+        },
+    )
+    .await
+    .expect("error creating user operation");
+}
+
+async fn find(db: &DatabaseConnection, user_op_hash: &str) -> user_operation_repo::Model {
+    user_operation_repo::find_by_account_and_user_op_hash(
+        db,
+        ACCOUNT_ID.to_string(),
+        user_op_hash.to_string(),
+    )
+    .await
+    .unwrap()
+    .expect("user operation not found")
+}
@@ -0,0 +1,576 @@
+use axum_test_helper::{TestClient, TestResponse};
+use clutch_wallet_lib::utils::{bundler::UserOperationTransport, wallet_lib::Transaction};
+use ethers::{
+    signers::{LocalWallet, Signer},
+    types::{Address, Bytes, U256},
+};
+use hyper::{header::AUTHORIZATION, StatusCode};
+use lib::{
+    jobs::{receipt_tracker::track_receipts, recovery::process_recoveries},
+    models::api::{
+        ApiErrorResponse, ApiPayload, ApiResponse, FormatUserOpRequest, FormatUserOpResponse,
+        Recovery, SendTransactionRequest, SigningStrategy,
+    },
+    operations::paymaster::call_targets,
+    operations::time::get_unix_timestamp_ms,
+    repos::{
+        account_repo, guardian_account_repo, guardian_repo, guardian_settings_repo,
+        verification_repo,
+    },
+    test::utils::{create_verified_account_jwt, mock_bundler, setup_with_fakes, tear_down},
+    utils::convert_to_hex,
+};
+use sea_orm::DatabaseConnection;
+use serde_json::json;
+use std::{collections::HashMap, str::FromStr};
+use uuid::Uuid;
+
+const EMAIL: &str = "someone@example.com";
+const NEW_OWNER: &str = "0x4e5f6a7b8c9d0e1f2a3b4c5d6e7f8a9b0c1d2e3f";
+const SECURITY_CONTROL_MODULE: &str = "0x5748f0a6a5d251e0f511470af60fec8a55291217";
+const TIMELOCK_MS: i64 = 86400 * 1000;
+const TRANSACTION_HASH: &str = "0x9999999999999999999999999999999999999999999999999999999999999999";
+
+#[tokio::test]
+async fn test_recover_wallet_after_guardian_approvals_and_timelock() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    let guardians = vec![
+        create_guardian(db, &client, &account, "first@example.com").await,
+        create_guardian(db, &client, &account, "second@example.com").await,
+    ];
+    create_guardian_settings(db, &account, SigningStrategy::new(2, 2)).await;
+
+    let recovery = recovery_from(start_recovery(&client, db, NEW_OWNER).await).await;
+    assert_eq!(recovery.status, "PENDING");
+    assert_eq!(recovery.approvals_required, 2);
+    assert_eq!(recovery.executable_at, recovery.created_at + TIMELOCK_MS);
+
+    let mut signatures = vec![];
+    let (guardian_jwt, guardian_wallet) = &guardians[0];
+    let signature = guardian_wallet
+        .sign_message(&recovery.message)
+        .await
+        .unwrap();
+    let res = approve_recovery(&client, guardian_jwt, &recovery.id, &signature.to_string()).await;
+    assert_eq!(recovery_from(res).await.approvals, 1);
+    signatures.push(signature);
+
+    // one approval is not enough, even after the timelock
+    process_recoveries(&app_state, recovery.executable_at)
+        .await
+        .unwrap();
+    assert!(fakes.wallet.sent_user_operations().is_empty());
+
+    let (guardian_jwt, guardian_wallet) = &guardians[1];
+    let signature = guardian_wallet
+        .sign_message(&recovery.message)
+        .await
+        .unwrap();
+    let res = approve_recovery(&client, guardian_jwt, &recovery.id, &signature.to_string()).await;
+    assert_eq!(recovery_from(res).await.approvals, 2);
+    signatures.push(signature);
+
+    process_recoveries(&app_state, recovery.executable_at - 1)
+        .await
+        .unwrap();
+    assert!(fakes.wallet.sent_user_operations().is_empty());
+
+    process_recoveries(&app_state, recovery.executable_at)
+        .await
+        .unwrap();
+    let sent = fakes.wallet.sent_user_operations();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(convert_to_hex(sent[0].sender), account.wallet_address);
+    assert_eq!(
+        call_targets(&sent[0].call_data),
+        Some(vec![Address::from_str(SECURITY_CONTROL_MODULE).unwrap()])
+    );
+    // the guardians authorize the rotation, not the owner key
+    for signature in signatures {
+        assert!(contains(&sent[0].call_data, &signature.to_vec()));
+    }
+
+    let res = get_recovery(&client, &jwt, &recovery.id).await;
+    let recovery = recovery_from(res).await;
+    assert_eq!(recovery.status, "SUBMITTED");
+    let user_op_hash = recovery.user_op_hash.expect("user op hash not stored");
+    assert_eq!(
+        find_account(db, EMAIL).await.eoa_address,
+        account.eoa_address
+    );
+
+    settle(db, &user_op_hash, true, recovery.executable_at).await;
+    let res = get_recovery(&client, &jwt, &recovery.id).await;
+    assert_eq!(recovery_from(res).await.status, "EXECUTED");
+    assert_eq!(find_account(db, EMAIL).await.eoa_address, NEW_OWNER);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_guardian_lists_pending_recoveries() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    let (guardian_jwt, _) = create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+    let recovery = recovery_from(start_recovery(&client, db, NEW_OWNER).await).await;
+
+    let res = client
+        .get("/guardian/recoveries")
+        .header(AUTHORIZATION, format!("Bearer {}", guardian_jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let body = res.text().await;
+    assert!(body.contains(&recovery.id));
+    assert!(body.contains(&serde_json::to_string(&recovery.message).unwrap()));
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_signature_is_not_from_guardian() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    let (guardian_jwt, _) = create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+    let recovery = recovery_from(start_recovery(&client, db, NEW_OWNER).await).await;
+
+    let someone_else = LocalWallet::new(&mut rand::thread_rng());
+    let signature = someone_else.sign_message(&recovery.message).await.unwrap();
+    let res = approve_recovery(&client, &guardian_jwt, &recovery.id, &signature.to_string()).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_guardian_has_no_verified_wallet() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    let (guardian_jwt, guardian_wallet, _) =
+        create_unverified_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+    let recovery = recovery_from(start_recovery(&client, db, NEW_OWNER).await).await;
+
+    // a wallet on the guardian record that was never proved to be theirs isn't enough
+    let signature = guardian_wallet
+        .sign_message(&recovery.message)
+        .await
+        .unwrap();
+    let res = approve_recovery(&client, &guardian_jwt, &recovery.id, &signature.to_string()).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_not_enough_active_guardians() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(2, 2)).await;
+
+    let code = create_code(db).await;
+    let res = start_recovery_with_code(&client, &code, NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+    // the code can still be used once the account has its guardians
+    assert_eq!(find_code(db, &code).await.unwrap().consumed_at, None);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_recovery_code_is_used_or_invalid() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+
+    let res = start_recovery_with_code(&client, "000000", NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    let code = create_code(db).await;
+    let res = start_recovery_with_code(&client, &code, NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = start_recovery_with_code(&client, &code, NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_recovery_already_pending() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+
+    let res = start_recovery(&client, db, NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let res = start_recovery(&client, db, NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_cancelled_recovery_is_not_executed() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    let (guardian_jwt, guardian_wallet) =
+        create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+    let recovery = recovery_from(start_recovery(&client, db, NEW_OWNER).await).await;
+
+    let res = client
+        .delete(&format!("/accounts/recovery/{}", recovery.id))
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(recovery_from(res).await.status, "CANCELLED");
+
+    let signature = guardian_wallet
+        .sign_message(&recovery.message)
+        .await
+        .unwrap();
+    let res = approve_recovery(&client, &guardian_jwt, &recovery.id, &signature.to_string()).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    process_recoveries(&app_state, recovery.executable_at)
+        .await
+        .unwrap();
+    assert!(fakes.wallet.sent_user_operations().is_empty());
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_sending_transaction_after_recovery() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    let (guardian_jwt, guardian_wallet) =
+        create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+    let recovery = recovery_from(start_recovery(&client, db, NEW_OWNER).await).await;
+    let signature = guardian_wallet
+        .sign_message(&recovery.message)
+        .await
+        .unwrap();
+    approve_recovery(&client, &guardian_jwt, &recovery.id, &signature.to_string()).await;
+    process_recoveries(&app_state, recovery.executable_at)
+        .await
+        .unwrap();
+    assert_eq!(fakes.wallet.sent_user_operations().len(), 1);
+    let res = get_recovery(&client, &jwt, &recovery.id).await;
+    let user_op_hash = recovery_from(res).await.user_op_hash.unwrap();
+    settle(db, &user_op_hash, true, recovery.executable_at).await;
+
+    let account = find_account(db, EMAIL).await;
+    assert_eq!(account.eoa_address, NEW_OWNER);
+    assert!(!account.is_custodial());
+    assert!(account.eoa_private_address.is_empty());
+
+    let user_op = format_user_op(&client, &jwt, &account).await;
+    let res = send_transaction(&client, &jwt, &account, user_op).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+    insta::assert_yaml_snapshot!(res.text().await);
+    assert_eq!(fakes.wallet.sent_user_operations().len(), 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_reverted_recovery_fails_and_keeps_the_owner() {
+    let (client, app_state, db_url, _fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+    let account = find_account(db, EMAIL).await;
+    let (guardian_jwt, guardian_wallet) =
+        create_guardian(db, &client, &account, "first@example.com").await;
+    create_guardian_settings(db, &account, SigningStrategy::new(1, 1)).await;
+    let recovery = recovery_from(start_recovery(&client, db, NEW_OWNER).await).await;
+    let signature = guardian_wallet
+        .sign_message(&recovery.message)
+        .await
+        .unwrap();
+    approve_recovery(&client, &guardian_jwt, &recovery.id, &signature.to_string()).await;
+    process_recoveries(&app_state, recovery.executable_at)
+        .await
+        .unwrap();
+
+    // a submitted recovery is still in progress
+    let res = start_recovery(&client, db, NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::CONFLICT);
+
+    let res = get_recovery(&client, &jwt, &recovery.id).await;
+    let user_op_hash = recovery_from(res).await.user_op_hash.unwrap();
+    settle(db, &user_op_hash, false, recovery.executable_at).await;
+
+    let res = get_recovery(&client, &jwt, &recovery.id).await;
+    assert_eq!(recovery_from(res).await.status, "FAILED");
+    let recovered = find_account(db, EMAIL).await;
+    assert_eq!(recovered.eoa_address, account.eoa_address);
+    assert!(recovered.is_custodial());
+
+    let res = start_recovery(&client, db, NEW_OWNER).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn find_account(db: &DatabaseConnection, email: &str) -> account_repo::Model {
+    account_repo::find_by_email(db, email)
+        .await
+        .unwrap()
+        .expect("account not found")
+}
+
+// a guardian with their own account and a verified wallet, active on the account
+async fn create_guardian(
+    db: &DatabaseConnection,
+    client: &TestClient,
+    account: &account_repo::Model,
+    email: &str,
+) -> (String, LocalWallet) {
+    let (jwt, wallet, guardian_id) = create_unverified_guardian(db, client, account, email).await;
+    guardian_repo::update_verified_address(
+        db,
+        guardian_id.to_string(),
+        convert_to_hex(wallet.address()),
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error verifying guardian");
+    (jwt, wallet)
+}
+
+// a guardian that never proved to control a wallet
+async fn create_unverified_guardian(
+    db: &DatabaseConnection,
+    client: &TestClient,
+    account: &account_repo::Model,
+    email: &str,
+) -> (String, LocalWallet, Uuid) {
+    let jwt = create_verified_account_jwt(db, client, email.to_string()).await;
+    let wallet = LocalWallet::new(&mut rand::thread_rng());
+    let guardian_id = Uuid::new_v4();
+    guardian_repo::create(
+        db,
+        guardian_id,
+        email.to_string(),
+        Some(find_account(db, email).await.id),
+        Some(convert_to_hex(wallet.address())),
+    )
+    .await
+    .expect("error creating guardian");
+    guardian_account_repo::create(
+        db,
+        Uuid::new_v4(),
+        guardian_id.to_string(),
+        account.id.clone(),
+        "ACTIVE".to_string(),
+    )
+    .await
+    .expect("error creating account guardian");
+    (jwt, wallet, guardian_id)
+}
+
+async fn create_guardian_settings(
+    db: &DatabaseConnection,
+    account: &account_repo::Model,
+    signers: SigningStrategy,
+) {
+    guardian_settings_repo::create(db, Uuid::new_v4(), signers, account.id.clone())
+        .await
+        .expect("error creating guardian settings");
+}
+
+// the owner starts it with a fresh code sent to the account's email
+async fn start_recovery(
+    client: &TestClient,
+    db: &DatabaseConnection,
+    new_owner: &str,
+) -> TestResponse {
+    let code = create_code(db).await;
+    start_recovery_with_code(client, &code, new_owner).await
+}
+
+async fn start_recovery_with_code(
+    client: &TestClient,
+    code: &str,
+    new_owner: &str,
+) -> TestResponse {
+    client
+        .post("/accounts/recovery")
+        .body(format!(
+            "{{\"email\":\"{}\",\"code\":\"{}\",\"new_owner\":\"{}\"}}",
+            EMAIL, code, new_owner
+        ))
+        .header("Content-Type", "application/json")
+        .send()
+        .await
+}
+
+async fn create_code(db: &DatabaseConnection) -> String {
+    let code = Uuid::new_v4().to_string();
+    let now = get_unix_timestamp_ms();
+    verification_repo::create(db, Uuid::new_v4(), EMAIL, &code, now + 60 * 1000, now)
+        .await
+        .expect("error creating verification");
+    code
+}
+
+async fn find_code(db: &DatabaseConnection, code: &str) -> Option<verification_repo::Model> {
+    verification_repo::find_by_email_and_code(db, EMAIL, code)
+        .await
+        .unwrap()
+}
+
+async fn get_recovery(client: &TestClient, jwt: &str, recovery_id: &str) -> TestResponse {
+    client
+        .get(&format!("/accounts/recovery/{}", recovery_id))
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+async fn approve_recovery(
+    client: &TestClient,
+    jwt: &str,
+    recovery_id: &str,
+    signature: &str,
+) -> TestResponse {
+    client
+        .post(&format!("/guardian/recoveries/{}/approve", recovery_id))
+        .body(format!("{{\"signature\":\"0x{}\"}}", signature))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+async fn recovery_from(res: TestResponse) -> Recovery {
+    match res
+        .json::<ApiResponse<Recovery, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(recovery) => recovery,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+async fn format_user_op(
+    client: &TestClient,
+    jwt: &str,
+    account: &account_repo::Model,
+) -> UserOperationTransport {
+    let req = FormatUserOpRequest {
+        max_fee_per_gas: U256::zero(),
+        max_priority_fee_per_gas: U256::zero(),
+        selected_address: Address::from_str(&account.wallet_address).unwrap(),
+        raw_txs: vec![Transaction {
+            to: Address::from_str(NEW_OWNER).unwrap(),
+            data: Some(Bytes::default()),
+            value: Some(U256::zero()),
+            gas_limit: None,
+        }],
+        pay_token: Address::zero(),
+        sponsored: false,
+    };
+
+    let res = client
+        .post("/transaction/format-user-op")
+        .body(serde_json::to_string(&req).unwrap())
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    match res
+        .json::<ApiResponse<FormatUserOpResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(FormatUserOpResponse { user_op, .. }) => user_op,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+async fn send_transaction(
+    client: &TestClient,
+    jwt: &str,
+    account: &account_repo::Model,
+    user_op: UserOperationTransport,
+) -> TestResponse {
+    let req = SendTransactionRequest {
+        user_op,
+        from: account.wallet_address.clone(),
+    };
+
+    client
+        .post("/transaction")
+        .body(serde_json::to_string(&req).unwrap())
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+// the receipt tracker sees the user op included with enough confirmations
+async fn settle(db: &DatabaseConnection, user_op_hash: &str, success: bool, now: i64) {
+    let receipt = json!({
+        "userOpHash": user_op_hash,
+        "success": success,
+        "actualGasCost": "0x5208",
+        "receipt": {
+            "transactionHash": TRANSACTION_HASH,
+            "blockNumber": "0x64",
+        },
+    });
+    let bundler = mock_bundler(HashMap::from([(user_op_hash.to_string(), receipt)]), 102).await;
+    track_receipts(db, &bundler, &bundler, now).await.unwrap();
+}
+
+fn contains(bytes: &[u8], part: &[u8]) -> bool {
+    bytes.windows(part.len()).any(|window| window == part)
+}
@@ -0,0 +1,142 @@
+use axum_test_helper::{TestClient, TestResponse};
+use clutch_wallet_lib::utils::wallet_lib::Transaction;
+use ethers::types::{Address, Bytes, U256};
+use hyper::{header::AUTHORIZATION, StatusCode};
+use lib::{
+    models::api::FormatUserOpRequest,
+    operations::time::Clock,
+    repos::{account_repo, token_repo},
+    test::utils::{create_verified_account_jwt, setup, setup_with_fakes, tear_down},
+};
+use sea_orm::DatabaseConnection;
+use std::str::FromStr;
+
+const EMAIL: &str = "someone@example.com";
+const USDC: &str = "0x1a2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b";
+const DAI: &str = "0x2b3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c";
+const UNKNOWN_TOKEN: &str = "0x3c4d5e6f7a8b9c0d1e2f3a4b5c6d7e8f9a0b1c2d";
+const RECEIVER: &str = "0x9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b";
+
+#[tokio::test]
+async fn test_seed_tokens_from_config() {
+    let (_client, app_state, db_url) = setup().await;
+
+    let usdc = token_repo::find_by_address_and_chain_id(
+        &app_state.database,
+        USDC.to_string(),
+        app_state.settings.chain_id() as i64,
+    )
+    .await
+    .unwrap()
+    .expect("token not seeded");
+    assert_eq!(usdc.symbol, Some("USDC".to_string()));
+    assert_eq!(usdc.decimals, 6);
+    assert!(usdc.paymaster_supported);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_resolve_unknown_token_decimals_once() {
+    let (_client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let token = Address::from_str(UNKNOWN_TOKEN).unwrap();
+    fakes.token_contract.set_decimals(token, 8);
+
+    let now = fakes.clock.now_ms();
+    let decimals = app_state.tokens.decimals(&app_state.database, token, now);
+    assert_eq!(decimals.await.unwrap(), 8);
+    let decimals = app_state.tokens.decimals(&app_state.database, token, now);
+    assert_eq!(decimals.await.unwrap(), 8);
+    assert_eq!(fakes.token_contract.calls(), 1);
+
+    let cached = app_state
+        .tokens
+        .find(&app_state.database, token)
+        .await
+        .unwrap()
+        .expect("token not cached");
+    assert_eq!(cached.symbol, None);
+    assert!(!cached.paymaster_supported);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_prefund_erc20_with_token_decimals() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+
+    let res = prefund_erc20(&client, db, &jwt, USDC, "1.5").await;
+    assert_eq!(res.status(), StatusCode::OK);
+    assert_eq!(fakes.token_contract.calls(), 0);
+
+    let res = prefund_erc20(&client, db, &jwt, USDC, "1.0000001").await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_pay_token_is_not_supported_by_paymaster() {
+    let (client, app_state, db_url) = setup().await;
+    let db = &app_state.database;
+    let jwt = create_verified_account_jwt(db, &client, EMAIL.to_string()).await;
+
+    let req = FormatUserOpRequest {
+        max_fee_per_gas: U256::zero(),
+        max_priority_fee_per_gas: U256::zero(),
+        selected_address: Address::from_str(&find_account(db).await.wallet_address).unwrap(),
+        raw_txs: vec![Transaction {
+            to: Address::from_str(RECEIVER).unwrap(),
+            data: Some(Bytes::default()),
+            value: Some(U256::zero()),
+            gas_limit: None,
+        }],
+        pay_token: Address::from_str(DAI).unwrap(),
+        sponsored: false,
+    };
+    let res = client
+        .post("/transaction/format-user-op")
+        .body(serde_json::to_string(&req).unwrap())
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn find_account(db: &DatabaseConnection) -> account_repo::Model {
+    account_repo::find_by_email(db, EMAIL)
+        .await
+        .unwrap()
+        .expect("account not found")
+}
+
+async fn prefund_erc20(
+    client: &TestClient,
+    db: &DatabaseConnection,
+    jwt: &str,
+    token: &str,
+    value: &str,
+) -> TestResponse {
+    client
+        .post("/transaction/prefund")
+        .body(format!(
+            "{{\"send_type\":\"send_erc20\",\"value\":\"{}\",\"pay_token\":\"{}\",\"from\":\"{}\",\"to\":\"{}\"}}",
+            value,
+            token,
+            find_account(db).await.wallet_address,
+            RECEIVER
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
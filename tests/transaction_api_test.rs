@@ -0,0 +1,373 @@
+use axum_test_helper::{TestClient, TestResponse};
+use hyper::{header::AUTHORIZATION, StatusCode};
+use lib::{
+    models::api::{
+        ApiErrorResponse, ApiPayload, ApiResponse, ListUserOperationsResponse, UserOperation,
+    },
+    operations::time::get_unix_timestamp_ms,
+    repos::{account_repo, user_operation_repo},
+    routes::auth_api::create_session,
+    test::utils::{setup, tear_down},
+};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+const WALLET_ADDRESS: &str = "0x1cf1d4ac0f6ac1b2e3c2a5e8b6d8e1f2a3b4c5d6";
+const ANOTHER_WALLET_ADDRESS: &str = "0x9a8b7c6d5e4f3a2b1c0d9e8f7a6b5c4d3e2f1a0b";
+const ZERO_ADDRESS: &str = "0x0000000000000000000000000000000000000000";
+
+#[tokio::test]
+async fn test_error_when_no_bearer_token_on_send_transaction() {
+    let (client, _app_state, db_url) = setup().await;
+
+    let res = client
+        .post("/transaction")
+        .body(format!("{{\"from\":\"{}\"}}", WALLET_ADDRESS))
+        .header("Content-Type", "application/json")
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::UNAUTHORIZED);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_send_transaction_from_another_users_wallet() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+
+    let res = client
+        .post("/transaction")
+        .body(format!("{{\"from\":\"{}\"}}", ANOTHER_WALLET_ADDRESS))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_prefund_from_another_users_wallet() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+
+    let res = prefund(&client, &jwt, ANOTHER_WALLET_ADDRESS).await;
+    assert_eq!(res.status(), StatusCode::FORBIDDEN);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_transaction_history_is_paginated() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+    let account_id = account_id(&app_state.database).await;
+    for (hash, created_at) in [("0x01", 1000), ("0x02", 2000), ("0x03", 3000)] {
+        create_user_operation(
+            &app_state.database,
+            &account_id,
+            hash,
+            "PENDING",
+            created_at,
+        )
+        .await;
+    }
+
+    let history = history_page(&client, &jwt, "page=0&page_size=2").await;
+    assert_eq!(history.total, 3);
+    assert_eq!(user_op_hashes(&history), vec!["0x03", "0x02"]);
+
+    let history = history_page(&client, &jwt, "page=1&page_size=2").await;
+    assert_eq!(history.total, 3);
+    assert_eq!(user_op_hashes(&history), vec!["0x01"]);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_filter_transaction_history_by_status_and_date() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+    let account_id = account_id(&app_state.database).await;
+    create_user_operation(&app_state.database, &account_id, "0x01", "PENDING", 1000).await;
+    create_user_operation(&app_state.database, &account_id, "0x02", "FAILED", 2000).await;
+    create_user_operation(&app_state.database, &account_id, "0x03", "PENDING", 3000).await;
+    create_user_operation(
+        &app_state.database,
+        "another_account",
+        "0x04",
+        "PENDING",
+        3000,
+    )
+    .await;
+
+    let history = history_page(&client, &jwt, "status=SUBMITTED").await;
+    assert_eq!(user_op_hashes(&history), vec!["0x03", "0x01"]);
+
+    let history = history_page(&client, &jwt, "created_after=2000&created_before=3000").await;
+    assert_eq!(user_op_hashes(&history), vec!["0x02"]);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_invalid_status_on_transaction_history() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+
+    let res = client
+        .get("/transaction/history?status=DONE")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_retrieve_user_operation_by_hash() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+    let account_id = account_id(&app_state.database).await;
+    create_user_operation(&app_state.database, &account_id, "0x01", "PENDING", 1000).await;
+
+    let res = client
+        .get("/transaction/0x01")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    match res
+        .json::<ApiResponse<UserOperation, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(user_operation) => {
+            assert_eq!(user_operation.user_op_hash, "0x01");
+            assert_eq!(user_operation.status, "PENDING");
+        }
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_retrieve_another_users_user_operation() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+    create_user_operation(
+        &app_state.database,
+        "another_account",
+        "0x01",
+        "PENDING",
+        1000,
+    )
+    .await;
+
+    let res = client
+        .get("/transaction/0x01")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::NOT_FOUND);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_prefund_has_unknown_send_type() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+
+    let res = client
+        .post("/transaction/prefund")
+        .body(format!(
+            "{{\"send_type\":\"send_btc\",\"value\":\"1\",\"from\":\"{}\",\"to\":\"{}\"}}",
+            WALLET_ADDRESS, ANOTHER_WALLET_ADDRESS
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    assert!(res.text().await.contains("unknown variant `send_btc`"));
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_prefund_has_invalid_fields() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+
+    let res = client
+        .post("/transaction/prefund")
+        .body(format!(
+            "{{\"send_type\":\"send_erc20\",\"value\":\"-1\",\"from\":\"{}\",\"to\":\"0x123\"}}",
+            WALLET_ADDRESS
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_format_user_op_has_no_transactions() {
+    let (client, app_state, db_url) = setup().await;
+
+    let jwt = create_account_jwt(&app_state.database, WALLET_ADDRESS).await;
+
+    let res = client
+        .post("/transaction/format-user-op")
+        .body(format!(
+            "{{\"maxFeePerGas\":\"0x0\",\"maxPriorityFeePerGas\":\"0x0\",\"selectedAddress\":\"{}\",\"rawTxs\":[],\"payToken\":\"{}\"}}",
+            WALLET_ADDRESS, ZERO_ADDRESS
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::BAD_REQUEST);
+    insta::assert_yaml_snapshot!(res.text().await);
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn create_account_jwt(db: &DatabaseConnection, wallet_address: &str) -> String {
+    let account_id = Uuid::new_v4();
+    account_repo::create(
+        db,
+        account_id,
+        "someone@example.com".to_string(),
+        wallet_address.to_string(),
+        "".to_string(),
+        "".to_string(),
+        "".to_string(),
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating account");
+
+    let (jwt, _) = create_session(
+        db,
+        account_id.to_string(),
+        None,
+        None,
+        get_unix_timestamp_ms(),
+    )
+    .await
+    .expect("error creating session");
+    jwt
+}
+
+async fn prefund(client: &TestClient, jwt: &str, from: &str) -> TestResponse {
+    client
+        .post("/transaction/prefund")
+        .body(format!(
+            "{{\"send_type\":\"send_eth\",\"value\":\"1\",\"from\":\"{}\",\"to\":\"{}\"}}",
+            from, WALLET_ADDRESS
+        ))
+        .header("Content-Type", "application/json")
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await
+}
+
+async fn account_id(db: &DatabaseConnection) -> String {
+    account_repo::find_by_wallet_address(db, WALLET_ADDRESS.to_string())
+        .await
+        .unwrap()
+        .expect("account not found")
+        .id
+}
+
+async fn create_user_operation(
+    db: &DatabaseConnection,
+    account_id: &str,
+    user_op_hash: &str,
+    status: &str,
+    created_at: i64,
+) {
+    user_operation_repo::create(
+        db,
+        user_operation_repo::Model {
+            id: Uuid::new_v4().to_string(),
+            account_id: account_id.to_string(),
+            user_op_hash: user_op_hash.to_string(),
+            sender: WALLET_ADDRESS.to_string(),
+            nonce: "0".to_string(),
+            call_data_summary: "0xb61d27f6 (196 bytes)".to_string(),
+            call_gas_limit: "50000".to_string(),
+            verification_gas_limit: "100000".to_string(),
+            pre_verification_gas: "45000".to_string(),
+            max_fee_per_gas: "100000000000".to_string(),
+            max_priority_fee_per_gas: "100000000000".to_string(),
+            paymaster: None,
+            bundler_response: Some("true".to_string()),
+            status: status.to_string(),
+            transaction_hash: None,
+            block_number: None,
+            actual_gas_cost: None,
+            created_at,
+            updated_at: created_at,
+        },
+    )
+    .await
+    .expect("error creating user operation");
+}
+
+async fn history_page(client: &TestClient, jwt: &str, query: &str) -> ListUserOperationsResponse {
+    let res = client
+        .get(format!("/transaction/history?{}", query).as_str())
+        .header(AUTHORIZATION, format!("Bearer {}", jwt))
+        .send()
+        .await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    match res
+        .json::<ApiResponse<ListUserOperationsResponse, ApiErrorResponse>>()
+        .await
+        .payload
+    {
+        ApiPayload::Success(history) => history,
+        ApiPayload::Error(ApiErrorResponse { error_message, .. }) => {
+            panic!("error: {}", error_message)
+        }
+    }
+}
+
+fn user_op_hashes(history: &ListUserOperationsResponse) -> Vec<String> {
+    history
+        .user_operations
+        .iter()
+        .map(|user_operation| user_operation.user_op_hash.clone())
+        .collect()
+}
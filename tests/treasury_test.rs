@@ -0,0 +1,137 @@
+use ethers::{types::Address, utils::parse_ether};
+use lib::{
+    operations::{time::Clock, treasury::SENT},
+    repos::treasury_funding_repo,
+    test::utils::{setup_with_fakes, tear_down},
+    utils::convert_to_hex,
+};
+
+const ACCOUNT_ID: &str = "d69e35f5-3ce3-402b-b70b-11745651d88f";
+const ANOTHER_ACCOUNT_ID: &str = "0b7f4c1e-6b2a-4f55-9c1d-3f2e8a9b7c6d";
+const THIRD_ACCOUNT_ID: &str = "5a3c2e1f-8d7b-4c6a-9e5f-1b2c3d4e5f6a";
+
+#[tokio::test]
+async fn test_fund_records_transfer_in_ledger() {
+    let (_client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+    let now = fakes.clock.now_ms();
+    let wallet = Address::repeat_byte(0x11);
+    let amount = parse_ether("0.05").unwrap();
+
+    let transaction_hash = app_state
+        .treasury
+        .fund(db, ACCOUNT_ID, wallet, amount, now)
+        .await
+        .unwrap();
+
+    assert_eq!(fakes.funding_wallet.transfers(), vec![(wallet, amount)]);
+    let fundings =
+        treasury_funding_repo::find_all_by_account_id_and_status(db, ACCOUNT_ID.to_string(), SENT)
+            .await
+            .unwrap();
+    assert_eq!(fundings.len(), 1);
+    assert_eq!(fundings[0].recipient, convert_to_hex(wallet));
+    assert_eq!(fundings[0].amount, amount.to_string());
+    assert_eq!(
+        fundings[0].transaction_hash,
+        Some(format!("{:?}", transaction_hash))
+    );
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_funding_would_cross_the_reserve() {
+    let (_client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let now = fakes.clock.now_ms();
+
+    fakes
+        .funding_wallet
+        .set_balance(parse_ether("1.04").unwrap());
+    let res = app_state
+        .treasury
+        .fund(
+            &app_state.database,
+            ACCOUNT_ID,
+            Address::repeat_byte(0x11),
+            parse_ether("0.05").unwrap(),
+            now,
+        )
+        .await;
+
+    assert!(res.unwrap_err().to_string().contains("reserve"));
+    assert!(fakes.funding_wallet.transfers().is_empty());
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_funding_exceeds_per_account_cap() {
+    let (_client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+    let now = fakes.clock.now_ms();
+    let wallet = Address::repeat_byte(0x11);
+    let amount = parse_ether("0.06").unwrap();
+
+    app_state
+        .treasury
+        .fund(db, ACCOUNT_ID, wallet, amount, now)
+        .await
+        .unwrap();
+    let res = app_state
+        .treasury
+        .fund(db, ACCOUNT_ID, wallet, amount, now)
+        .await;
+
+    assert!(res.unwrap_err().to_string().contains("per account cap"));
+    assert_eq!(fakes.funding_wallet.transfers().len(), 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_error_when_funding_exceeds_daily_cap() {
+    let (_client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+    let now = fakes.clock.now_ms();
+    let amount = parse_ether("0.09").unwrap();
+
+    for account_id in [ACCOUNT_ID, ANOTHER_ACCOUNT_ID] {
+        app_state
+            .treasury
+            .fund(db, account_id, Address::repeat_byte(0x11), amount, now)
+            .await
+            .unwrap();
+    }
+    let res = app_state
+        .treasury
+        .fund(
+            db,
+            THIRD_ACCOUNT_ID,
+            Address::repeat_byte(0x22),
+            amount,
+            now,
+        )
+        .await;
+    assert!(res.unwrap_err().to_string().contains("daily cap"));
+
+    let a_day_later = now + 24 * 60 * 60 * 1000 + 1;
+    let res = app_state
+        .treasury
+        .fund(
+            db,
+            THIRD_ACCOUNT_ID,
+            Address::repeat_byte(0x22),
+            amount,
+            a_day_later,
+        )
+        .await;
+    assert!(res.is_ok());
+    assert_eq!(fakes.funding_wallet.transfers().len(), 3);
+    assert_eq!(
+        fakes.funding_wallet.transfers()[2],
+        (Address::repeat_byte(0x22), amount)
+    );
+
+    tear_down(db_url).await;
+}
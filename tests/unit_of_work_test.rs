@@ -0,0 +1,91 @@
+use lib::{
+    repos::{account_repo, guardian_repo, unit_of_work::UnitOfWork},
+    test::utils::{setup, tear_down},
+};
+use sea_orm::DatabaseConnection;
+use uuid::Uuid;
+
+const EMAIL: &str = "someone@example.com";
+const GUARDIAN_EMAIL: &str = "guardian@example.com";
+
+#[tokio::test]
+async fn test_commit_unit_of_work() {
+    let (_client, app_state, db_url) = setup().await;
+    let db = &app_state.database;
+
+    let uow = UnitOfWork::begin(db).await.unwrap();
+    let account_id = create_account(&uow).await;
+    create_guardian(&uow, Some(account_id.clone())).await;
+    assert!(
+        guardian_repo::find_by_email(uow.db(), GUARDIAN_EMAIL.to_string())
+            .await
+            .unwrap()
+            .is_some()
+    );
+    uow.commit().await.unwrap();
+
+    assert!(account_repo::find_by_email(db, EMAIL)
+        .await
+        .unwrap()
+        .is_some());
+    let guardian = find_guardian(db).await.expect("guardian not found");
+    assert_eq!(guardian.account_id, Some(account_id));
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_rollback_unit_of_work_when_not_committed() {
+    let (_client, app_state, db_url) = setup().await;
+    let db = &app_state.database;
+
+    let uow = UnitOfWork::begin(db).await.unwrap();
+    let account_id = create_account(&uow).await;
+    create_guardian(&uow, Some(account_id)).await;
+    drop(uow);
+
+    assert!(account_repo::find_by_email(db, EMAIL)
+        .await
+        .unwrap()
+        .is_none());
+    assert!(find_guardian(db).await.is_none());
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn create_account(uow: &UnitOfWork) -> String {
+    let id = Uuid::new_v4();
+    account_repo::create(
+        uow.db(),
+        id,
+        EMAIL.to_string(),
+        "0x1".to_string(),
+        "0x2".to_string(),
+        "private".to_string(),
+        "data_key".to_string(),
+        0,
+    )
+    .await
+    .expect("error creating account");
+    id.to_string()
+}
+
+async fn create_guardian(uow: &UnitOfWork, account_id: Option<String>) {
+    guardian_repo::create(
+        uow.db(),
+        Uuid::new_v4(),
+        GUARDIAN_EMAIL.to_string(),
+        account_id,
+        None,
+    )
+    .await
+    .expect("error creating guardian");
+}
+
+async fn find_guardian(db: &DatabaseConnection) -> Option<guardian_repo::Model> {
+    guardian_repo::find_by_email(db, GUARDIAN_EMAIL.to_string())
+        .await
+        .unwrap()
+}
@@ -0,0 +1,245 @@
+use axum::http::StatusCode;
+use axum_test_helper::TestClient;
+use ethers::types::U256;
+use lib::{
+    jobs::{receipt_tracker::track_receipts, wallet_deployment::process_deployments},
+    operations::time::Clock,
+    repos::{account_repo, deployment_job_repo},
+    test::utils::{
+        create_account, create_verify, mock_bundler, setup_with_fakes, tear_down, Fakes,
+    },
+    utils::convert_to_hex,
+};
+use sea_orm::DatabaseConnection;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+const TRANSACTION_HASH: &str = "0x9999999999999999999999999999999999999999999999999999999999999999";
+const DROP_AFTER_MS: i64 = 30 * 60 * 1000;
+
+#[tokio::test]
+async fn test_deploy_wallet_after_account_is_created() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let account = sign_up(&client, db, &fakes, "someone@example.com").await;
+    assert_eq!(account.deployment_status, "PENDING");
+    assert!(fakes.wallet.sent_user_operations().is_empty());
+
+    let now = fakes.clock.now_ms();
+    process_deployments(&app_state, now).await.unwrap();
+
+    let sent = fakes.wallet.sent_user_operations();
+    assert_eq!(sent.len(), 1);
+    assert_eq!(convert_to_hex(sent[0].sender), account.wallet_address);
+    assert_eq!(sent[0].max_fee_per_gas, U256::from(2_000_000_000_u64));
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "SUBMITTED");
+    assert_eq!(job.attempts, 1);
+    // the wallet isn't deployed until the user op succeeded on chain
+    assert_eq!(
+        find_account(db, &account.id).await.deployment_status,
+        "PENDING"
+    );
+
+    let user_op_hash = job.user_op_hash.expect("user op hash not stored");
+    let bundler = mock_bundler(
+        HashMap::from([(user_op_hash.clone(), receipt(&user_op_hash, true))]),
+        102,
+    )
+    .await;
+    track_receipts(db, &bundler, &bundler, now).await.unwrap();
+
+    assert_eq!(
+        find_account(db, &account.id).await.deployment_status,
+        "DEPLOYED"
+    );
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "DEPLOYED");
+    assert_eq!(job.attempts, 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_retry_wallet_deployment_when_user_op_is_dropped_or_reverted() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let account = sign_up(&client, db, &fakes, "someone@example.com").await;
+    let now = fakes.clock.now_ms();
+    process_deployments(&app_state, now).await.unwrap();
+    let user_op_hash = find_job(db, &account.id).await.user_op_hash.unwrap();
+
+    // the bundler never includes it
+    let bundler = mock_bundler(HashMap::new(), 102).await;
+    let dropped_at = now + DROP_AFTER_MS + 1;
+    track_receipts(db, &bundler, &bundler, dropped_at)
+        .await
+        .unwrap();
+
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "PENDING");
+    assert_eq!(job.attempts, 1);
+    assert_eq!(job.next_attempt_at, dropped_at + 30 * 1000);
+    assert_eq!(
+        job.last_error,
+        Some(format!(
+            "Deployment user operation {} DROPPED",
+            user_op_hash
+        ))
+    );
+
+    process_deployments(&app_state, job.next_attempt_at)
+        .await
+        .unwrap();
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "SUBMITTED");
+    assert_eq!(job.attempts, 2);
+
+    let user_op_hash = job.user_op_hash.unwrap();
+    let bundler = mock_bundler(
+        HashMap::from([(user_op_hash.clone(), receipt(&user_op_hash, false))]),
+        102,
+    )
+    .await;
+    track_receipts(db, &bundler, &bundler, job.next_attempt_at)
+        .await
+        .unwrap();
+
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "PENDING");
+    assert_eq!(job.attempts, 2);
+    assert_eq!(
+        find_account(db, &account.id).await.deployment_status,
+        "PENDING"
+    );
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_fund_wallet_from_treasury_before_deployment() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+
+    let account = sign_up(&client, &app_state.database, &fakes, "someone@example.com").await;
+    let missfund = U256::from(1_000_000_000_000_000_u64);
+    fakes.wallet.set_missfund(missfund);
+
+    process_deployments(&app_state, fakes.clock.now_ms())
+        .await
+        .unwrap();
+
+    let transfers = fakes.funding_wallet.transfers();
+    assert_eq!(transfers.len(), 1);
+    assert_eq!(convert_to_hex(transfers[0].0), account.wallet_address);
+    assert_eq!(transfers[0].1, missfund);
+    assert_eq!(fakes.wallet.sent_user_operations().len(), 1);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_retry_wallet_deployment_after_backoff() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let account = sign_up(&client, db, &fakes, "someone@example.com").await;
+
+    fakes.wallet.set_unavailable(true);
+    let now = fakes.clock.now_ms();
+    process_deployments(&app_state, now).await.unwrap();
+
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "PENDING");
+    assert_eq!(job.attempts, 1);
+    assert_eq!(job.next_attempt_at, now + 30 * 1000);
+    assert_eq!(job.last_error, Some("bundler unavailable".to_string()));
+
+    // still backing off
+    fakes.wallet.set_unavailable(false);
+    process_deployments(&app_state, now + 1000).await.unwrap();
+    assert_eq!(find_job(db, &account.id).await.attempts, 1);
+
+    process_deployments(&app_state, job.next_attempt_at)
+        .await
+        .unwrap();
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "SUBMITTED");
+    assert_eq!(job.attempts, 2);
+    assert_eq!(job.last_error, None);
+
+    tear_down(db_url).await;
+}
+
+#[tokio::test]
+async fn test_fail_wallet_deployment_after_max_attempts() {
+    let (client, app_state, db_url, fakes) = setup_with_fakes().await;
+    let db = &app_state.database;
+
+    let account = sign_up(&client, db, &fakes, "someone@example.com").await;
+
+    fakes.wallet.set_unavailable(true);
+    let mut now = fakes.clock.now_ms();
+    for _ in 0..8 {
+        process_deployments(&app_state, now).await.unwrap();
+        now = find_job(db, &account.id).await.next_attempt_at;
+    }
+
+    let job = find_job(db, &account.id).await;
+    assert_eq!(job.status, "FAILED");
+    assert_eq!(job.attempts, 8);
+    assert_eq!(
+        find_account(db, &account.id).await.deployment_status,
+        "FAILED"
+    );
+
+    tear_down(db_url).await;
+}
+
+// Helper functions
+
+async fn sign_up(
+    client: &TestClient,
+    db: &DatabaseConnection,
+    fakes: &Fakes,
+    email: &str,
+) -> account_repo::Model {
+    let res = create_verify(client, email.to_string()).await;
+    assert_eq!(res.status(), StatusCode::OK);
+    let code = fakes.mailer.last_code_for(email).expect("no code sent");
+
+    let res = create_account(client, email.to_string(), code).await;
+    assert_eq!(res.status(), StatusCode::OK);
+
+    account_repo::find_by_email(db, email)
+        .await
+        .unwrap()
+        .expect("account not found")
+}
+
+async fn find_account(db: &DatabaseConnection, account_id: &str) -> account_repo::Model {
+    account_repo::find_by_id(db, account_id.to_string())
+        .await
+        .unwrap()
+        .expect("account not found")
+}
+
+async fn find_job(db: &DatabaseConnection, account_id: &str) -> deployment_job_repo::Model {
+    deployment_job_repo::find_by_account_id(db, account_id.to_string())
+        .await
+        .unwrap()
+        .expect("deployment job not found")
+}
+
+fn receipt(user_op_hash: &str, success: bool) -> Value {
+    json!({
+        "userOpHash": user_op_hash,
+        "success": success,
+        "actualGasCost": "0x5208",
+        "receipt": {
+            "transactionHash": TRANSACTION_HASH,
+            "blockNumber": "0x64",
+        },
+    })
+}